@@ -1,5 +1,6 @@
 use std::cmp::max;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::time::Duration;
 
@@ -8,15 +9,20 @@ use anyhow::Context;
 use anyhow::Result;
 use csv::Writer;
 use gourd_lib::bailc;
+use gourd_lib::constants::CUSTOM_METRICS_ENV;
 use gourd_lib::constants::PLOT_SIZE;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::experiment::FieldRef;
+use gourd_lib::measurement::read_samples;
+use gourd_lib::measurement::Counters;
+use gourd_lib::measurement::Measurement;
 use gourd_lib::measurement::RUsage;
 use log::debug;
 use plotters::prelude::*;
 use plotters::style::register_font;
 use plotters::style::BLACK;
 
+use crate::post::agreement::InputAgreement;
 use crate::status::FileSystemBasedStatus;
 use crate::status::FsState;
 use crate::status::SlurmBasedStatus;
@@ -25,19 +31,59 @@ use crate::status::Status;
 /// Plot width, size, and data to plot.
 type PlotData = (u128, u128, BTreeMap<FieldRef, Vec<(u128, u128)>>);
 
+/// The sorted values of a custom metric for every program.
+type MetricData = BTreeMap<FieldRef, Vec<f64>>;
+
+/// The memory usage over time (seconds, megabytes) of every run, per program.
+type MemoryData = BTreeMap<FieldRef, Vec<Vec<(f64, f64)>>>;
+
 /// Collect and export metrics.
-pub fn analysis_csv(path: &Path, statuses: BTreeMap<usize, Status>) -> Result<()> {
+pub fn analysis_csv(
+    path: &Path,
+    experiment: &Experiment,
+    statuses: BTreeMap<usize, Status>,
+) -> Result<()> {
     let mut writer = Writer::from_path(path)?;
 
-    let header = vec![
+    let custom_metrics = get_custom_metric_names(&statuses);
+    let has_counters = statuses.values().any(|status| {
+        matches!(
+            &status.fs_status.completion,
+            FsState::Completed(Measurement {
+                counters: Some(_),
+                ..
+            })
+        )
+    });
+
+    let has_verdicts = statuses
+        .values()
+        .any(|status| status.fs_status.verdict.is_some());
+
+    let mut header = vec![
         "id".to_string(),
+        "program".to_string(),
+        "input".to_string(),
+        "repetition".to_string(),
         "file system status".to_string(),
         "wall micros".to_string(),
         "exit code".to_string(),
+        "termination".to_string(),
         "RUsage".to_string(),
         "afterscript output".to_string(),
         "slurm completion".to_string(),
     ];
+    if has_verdicts {
+        header.push("verdict".to_string());
+    }
+    if has_counters {
+        header.extend(
+            Counters::default()
+                .named()
+                .map(|(name, _)| name.to_string()),
+        );
+    }
+    header.extend(custom_metrics.iter().cloned());
 
     writer.write_record(header)?;
 
@@ -46,10 +92,27 @@ pub fn analysis_csv(path: &Path, statuses: BTreeMap<usize, Status>) -> Result<()
         let slurm_status = status.slurm_status;
 
         let mut record = get_fs_status_info(id, fs_status);
+        record.splice(1..1, get_run_info(experiment, id)?);
         record.append(&mut get_afterscript_output_info(
             &status.fs_status.afterscript_completion,
         ));
         record.append(&mut get_slurm_status_info(&slurm_status));
+        if has_verdicts {
+            record.push(
+                status
+                    .fs_status
+                    .verdict
+                    .map(|verdict| verdict.to_string())
+                    .unwrap_or("...".to_string()),
+            );
+        }
+        if has_counters {
+            record.append(&mut get_counters_info(&status.fs_status));
+        }
+        record.append(&mut get_custom_metrics_info(
+            &status.fs_status,
+            &custom_metrics,
+        ));
 
         writer.write_record(record)?;
     }
@@ -59,26 +122,92 @@ pub fn analysis_csv(path: &Path, statuses: BTreeMap<usize, Status>) -> Result<()
     Ok(())
 }
 
+/// Export how the programs agree on the output of every input.
+///
+/// Every compared run has a row with the output it belongs to, numbered per
+/// input, and whether that output is the consensus.
+pub fn analysis_agreement_csv(
+    path: &Path,
+    experiment: &Experiment,
+    agreements: &[InputAgreement],
+) -> Result<()> {
+    let mut writer = Writer::from_path(path)?;
+
+    writer.write_record([
+        "id",
+        "program",
+        "input",
+        "repetition",
+        "output",
+        "agreement",
+    ])?;
+
+    for agreement in agreements {
+        for (index, class) in agreement.classes.iter().enumerate() {
+            for run in class {
+                let mut record = vec![run.to_string()];
+                record.append(&mut get_run_info(experiment, *run)?);
+                record.push((index + 1).to_string());
+                record.push(
+                    match agreement.dissents(*run) {
+                        Some(false) => "agrees",
+                        Some(true) => "differs",
+                        None => "no consensus",
+                    }
+                    .to_string(),
+                );
+
+                writer.write_record(record)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
 /// Gets file system info for CSV.
 pub fn get_fs_status_info(id: usize, fs_status: &FileSystemBasedStatus) -> Vec<String> {
-    let mut completion = match fs_status.completion {
+    let mut completion = match &fs_status.completion {
         FsState::Pending => vec![
             "pending".to_string(),
             "...".to_string(),
             "...".to_string(),
             "...".to_string(),
+            "...".to_string(),
         ],
         FsState::Running => vec![
             "running".to_string(),
             "...".to_string(),
             "...".to_string(),
             "...".to_string(),
+            "...".to_string(),
+        ],
+        FsState::Cancelled => vec![
+            "cancelled".to_string(),
+            "...".to_string(),
+            "...".to_string(),
+            "...".to_string(),
+            "...".to_string(),
+        ],
+        FsState::WrapperFailed(reason) => vec![
+            "wrapper failed".to_string(),
+            "...".to_string(),
+            "...".to_string(),
+            reason.clone(),
+            "...".to_string(),
         ],
         FsState::Completed(measurement) => {
             vec![
                 "completed".to_string(),
                 format!("{:?}", measurement.wall_micros),
-                format!("{:?}", measurement.exit_code),
+                measurement
+                    .termination
+                    .exit_code()
+                    .map(|code| code.to_string())
+                    .unwrap_or("...".to_string()),
+                measurement.termination.to_string(),
                 format_rusage(measurement.rusage),
             ]
         }
@@ -90,6 +219,73 @@ pub fn get_fs_status_info(id: usize, fs_status: &FileSystemBasedStatus) -> Vec<S
     res
 }
 
+/// Gets the program, input and repetition of a run for CSV.
+pub fn get_run_info(experiment: &Experiment, id: usize) -> Result<Vec<String>> {
+    let run = experiment
+        .runs
+        .get(id)
+        .ok_or_else(|| anyhow!("Run {id} is not part of the experiment"))?;
+
+    Ok(vec![
+        experiment.get_program(run)?.name,
+        run.generated_from_input
+            .clone()
+            .unwrap_or("...".to_string()),
+        run.repetition
+            .map(|repetition| repetition.to_string())
+            .unwrap_or("...".to_string()),
+    ])
+}
+
+/// Gets the performance counters of a run for CSV.
+pub fn get_counters_info(fs_status: &FileSystemBasedStatus) -> Vec<String> {
+    match &fs_status.completion {
+        FsState::Completed(Measurement {
+            counters: Some(counters),
+            ..
+        }) => counters
+            .named()
+            .map(|(_, value)| {
+                value
+                    .map(|value| value.to_string())
+                    .unwrap_or("unavailable".to_string())
+            })
+            .to_vec(),
+        _ => vec!["...".to_string(); Counters::default().named().len()],
+    }
+}
+
+/// Get the names of all custom metrics reported by the runs.
+pub fn get_custom_metric_names(statuses: &BTreeMap<usize, Status>) -> BTreeSet<String> {
+    statuses
+        .values()
+        .filter_map(|status| match &status.fs_status.completion {
+            FsState::Completed(measurement) => Some(measurement.custom_metrics.keys()),
+            _ => None,
+        })
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Gets the custom metrics of a run for CSV, in the order of `names`.
+pub fn get_custom_metrics_info(
+    fs_status: &FileSystemBasedStatus,
+    names: &BTreeSet<String>,
+) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| match &fs_status.completion {
+            FsState::Completed(measurement) => measurement
+                .custom_metrics
+                .get(name)
+                .map(|value| value.to_string())
+                .unwrap_or("...".to_string()),
+            _ => "...".to_string(),
+        })
+        .collect()
+}
+
 /// Formats RUsage of a run for the CSV.
 pub fn format_rusage(rusage: Option<RUsage>) -> String {
     if rusage.is_some() {
@@ -122,12 +318,36 @@ pub fn get_afterscript_output_info(afterscript_completion: &Option<Option<String
 }
 
 /// Get data for plotting and generate plots.
+///
+/// If a custom `metric` is given, its values are plotted instead of the cpu
+/// time of the runs.
 pub fn analysis_plot(
     path: &Path,
     statuses: BTreeMap<usize, Status>,
     experiment: Experiment,
+    metric: Option<&str>,
     is_png: bool,
 ) -> Result<()> {
+    if let Some(metric) = metric {
+        let data = get_custom_metric_values(&statuses, &experiment, metric)?;
+
+        if data.is_empty() {
+            bailc!(
+                "No completed run reported the metric {metric:?}", ;
+                "", ;
+                "Programs report custom metrics to the file in ${CUSTOM_METRICS_ENV}",
+            );
+        }
+
+        if is_png {
+            make_metric_plot(metric, data, BitMapBackend::new(&path, PLOT_SIZE))?;
+        } else {
+            make_metric_plot(metric, data, SVGBackend::new(&path, PLOT_SIZE))?;
+        }
+
+        return Ok(());
+    }
+
     let completions = get_completions(statuses, experiment)?;
 
     let data = get_data_for_plot(completions);
@@ -141,6 +361,58 @@ pub fn analysis_plot(
     Ok(())
 }
 
+/// Plot the memory usage over time of every run that was sampled.
+pub fn analysis_memory_plot(path: &Path, experiment: &Experiment, is_png: bool) -> Result<()> {
+    let data = get_memory_series(experiment)?;
+
+    if data.is_empty() {
+        bailc!(
+            "No run has resource usage samples", ;
+            "", ;
+            "Set sampling_interval in the configuration to sample the runs",
+        );
+    }
+
+    if is_png {
+        make_memory_plot(data, BitMapBackend::new(&path, PLOT_SIZE))?;
+    } else {
+        make_memory_plot(data, SVGBackend::new(&path, PLOT_SIZE))?;
+    }
+
+    Ok(())
+}
+
+/// Get the memory usage over time of every run that was sampled.
+pub fn get_memory_series(experiment: &Experiment) -> Result<MemoryData> {
+    let mut data: MemoryData = BTreeMap::new();
+
+    for (id, run) in experiment.runs.iter().enumerate() {
+        let Some(samples_path) = &run.samples_path else {
+            continue;
+        };
+
+        let samples = match read_samples(samples_path) {
+            Ok(samples) if !samples.is_empty() => samples,
+            Ok(_) => continue,
+            Err(e) => {
+                debug!("Could not read the samples of run {id}: {e:?}");
+                continue;
+            }
+        };
+
+        data.entry(experiment.program_from_run_id(id)?.name)
+            .or_default()
+            .push(
+                samples
+                    .iter()
+                    .map(|s| (s.millis as f64 / 1000.0, s.rss_kb as f64 / 1024.0))
+                    .collect(),
+            );
+    }
+
+    Ok(data)
+}
+
 /// Get completion times of jobs.
 pub fn get_completions(
     statuses: BTreeMap<usize, Status>,
@@ -174,6 +446,32 @@ pub fn get_completions(
     Ok(completions)
 }
 
+/// Get the values of a custom metric for every program, sorted.
+pub fn get_custom_metric_values(
+    statuses: &BTreeMap<usize, Status>,
+    experiment: &Experiment,
+    metric: &str,
+) -> Result<MetricData> {
+    let mut values: MetricData = BTreeMap::new();
+
+    for (id, status) in statuses {
+        if let FsState::Completed(measurement) = &status.fs_status.completion {
+            if let Some(value) = measurement.custom_metrics.get(metric) {
+                values
+                    .entry(experiment.program_from_run_id(*id)?.name)
+                    .or_default()
+                    .push(*value);
+            }
+        }
+    }
+
+    for program in values.values_mut() {
+        program.sort_by(f64::total_cmp);
+    }
+
+    Ok(values)
+}
+
 /// Get completion time of a run.
 pub fn get_completion_time(state: FsState) -> Result<Duration> {
     match state {
@@ -237,14 +535,7 @@ where
 
     let (max_time, max_count, cactus_data) = plot_data;
 
-    register_font(
-        "sans-serif",
-        FontStyle::Normal,
-        include_bytes!("../../resources/LinLibertine_R.otf"),
-    )
-    .map_err(|_| anyhow!("Could not load the font"))?;
-
-    let style = TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK);
+    let style = plot_text_style()?;
     let root = backend.into_drawing_area();
 
     root.fill(&WHITE)?;
@@ -288,6 +579,148 @@ where
     Ok(())
 }
 
+/// Plot the sorted values of a custom metric for every program.
+pub fn make_metric_plot<T>(metric: &str, data: MetricData, backend: T) -> Result<()>
+where
+    T: DrawingBackend,
+    <T as DrawingBackend>::ErrorType: 'static,
+{
+    debug!("Drawing a plot of {metric}");
+
+    let max_count = data.values().map(Vec::len).max().unwrap_or(0);
+    let (min_value, max_value) = data
+        .values()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(*value), high.max(*value))
+        });
+
+    // Keep a flat line visible when all values are the same.
+    let margin = ((max_value - min_value) / 20.0).max(1.0);
+
+    let style = plot_text_style()?;
+    let root = backend.into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .caption(metric, 40)
+        .build_cartesian_2d(0..max_count + 1, min_value - margin..max_value + margin)?;
+
+    chart
+        .configure_mesh()
+        .light_line_style(WHITE)
+        .x_label_style(style.clone())
+        .y_label_style(style.clone())
+        .label_style(style.clone())
+        .x_desc("Runs")
+        .y_desc(metric)
+        .draw()?;
+
+    for (idx, (name, values)) in (0..).zip(data) {
+        chart
+            .draw_series(LineSeries::new(
+                (1..).zip(values),
+                Into::<ShapeStyle>::into(Palette99::pick(idx)).stroke_width(3),
+            ))?
+            .label(name.to_string())
+            .legend(move |(x, y)| {
+                Rectangle::new(
+                    [(x - 5, y - 5), (x + 5, y + 5)],
+                    Palette99::pick(idx).stroke_width(5),
+                )
+            });
+    }
+
+    chart.configure_series_labels().label_font(style).draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+/// Plot the memory usage over time of every run, coloured by program.
+pub fn make_memory_plot<T>(data: MemoryData, backend: T) -> Result<()>
+where
+    T: DrawingBackend,
+    <T as DrawingBackend>::ErrorType: 'static,
+{
+    debug!("Drawing a memory plot");
+
+    let (max_seconds, max_megabytes) = data
+        .values()
+        .flatten()
+        .flatten()
+        .fold((0.0f64, 0.0f64), |(x, y), (seconds, megabytes)| {
+            (x.max(*seconds), y.max(*megabytes))
+        });
+
+    let style = plot_text_style()?;
+    let root = backend.into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .caption("Memory usage", 40)
+        .build_cartesian_2d(
+            0.0..max_seconds * 1.05 + 0.1,
+            0.0..max_megabytes * 1.05 + 1.0,
+        )?;
+
+    chart
+        .configure_mesh()
+        .light_line_style(WHITE)
+        .x_label_style(style.clone())
+        .y_label_style(style.clone())
+        .label_style(style.clone())
+        .x_desc("Seconds")
+        .y_desc("Megabytes")
+        .draw()?;
+
+    for (idx, (name, runs)) in (0..).zip(data) {
+        for (run, series) in runs.into_iter().enumerate() {
+            let drawn = chart.draw_series(LineSeries::new(
+                series,
+                Into::<ShapeStyle>::into(Palette99::pick(idx)).stroke_width(2),
+            ))?;
+
+            // One legend entry per program, not per run.
+            if run == 0 {
+                drawn.label(name.to_string()).legend(move |(x, y)| {
+                    Rectangle::new(
+                        [(x - 5, y - 5), (x + 5, y + 5)],
+                        Palette99::pick(idx).stroke_width(5),
+                    )
+                });
+            }
+        }
+    }
+
+    chart.configure_series_labels().label_font(style).draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+/// Load the font of the plots and get the style of their text.
+fn plot_text_style() -> Result<TextStyle<'static>> {
+    register_font(
+        "sans-serif",
+        FontStyle::Normal,
+        include_bytes!("../../resources/LinLibertine_R.otf"),
+    )
+    .map_err(|_| anyhow!("Could not load the font"))?;
+
+    Ok(TextStyle::from(("sans-serif", 20).into_font()).color(&BLACK))
+}
+
 #[cfg(test)]
 #[path = "tests/mod.rs"]
 mod tests;
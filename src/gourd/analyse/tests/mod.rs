@@ -5,14 +5,22 @@ use std::time::Duration;
 
 use csv::Reader;
 use csv::StringRecord;
+use gourd_lib::config::compare::Agreement;
+use gourd_lib::config::compare::Comparison;
 use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::InternalProgram;
 use gourd_lib::experiment::Run;
 use gourd_lib::experiment::RunInput;
+use gourd_lib::file_system::FileSystemInteractor;
 use gourd_lib::measurement::Measurement;
+use gourd_lib::measurement::Termination;
 use tempdir::TempDir;
 
 use super::*;
+use crate::post::agreement::check_agreement;
+use crate::post::agreement::InputAgreement;
+use crate::post::verdict::Verdict;
+use crate::status::LabelSource;
 use crate::status::SlurmState;
 
 static TEST_RUSAGE: RUsage = RUsage {
@@ -34,6 +42,61 @@ static TEST_RUSAGE: RUsage = RUsage {
     nivcsw: 2137,
 };
 
+/// An experiment with `runs` runs of a single program.
+fn test_experiment(runs: usize) -> Experiment {
+    let run = Run {
+        program: 0,
+        input: RunInput {
+            file: None,
+            arguments: Vec::new(),
+            files: BTreeMap::new(),
+            generator: None,
+            env: BTreeMap::new(),
+        },
+        err_path: Default::default(),
+        output_path: Default::default(),
+        metrics_path: Default::default(),
+        custom_metrics_path: None,
+        samples_path: None,
+        work_dir: Default::default(),
+        slurm_id: None,
+        afterscript_output_path: None,
+        rerun: None,
+        generated_from_input: None,
+        parent: None,
+        limits: Default::default(),
+        group: None,
+        repetition: None,
+        seed: None,
+    };
+
+    Experiment {
+        runs: vec![run; runs],
+        resource_limits: None,
+        creation_time: Default::default(),
+        home: Default::default(),
+        wrapper: "".to_string(),
+        inputs: Default::default(),
+        programs: vec![InternalProgram::default()],
+        output_folder: Default::default(),
+        metrics_folder: Default::default(),
+        seq: 0,
+        env: Environment::Local,
+        labels: Default::default(),
+        afterscript_output_folder: Default::default(),
+        slurm: None,
+        chunks: vec![],
+        groups: vec![],
+        sampling_interval: None,
+        performance_counters: false,
+        seed: None,
+        compare: Comparison::default(),
+        agreement: None,
+        clear_env: false,
+        tuning: None,
+    }
+}
+
 #[test]
 fn test_analysis_csv_unwritable() {
     let tmp_dir = TempDir::new("testing").unwrap();
@@ -43,7 +106,7 @@ fn test_analysis_csv_unwritable() {
     // By creating a directory, the path becomes unwritable
     let _ = fs::create_dir(&output_path);
 
-    assert!(analysis_csv(&output_path, BTreeMap::new()).is_err());
+    assert!(analysis_csv(&output_path, &test_experiment(0), BTreeMap::new()).is_err());
 }
 
 #[test]
@@ -60,6 +123,8 @@ fn test_analysis_csv_success() {
             fs_status: FileSystemBasedStatus {
                 completion: crate::status::FsState::Pending,
                 afterscript_completion: Some(Some(String::from("lol-label"))),
+                label_source: Some(LabelSource::Afterscript),
+                verdict: None,
             },
             slurm_status: None,
         },
@@ -72,10 +137,17 @@ fn test_analysis_csv_success() {
             fs_status: FileSystemBasedStatus {
                 completion: FsState::Completed(Measurement {
                     wall_micros: Duration::from_nanos(0),
-                    exit_code: 0,
+                    termination: Termination::Exited { code: 0 },
                     rusage: None,
+                    limit_exceeded: None,
+                    counters: None,
+                    custom_metrics: BTreeMap::new(),
+                    setup: None,
+                    teardown: None,
                 }),
                 afterscript_completion: None,
+                label_source: None,
+                verdict: None,
             },
             slurm_status: Some(SlurmBasedStatus {
                 completion: SlurmState::Success,
@@ -85,17 +157,28 @@ fn test_analysis_csv_success() {
         },
     );
 
-    analysis_csv(&output_path, statuses).unwrap();
+    let mut experiment = test_experiment(2);
+    experiment.programs[0].name = "solver".to_string();
+    experiment.runs[0].generated_from_input = Some("small".to_string());
+    experiment.runs[0].repetition = Some(0);
+    experiment.runs[1].generated_from_input = Some("small".to_string());
+    experiment.runs[1].repetition = Some(1);
+
+    analysis_csv(&output_path, &experiment, statuses).unwrap();
 
     let mut reader = Reader::from_path(output_path).unwrap();
 
     let res1 = reader.records().next();
     let ans1 = StringRecord::from(vec![
+        "0",
+        "solver",
+        "small",
         "0",
         "pending",
         "...",
         "...",
         "...",
+        "...",
         "lol-label",
         "...",
     ]);
@@ -103,10 +186,14 @@ fn test_analysis_csv_success() {
 
     let res2 = reader.records().next();
     let ans2 = StringRecord::from(vec![
+        "1",
+        "solver",
+        "small",
         "1",
         "completed",
         "0ns",
         "0",
+        "exit code 0",
         "none",
         "no afterscript",
         "Success",
@@ -116,6 +203,156 @@ fn test_analysis_csv_success() {
     assert!(tmp_dir.close().is_ok());
 }
 
+#[test]
+fn test_analysis_csv_verdict() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+
+    let output_path = tmp_dir.path().join("analysis.csv");
+    let mut statuses = BTreeMap::new();
+
+    for (id, verdict) in [(0, Some(Verdict::Incorrect)), (1, None)] {
+        statuses.insert(
+            id,
+            Status {
+                slurm_file_text: None,
+                fs_status: FileSystemBasedStatus {
+                    completion: FsState::Pending,
+                    afterscript_completion: None,
+                    label_source: None,
+                    verdict,
+                },
+                slurm_status: None,
+            },
+        );
+    }
+
+    analysis_csv(&output_path, &test_experiment(2), statuses).unwrap();
+
+    let mut reader = Reader::from_path(output_path).unwrap();
+
+    assert_eq!(
+        reader.headers().unwrap().iter().next_back(),
+        Some("verdict")
+    );
+
+    let verdicts: Vec<String> = reader
+        .records()
+        .map(|record| record.unwrap().iter().next_back().unwrap().to_string())
+        .collect();
+
+    assert_eq!(verdicts, vec!["incorrect", "..."]);
+
+    assert!(tmp_dir.close().is_ok());
+}
+
+#[test]
+fn test_analysis_agreement_csv() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+
+    let mut experiment = test_experiment(5);
+    experiment.agreement = Some(Agreement::default());
+    experiment.compare = Comparison::Whitespace;
+    experiment.programs = ["dijkstra", "bellman_ford", "broken"]
+        .into_iter()
+        .map(|name| InternalProgram {
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut statuses = BTreeMap::new();
+
+    // Run 3 failed and run 4 was rerun, so neither is compared.
+    for (id, (program, output, succeeded)) in [
+        (0, "1 2 3", true),
+        (1, "1  2\n3", true),
+        (2, "1 2 4", true),
+        (0, "", false),
+        (2, "1 2 3", true),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let run = &mut experiment.runs[id];
+        run.program = program;
+        run.generated_from_input = Some("graph".to_string());
+        run.output_path = tmp_dir.path().join(format!("output_{id}"));
+        run.work_dir = tmp_dir.path().to_path_buf();
+        fs::write(&run.output_path, output).unwrap();
+
+        statuses.insert(
+            id,
+            Status {
+                slurm_file_text: None,
+                fs_status: FileSystemBasedStatus {
+                    completion: FsState::Completed(Measurement {
+                        wall_micros: Duration::from_nanos(0),
+                        termination: Termination::Exited {
+                            code: if succeeded { 0 } else { 1 },
+                        },
+                        rusage: None,
+                        limit_exceeded: None,
+                        counters: None,
+                        custom_metrics: BTreeMap::new(),
+                        setup: None,
+                        teardown: None,
+                    }),
+                    afterscript_completion: None,
+                    label_source: None,
+                    verdict: None,
+                },
+                slurm_status: None,
+            },
+        );
+    }
+    experiment.runs[4].rerun = Some(2);
+
+    let fs = FileSystemInteractor { dry_run: false };
+    let agreements = check_agreement(&experiment, &statuses, &fs).unwrap();
+
+    assert_eq!(
+        agreements,
+        vec![InputAgreement {
+            input: "graph".to_string(),
+            classes: vec![vec![0, 1], vec![2]],
+            consensus: Some(0),
+        }]
+    );
+    assert_eq!(agreements[0].dissents(2), Some(true));
+    assert_eq!(agreements[0].dissents(3), None);
+
+    let output_path = tmp_dir.path().join("agreement.csv");
+    analysis_agreement_csv(&output_path, &experiment, &agreements).unwrap();
+
+    let mut reader = Reader::from_path(output_path).unwrap();
+
+    assert_eq!(
+        reader.records().nth(2).unwrap().unwrap(),
+        StringRecord::from(vec!["2", "broken", "graph", "...", "2", "differs"])
+    );
+
+    // With a reference, its output is the consensus even in the minority.
+    experiment.agreement = Some(Agreement {
+        reference: Some("broken".to_string()),
+    });
+
+    let agreements = check_agreement(&experiment, &statuses, &fs).unwrap();
+
+    assert_eq!(agreements[0].consensus, Some(1));
+    assert_eq!(agreements[0].dissents(0), Some(true));
+
+    // Without one, a tie has no consensus.
+    experiment.agreement = Some(Agreement::default());
+    experiment.runs[1].rerun = Some(4);
+
+    let agreements = check_agreement(&experiment, &statuses, &fs).unwrap();
+
+    assert_eq!(agreements[0].consensus, None);
+    assert_eq!(agreements[0].dissents(0), None);
+
+    assert!(tmp_dir.close().is_ok());
+}
+
 #[test]
 fn test_analysis_png_plot_success() {
     let tmp_dir = TempDir::new("testing").unwrap();
@@ -125,10 +362,17 @@ fn test_analysis_png_plot_success() {
         fs_status: FileSystemBasedStatus {
             completion: FsState::Completed(Measurement {
                 wall_micros: Duration::from_nanos(0),
-                exit_code: 0,
+                termination: Termination::Exited { code: 0 },
                 rusage: Some(TEST_RUSAGE),
+                limit_exceeded: None,
+                counters: None,
+                custom_metrics: BTreeMap::new(),
+                setup: None,
+                teardown: None,
             }),
             afterscript_completion: None,
+            label_source: None,
+            verdict: None,
         },
         slurm_status: Some(SlurmBasedStatus {
             completion: SlurmState::Success,
@@ -139,8 +383,13 @@ fn test_analysis_png_plot_success() {
     let mut status_no_rusage = status_with_rusage.clone();
     status_no_rusage.fs_status.completion = FsState::Completed(Measurement {
         wall_micros: Duration::from_nanos(0),
-        exit_code: 0,
+        termination: Termination::Exited { code: 0 },
         rusage: None,
+        limit_exceeded: None,
+        counters: None,
+        custom_metrics: BTreeMap::new(),
+        setup: None,
+        teardown: None,
     });
     statuses.insert(
         0,
@@ -148,6 +397,8 @@ fn test_analysis_png_plot_success() {
             fs_status: FileSystemBasedStatus {
                 completion: crate::status::FsState::Pending,
                 afterscript_completion: Some(Some(String::from("lol-label"))),
+                label_source: Some(LabelSource::Afterscript),
+                verdict: None,
             },
             slurm_status: None,
             slurm_file_text: None,
@@ -156,51 +407,23 @@ fn test_analysis_png_plot_success() {
     statuses.insert(1, status_no_rusage);
     statuses.insert(2, status_with_rusage.clone());
     statuses.insert(3, status_with_rusage);
-    let run = Run {
-        program: 0,
-        input: RunInput {
-            file: None,
-            arguments: Vec::new(),
-        },
-        err_path: Default::default(),
-        output_path: Default::default(),
-        metrics_path: Default::default(),
-        work_dir: Default::default(),
-        slurm_id: None,
-        afterscript_output_path: None,
-        rerun: None,
-        generated_from_input: None,
-        parent: None,
-        limits: Default::default(),
-        group: None,
-    };
-    let experiment = Experiment {
-        runs: vec![run.clone(), run.clone(), run.clone(), run],
-        resource_limits: None,
-        creation_time: Default::default(),
-        home: Default::default(),
-        wrapper: "".to_string(),
-        inputs: Default::default(),
-        programs: vec![InternalProgram::default()],
-        output_folder: Default::default(),
-        metrics_folder: Default::default(),
-        seq: 0,
-        env: Environment::Local,
-        labels: Default::default(),
-        afterscript_output_folder: Default::default(),
-        slurm: None,
-        chunks: vec![],
-        groups: vec![],
-    };
+    let experiment = test_experiment(4);
 
     let png_output_path = tmp_dir.path().join("analysis.png");
-    analysis_plot(&png_output_path, statuses.clone(), experiment.clone(), true).unwrap();
+    analysis_plot(
+        &png_output_path,
+        statuses.clone(),
+        experiment.clone(),
+        None,
+        true,
+    )
+    .unwrap();
 
     assert!(&png_output_path.exists());
     assert!(fs::read(&png_output_path).is_ok_and(|r| !r.is_empty()));
 
     let svg_output_path = tmp_dir.path().join("analysis.svg");
-    analysis_plot(&svg_output_path, statuses, experiment, false).unwrap();
+    analysis_plot(&svg_output_path, statuses, experiment, None, false).unwrap();
 
     assert!(&svg_output_path.exists());
     assert!(fs::read(&svg_output_path).is_ok_and(|r| !r.is_empty()));
@@ -213,7 +436,7 @@ fn test_analysis_csv_wrong_path() {
     let output_path = tmp_dir.path().join("");
     let statuses = BTreeMap::new();
 
-    assert!(analysis_csv(&output_path, statuses).is_err());
+    assert!(analysis_csv(&output_path, &test_experiment(0), statuses).is_err());
     assert!(tmp_dir.close().is_ok());
 }
 
@@ -222,9 +445,11 @@ fn test_get_fs_status_info_pending() {
     let fs_status = FileSystemBasedStatus {
         completion: FsState::Pending,
         afterscript_completion: None,
+        label_source: None,
+        verdict: None,
     };
     let res = get_fs_status_info(0, &fs_status);
-    assert_eq!(res, vec!["0", "pending", "...", "...", "..."]);
+    assert_eq!(res, vec!["0", "pending", "...", "...", "...", "..."]);
 }
 
 #[test]
@@ -232,9 +457,11 @@ fn test_get_fs_status_info_running() {
     let fs_status = FileSystemBasedStatus {
         completion: FsState::Running,
         afterscript_completion: None,
+        label_source: None,
+        verdict: None,
     };
     let res = get_fs_status_info(0, &fs_status);
-    assert_eq!(res, vec!["0", "running", "...", "...", "..."]);
+    assert_eq!(res, vec!["0", "running", "...", "...", "...", "..."]);
 }
 
 #[test]
@@ -242,13 +469,58 @@ fn test_get_fs_status_info_completed() {
     let fs_status = FileSystemBasedStatus {
         completion: FsState::Completed(Measurement {
             wall_micros: Duration::from_nanos(20),
-            exit_code: 0,
+            termination: Termination::Exited { code: 0 },
             rusage: None,
+            limit_exceeded: None,
+            counters: None,
+            custom_metrics: BTreeMap::new(),
+            setup: None,
+            teardown: None,
         }),
         afterscript_completion: None,
+        label_source: None,
+        verdict: None,
     };
     let res = get_fs_status_info(0, &fs_status);
-    assert_eq!(res, vec!["0", "completed", "20ns", "0", "none"]);
+    assert_eq!(
+        res,
+        vec!["0", "completed", "20ns", "0", "exit code 0", "none"]
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_get_fs_status_info_signalled() {
+    let fs_status = FileSystemBasedStatus {
+        completion: FsState::Completed(Measurement {
+            wall_micros: Duration::from_nanos(20),
+            termination: Termination::Signalled {
+                signal: libc::SIGSEGV,
+                core_dumped: true,
+            },
+            rusage: None,
+            limit_exceeded: None,
+            counters: None,
+            custom_metrics: BTreeMap::new(),
+            setup: None,
+            teardown: None,
+        }),
+        afterscript_completion: None,
+        label_source: None,
+        verdict: None,
+    };
+    let res = get_fs_status_info(0, &fs_status);
+    assert_eq!(
+        res,
+        vec![
+            "0",
+            "completed",
+            "20ns",
+            "...",
+            "killed by SIGSEGV (core dumped)",
+            "none"
+        ]
+    );
 }
 
 #[test]
@@ -295,8 +567,13 @@ fn test_get_afterscript_output_info() {
 fn test_get_completion_time() {
     let state = FsState::Completed(Measurement {
         wall_micros: Duration::from_nanos(20),
-        exit_code: 0,
+        termination: Termination::Exited { code: 0 },
         rusage: Some(TEST_RUSAGE),
+        limit_exceeded: None,
+        counters: None,
+        custom_metrics: BTreeMap::new(),
+        setup: None,
+        teardown: None,
     });
     let res = get_completion_time(state).unwrap();
 
@@ -350,3 +627,112 @@ fn test_make_plot() {
 
     assert!(make_plot((5, 3, data), BitMapBackend::new(&output_path, (300, 300))).is_ok());
 }
+
+#[test]
+fn test_get_custom_metrics_info() {
+    let mut custom_metrics = BTreeMap::new();
+    custom_metrics.insert("nodes".to_string(), 1200.0);
+
+    let completed = FileSystemBasedStatus {
+        completion: FsState::Completed(Measurement {
+            wall_micros: Duration::from_nanos(20),
+            termination: Termination::Exited { code: 0 },
+            rusage: None,
+            limit_exceeded: None,
+            counters: None,
+            custom_metrics,
+            setup: None,
+            teardown: None,
+        }),
+        afterscript_completion: None,
+        label_source: None,
+        verdict: None,
+    };
+    let running = FileSystemBasedStatus {
+        completion: FsState::Running,
+        afterscript_completion: None,
+        label_source: None,
+        verdict: None,
+    };
+
+    let names = BTreeSet::from(["nodes".to_string(), "objective".to_string()]);
+
+    assert_eq!(
+        get_custom_metrics_info(&completed, &names),
+        vec!["1200", "..."]
+    );
+    assert_eq!(
+        get_custom_metrics_info(&running, &names),
+        vec!["...", "..."]
+    );
+}
+
+#[test]
+fn test_make_metric_plot() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+    let output_path = tmp_dir.path().join("plot.png");
+
+    let mut data: BTreeMap<FieldRef, Vec<f64>> = BTreeMap::new();
+    data.insert("first".to_string(), vec![-1.5, 2.0, 2.0]);
+    data.insert("second".to_string(), vec![3.0]);
+
+    assert!(make_metric_plot(
+        "objective",
+        data,
+        BitMapBackend::new(&output_path, (300, 300))
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_make_memory_plot() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+    let output_path = tmp_dir.path().join("plot.png");
+
+    let mut data: BTreeMap<FieldRef, Vec<Vec<(f64, f64)>>> = BTreeMap::new();
+    data.insert(
+        "first".to_string(),
+        vec![
+            vec![(0.0, 1.0), (0.1, 20.0), (0.2, 35.5)],
+            vec![(0.0, 2.0), (0.1, 4.0)],
+        ],
+    );
+    data.insert("second".to_string(), vec![vec![(0.0, 1.0), (0.5, 1.0)]]);
+
+    assert!(make_memory_plot(data, BitMapBackend::new(&output_path, (300, 300))).is_ok());
+}
+
+#[test]
+fn test_get_counters_info() {
+    let completed = FileSystemBasedStatus {
+        completion: FsState::Completed(Measurement {
+            wall_micros: Duration::from_nanos(20),
+            termination: Termination::Exited { code: 0 },
+            rusage: None,
+            limit_exceeded: None,
+            counters: Some(Counters {
+                instructions: Some(1000),
+                page_faults: Some(12),
+                ..Default::default()
+            }),
+            custom_metrics: BTreeMap::new(),
+            setup: None,
+            teardown: None,
+        }),
+        afterscript_completion: None,
+        label_source: None,
+        verdict: None,
+    };
+    let pending = FileSystemBasedStatus {
+        completion: FsState::Pending,
+        afterscript_completion: None,
+        label_source: None,
+        verdict: None,
+    };
+
+    assert_eq!(
+        get_counters_info(&completed),
+        vec!["1000", "unavailable", "unavailable", "unavailable", "12"]
+    );
+    assert_eq!(get_counters_info(&pending), vec!["..."; 5]);
+}
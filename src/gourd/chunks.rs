@@ -106,7 +106,7 @@ impl Chunkable for Experiment {
             for f in c.chunks(chunk_length) {
                 chunks.push(Chunk {
                     runs: f.iter().map(|(i, _)| *i).collect(),
-                    resource_limits: f[0].1.limits,
+                    resource_limits: f[0].1.limits.unwrap_or_default(),
                 });
             }
         }
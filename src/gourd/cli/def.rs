@@ -1,3 +1,4 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -50,13 +51,14 @@ pub enum RunSubcommand {
     /// Create and run an experiment on this computer.
     #[command()]
     Local {
-        /// Force running more experiments than recommended.
+        /// Keep running the experiment in the background, also after logging
+        /// out.
         #[arg(long)]
-        force: bool,
+        detach: bool,
 
-        /// Force running the experiments in sequence rather than concurrently.
-        #[arg(long)]
-        sequential: bool,
+        /// How to run the experiment on this computer.
+        #[command(flatten)]
+        local: LocalArgs,
     },
 
     /// Create and run an experiment using Slurm.
@@ -64,6 +66,65 @@ pub enum RunSubcommand {
     Slurm {},
 }
 
+/// Options for running an experiment on this computer.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub struct LocalArgs {
+    /// How many runs execute at the same time [default: the amount of
+    /// available cores].
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Force running the experiments in sequence rather than concurrently,
+    /// like `--jobs 1`.
+    #[arg(long, conflicts_with = "jobs")]
+    pub sequential: bool,
+
+    /// How many cpus the running runs can take together [default: the amount
+    /// of available cores].
+    #[arg(long)]
+    pub cpus: Option<NonZeroUsize>,
+
+    /// How much memory in MB the running runs can take together [default: the
+    /// memory of this computer].
+    #[arg(long)]
+    pub memory: Option<NonZeroUsize>,
+}
+
+/// Arguments supplied with the `tune` command.
+#[derive(Args, Debug, Clone, Copy)]
+pub struct TuneStruct {
+    /// Where to tune, or which tuned experiment to take the next step of.
+    #[command(subcommand)]
+    pub subcommand: TuneSubcommand,
+}
+
+/// Enum for subcommands of the `tune` subcommand.
+#[derive(Subcommand, Debug, Copy, Clone)]
+pub enum TuneSubcommand {
+    /// Create an experiment that tunes the parameters, and tune them on this
+    /// computer until the budget is spent.
+    #[command()]
+    Local {
+        /// How to run the experiment on this computer.
+        #[command(flatten)]
+        local: LocalArgs,
+    },
+
+    /// Create an experiment that tunes the parameters, and schedule its first
+    /// configurations using Slurm.
+    #[command()]
+    Slurm {},
+
+    /// Try the next configurations of a tuned experiment, once the runs of the
+    /// previous ones have completed.
+    #[command()]
+    Next {
+        /// The id of the tuned experiment [default: newest experiment].
+        #[arg(value_name = "EXPERIMENT")]
+        experiment_id: Option<usize>,
+    },
+}
+
 /// Arguments for the Rerun command.
 #[derive(Args, Debug, Clone)]
 pub struct RerunOptions {
@@ -105,6 +166,27 @@ pub struct ContinueStruct {
     /// [default: newest experiment].
     #[arg(value_name = "EXPERIMENT")]
     pub experiment_id: Option<usize>,
+
+    /// Keep running a local experiment in the background, also after logging
+    /// out.
+    #[arg(long)]
+    pub detach: bool,
+
+    /// How to run a local experiment on this computer.
+    #[command(flatten)]
+    pub local: LocalArgs,
+}
+
+/// Arguments supplied with the internal `supervise` command.
+#[derive(Args, Debug, Clone, Copy)]
+pub struct SuperviseStruct {
+    /// The id of the experiment to run.
+    #[arg(value_name = "EXPERIMENT")]
+    pub experiment_id: usize,
+
+    /// How to run the experiment on this computer.
+    #[command(flatten)]
+    pub local: LocalArgs,
 }
 
 /// Structure of cancel subcommand.
@@ -169,10 +251,21 @@ pub struct AnalyseStruct {
     /// For all formats see the manual.
     #[arg(long, short, default_value = "csv", value_parser = [
         PossibleValue::new("csv"),
+        PossibleValue::new("agreement"),
         PossibleValue::new("plot-svg"),
         PossibleValue::new("plot-png"),
     ])]
     pub output: String,
+
+    /// Plot a custom metric reported by the programs instead of their cpu
+    /// time.
+    #[arg(long)]
+    pub metric: Option<String>,
+
+    /// Plot the memory usage over time of every sampled run instead of their
+    /// cpu time.
+    #[arg(long, conflicts_with = "metric")]
+    pub memory: bool,
 }
 
 /// Arguments supplied with the `set-limits` command.
@@ -235,6 +328,11 @@ pub enum GourdCommand {
     #[command()]
     Rerun(RerunOptions),
 
+    /// Tune the parameters of an experiment, running new configurations of
+    /// parameter values based on the results of earlier ones.
+    #[command()]
+    Tune(TuneStruct),
+
     /// Output metrics of completed runs.
     #[command()]
     Analyse(AnalyseStruct),
@@ -242,4 +340,8 @@ pub enum GourdCommand {
     /// Print information about the version.
     #[command()]
     Version,
+
+    /// Run a local experiment in the background, as started by `--detach`.
+    #[command(hide = true)]
+    Supervise(SuperviseStruct),
 }
@@ -26,27 +26,42 @@ use indicatif_log_bridge::LogWrapper;
 use log::debug;
 use log::info;
 use log::trace;
+use log::warn;
 use log::LevelFilter;
 
 use super::def::ContinueStruct;
 use super::def::RerunOptions;
 use super::log::LogTokens;
 use super::printing::get_styles;
+use crate::analyse::analysis_agreement_csv;
 use crate::analyse::analysis_csv;
+use crate::analyse::analysis_memory_plot;
 use crate::analyse::analysis_plot;
 use crate::chunks::Chunkable;
 use crate::cli::def::AnalyseStruct;
 use crate::cli::def::CancelStruct;
 use crate::cli::def::Cli;
 use crate::cli::def::GourdCommand;
+use crate::cli::def::LocalArgs;
 use crate::cli::def::RunSubcommand;
 use crate::cli::def::StatusStruct;
+use crate::cli::def::SuperviseStruct;
+use crate::cli::def::TuneStruct;
+use crate::cli::def::TuneSubcommand;
 use crate::cli::printing::print_version;
 use crate::experiments::run::generate_new_run;
 use crate::experiments::ExperimentExt;
 use crate::init::init_experiment_setup;
 use crate::init::list_init_examples;
+use crate::local::cancel::cancel_runs;
+use crate::local::local_budget;
 use crate::local::run_local;
+use crate::local::supervisor::detach;
+use crate::local::supervisor::running_supervisor;
+use crate::local::supervisor::stop_supervisor;
+use crate::local::supervisor::supervise;
+use crate::local::supervisor::supervisor_log_file;
+use crate::post::agreement::check_agreement;
 use crate::rerun;
 use crate::rerun::slurm::query_changing_resource_limits;
 use crate::slurm::checks::slurm_options_from_experiment;
@@ -55,9 +70,15 @@ use crate::slurm::interactor::SlurmCli;
 use crate::slurm::SlurmInteractor;
 use crate::status::blocking_status;
 use crate::status::chunks::print_scheduling;
+use crate::status::printing::display_agreement;
 use crate::status::printing::display_job;
 use crate::status::printing::display_statuses;
 use crate::status::DynamicStatus;
+use crate::status::FsState;
+use crate::tune::report_tune_step;
+use crate::tune::start_tuning;
+use crate::tune::tune_step;
+use crate::tune::TuneStep;
 
 /// This function parses command that gourd was run with.
 pub async fn parse_command() {
@@ -134,12 +155,29 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
             debug!("Saved the experiment at {exp_path:?}");
 
             match args.subcommand {
-                RunSubcommand::Local { force, sequential } => {
+                RunSubcommand::Local {
+                    detach: true,
+                    local,
+                } => {
+                    if cmd.dry {
+                        info!("Would have ran the experiment in the background (dry)");
+                    } else {
+                        let pid = detach(&experiment, &cmd.config, &local)?;
+
+                        info!(
+                            "Experiment started in the background by process {pid}, \
+                            logging to {:?}",
+                            supervisor_log_file(&experiment)
+                        );
+                    }
+                }
+
+                RunSubcommand::Local { local, .. } => {
                     if cmd.dry {
                         info!("Would have ran the experiment (dry)");
                     } else {
-                        let to_complete =
-                            run_local(&mut experiment, &file_system, force, sequential).await?;
+                        let (to_complete, _) =
+                            run_local(&mut experiment, &file_system, local_budget(&local)).await?;
 
                         info!("Experiment started");
 
@@ -223,6 +261,26 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
                     } else {
                         display_statuses(&mut stdout(), &experiment, &statuses, *full)?;
                     }
+
+                    if experiment.agreement.is_some() {
+                        // After following, the statuses are out of date.
+                        let statuses = if *blocking {
+                            experiment.status(&file_system)?
+                        } else {
+                            statuses
+                        };
+
+                        let agreements = check_agreement(&experiment, &statuses, &file_system)?;
+                        display_agreement(&mut stdout(), &experiment, &agreements)?;
+                    }
+
+                    if let Some(pid) = running_supervisor(&experiment) {
+                        info!(
+                            "The experiment is running in the background by process {pid}, \
+                            logging to {:?}",
+                            supervisor_log_file(&experiment)
+                        );
+                    }
                 }
             }
         }
@@ -253,6 +311,8 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
         GourdCommand::Analyse(AnalyseStruct {
             experiment_id,
             output,
+            metric,
+            memory,
         }) => {
             let experiment = read_experiment(experiment_id, cmd, &file_system)?;
 
@@ -277,28 +337,64 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
                 match &output[..] {
                     "csv" => {
                         output_path.push(format!("analysis_{}.csv", experiment.seq));
-                        analysis_csv(&output_path, statuses).with_context(ctx!(
+                        analysis_csv(&output_path, &experiment, statuses).with_context(ctx!(
                                 "Could not analyse to a CSV file at {:?}",
                                 &output_path; "",
                         ))?;
                     }
+                    "agreement" => {
+                        if experiment.agreement.is_none() {
+                            bailc!(
+                                "The experiment does not check for agreement", ;
+                                "The programs are only compared when [agreement] is configured",;
+                                "Add an [agreement] section to the configuration",
+                            );
+                        }
+
+                        output_path.push(format!("agreement_{}.csv", experiment.seq));
+                        let agreements = check_agreement(&experiment, &statuses, &file_system)?;
+                        analysis_agreement_csv(&output_path, &experiment, &agreements)
+                            .with_context(ctx!(
+                                "Could not analyse to a CSV file at {:?}",
+                                &output_path; "",
+                            ))?;
+                    }
                     "plot-png" => {
                         output_path.push(format!("plot_{}.png", experiment.seq));
-                        analysis_plot(&output_path, statuses, experiment, true)
-                            .with_context(ctx!(
+                        if *memory {
+                            analysis_memory_plot(&output_path, &experiment, true)
+                        } else {
+                            analysis_plot(
+                                &output_path,
+                                statuses,
+                                experiment,
+                                metric.as_deref(),
+                                true,
+                            )
+                        }
+                        .with_context(ctx!(
                                 "Could not create a plot at {:?}", &output_path; "", ))?;
                     }
                     "plot-svg" => {
                         output_path.push(format!("plot_{}.svg", experiment.seq));
-                        analysis_plot(&output_path, statuses, experiment, false).with_context(
-                            ctx!(
-                                    "Could not create a plot at {:?}",
-                                    &output_path; "",
-                            ),
-                        )?;
+                        if *memory {
+                            analysis_memory_plot(&output_path, &experiment, false)
+                        } else {
+                            analysis_plot(
+                                &output_path,
+                                statuses,
+                                experiment,
+                                metric.as_deref(),
+                                false,
+                            )
+                        }
+                        .with_context(ctx!(
+                                "Could not create a plot at {:?}",
+                                &output_path; "",
+                        ))?;
                     }
                     _ => bailc!("Unsupported output format {}", &output;
-                        "Use 'csv', 'plot-png', or 'plot-svg'.", ; "" ,),
+                        "Use 'csv', 'agreement', 'plot-png', or 'plot-svg'.", ; "" ,),
                 }
             } else {
                 bailc!(
@@ -320,6 +416,75 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
             let s: SlurmHandler<SlurmCli> = SlurmHandler::default();
             let experiment = read_experiment(experiment_id, cmd, &file_system)?;
 
+            if experiment.env == Environment::Local && !*all {
+                // Without run ids, the experiment stops starting runs before
+                // the running ones are cancelled.
+                let supervisor = match run_ids {
+                    None if !cmd.dry => stop_supervisor(&experiment)?,
+                    _ => None,
+                };
+
+                if let Some(pid) = supervisor {
+                    info!("Stopped process {pid} from starting more runs");
+                }
+
+                let statuses = experiment.status(&file_system)?;
+
+                let runs = match run_ids {
+                    Some(ids) => {
+                        let mut runs = Vec::new();
+                        let last = experiment.runs.len().saturating_sub(1);
+
+                        for id in ids {
+                            let Some(status) = statuses.get(id) else {
+                                bailc!(
+                                    "Could not find a run with id {id} in experiment {}",
+                                    experiment.seq;
+                                    "Experiment {} has runs with ids 0-{last}",
+                                    experiment.seq;
+                                    "",
+                                );
+                            };
+
+                            if status.is_completed() {
+                                warn!("Run {id} has already finished");
+                            } else {
+                                runs.push(*id);
+                            }
+                        }
+
+                        runs
+                    }
+                    None => statuses
+                        .iter()
+                        .filter(|(_, status)| status.fs_status.completion == FsState::Running)
+                        .map(|(id, _)| *id)
+                        .collect(),
+                };
+
+                if runs.is_empty() {
+                    if supervisor.is_none() {
+                        bailc!(
+                            "No runs to cancel", ;
+                            "None of these runs of experiment {} are running", experiment.seq;
+                            "Run {CMD_STYLE}gourd status {}{CMD_STYLE:#} to check \
+                            which runs are running.", experiment.seq
+                        );
+                    }
+                } else if cmd.dry {
+                    info!("Would have cancelled {TERTIARY_STYLE}{runs:?}{TERTIARY_STYLE:#}");
+                } else {
+                    cancel_runs(&experiment, &runs, &file_system)?;
+
+                    info!(
+                        "Cancelled {TERTIARY_STYLE}{runs:?}{TERTIARY_STYLE:#}, \
+                        rerun them with {CMD_STYLE}gourd rerun{CMD_STYLE:#}"
+                    );
+                }
+
+                return Ok(());
+            }
+
             let id_list = if *all {
                 s.internal.scheduled_jobs()?
             } else if let Some(ids) = run_ids {
@@ -380,9 +545,34 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
 
         GourdCommand::Version => print_version(cmd.script),
 
-        GourdCommand::Continue(ContinueStruct { experiment_id }) => {
+        GourdCommand::Supervise(SuperviseStruct {
+            experiment_id,
+            local,
+        }) => {
+            let mut experiment = read_experiment(&Some(*experiment_id), cmd, &file_system)?;
+
+            supervise(&mut experiment, &file_system, local_budget(local)).await?;
+        }
+
+        GourdCommand::Continue(ContinueStruct {
+            experiment_id,
+            detach: detached,
+            local,
+        }) => {
             let mut experiment = read_experiment(experiment_id, cmd, &file_system)?;
 
+            // A tuned experiment continues with the next configurations, once
+            // the runs of the earlier ones have completed.
+            if experiment
+                .tuning
+                .as_ref()
+                .is_some_and(|tuning| !tuning.finished)
+            {
+                let statuses = experiment.status(&file_system)?;
+                report_tune_step(tune_step(&mut experiment, &statuses, &file_system)?);
+                experiment.save(&file_system)?;
+            }
+
             let statuses = experiment.status(&file_system)?;
 
             if experiment.unscheduled(&statuses).is_empty() {
@@ -394,10 +584,27 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
             let exp_path = experiment.save(&file_system)?;
 
             if experiment.env == Environment::Local {
+                if let Some(pid) = running_supervisor(&experiment) {
+                    bailc!(
+                        "Experiment {} is still running in the background", experiment.seq;
+                        "It is run by process {}", pid;
+                        "Wait for it to finish, or stop it with \
+                        {CMD_STYLE}gourd cancel {}{CMD_STYLE:#}", experiment.seq
+                    );
+                }
+
                 if cmd.dry {
                     info!("Would have continued the experiment (dry)");
+                } else if *detached {
+                    let pid = detach(&experiment, &cmd.config, local)?;
+
+                    info!(
+                        "Experiment continued in the background by process {pid}, logging to {:?}",
+                        supervisor_log_file(&experiment)
+                    );
                 } else {
-                    let to_complete = run_local(&mut experiment, &file_system, true, false).await?;
+                    let (to_complete, _) =
+                        run_local(&mut experiment, &file_system, local_budget(local)).await?;
 
                     info!("Experiment started");
 
@@ -426,6 +633,118 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
             }
         }
 
+        GourdCommand::Tune(TuneStruct { subcommand }) => {
+            let mut experiment = match subcommand {
+                TuneSubcommand::Next { experiment_id } => {
+                    read_experiment(experiment_id, cmd, &file_system)?
+                }
+
+                TuneSubcommand::Local { .. } | TuneSubcommand::Slurm {} => {
+                    debug!("Reading the config: {:?}", cmd.config);
+
+                    let config = Config::from_file(&cmd.config, &file_system)?;
+
+                    debug!("Creating a new tuned experiment");
+                    trace!("The config is: {config:#?}");
+
+                    start_tuning(
+                        &config,
+                        match subcommand {
+                            TuneSubcommand::Slurm {} => Environment::Slurm,
+                            _ => Environment::Local,
+                        },
+                        &file_system,
+                    )?
+                }
+            };
+
+            let exp_path = experiment.save(&file_system)?;
+            debug!("Saved the experiment at {exp_path:?}");
+
+            if experiment.env == Environment::Local {
+                let budget = match subcommand {
+                    TuneSubcommand::Local { local } => local_budget(local),
+                    _ => local_budget(&LocalArgs::default()),
+                };
+
+                if cmd.dry {
+                    info!("Would have tuned the experiment (dry)");
+                } else {
+                    loop {
+                        let (to_complete, _) =
+                            run_local(&mut experiment, &file_system, budget).await?;
+
+                        // Local will never unshorten status, hence the false.
+                        blocking_status(
+                            &progress,
+                            &experiment,
+                            &mut file_system,
+                            false,
+                            to_complete,
+                        )?;
+
+                        let statuses = experiment.status(&file_system)?;
+                        let step = tune_step(&mut experiment, &statuses, &file_system)?;
+                        experiment.save(&file_system)?;
+
+                        match step {
+                            TuneStep::Added(runs) => info!("Tuning added {runs} new runs"),
+                            TuneStep::Finished => {
+                                info!("Tuning finished");
+                                println!();
+                                break;
+                            }
+                            TuneStep::Waiting(configurations) => {
+                                info!(
+                                    "The runs of {configurations} configurations have not \
+                                    completed, run {CMD_STYLE}gourd tune next {}{CMD_STYLE:#} \
+                                    to try again",
+                                    experiment.seq
+                                );
+                                break;
+                            }
+                        }
+                    }
+                }
+            } else if experiment.env == Environment::Slurm {
+                let s: SlurmHandler<SlurmCli> = SlurmHandler::default();
+                s.check_version()?;
+                s.check_partition(&slurm_options_from_experiment(&experiment)?.partition)?;
+
+                if cmd.dry {
+                    info!("Would have tuned the experiment on slurm (dry)");
+                } else {
+                    if let TuneSubcommand::Next { .. } = subcommand {
+                        let statuses = experiment.status(&file_system)?;
+                        report_tune_step(tune_step(&mut experiment, &statuses, &file_system)?);
+                        experiment.save(&file_system)?;
+                    }
+
+                    let statuses = experiment.status(&file_system)?;
+
+                    if !experiment.unscheduled(&statuses).is_empty() {
+                        let sched = s.run_experiment(&mut experiment, exp_path, &file_system)?;
+                        print_scheduling(&experiment, false)?;
+                        info!("You just scheduled {sched} chunks");
+                    }
+
+                    if experiment.tuning.as_ref().is_some_and(|t| !t.finished) {
+                        info!(
+                            "Run {CMD_STYLE}gourd tune next {}{CMD_STYLE:#} once the runs have \
+                            completed to continue tuning",
+                            experiment.seq
+                        );
+                    }
+                }
+
+                experiment.save(&file_system)?;
+            }
+
+            if cmd.script {
+                println!("{}", experiment.file().display());
+            }
+        }
+
         GourdCommand::Rerun(RerunOptions {
             experiment_id,
             run_ids,
@@ -466,6 +785,7 @@ pub async fn process_command(cmd: &Cli) -> Result<()> {
                     // instead of the limits of the entire program.
                     experiment.programs[experiment.runs[*run_id].program].limits,
                     old_run.parent,
+                    old_run.repetition,
                     &experiment,
                     &file_system,
                 )?);
@@ -3,10 +3,17 @@ use std::path::PathBuf;
 
 use anyhow::Context;
 use gourd_lib::bailc;
+use gourd_lib::constants::INPUT_PLACEHOLDER;
+use gourd_lib::constants::INPUT_STEM_PLACEHOLDER;
+use gourd_lib::experiment::inputs::referenced_files;
 use gourd_lib::experiment::Experiment;
+use gourd_lib::experiment::FieldRef;
+use gourd_lib::experiment::InternalInput;
+use gourd_lib::experiment::InternalProgram;
 use gourd_lib::experiment::Run;
 use gourd_lib::experiment::RunInput;
 use gourd_lib::file_system::FileOperations;
+use log::warn;
 
 use crate::experiments::run::generate_new_run;
 
@@ -19,11 +26,72 @@ enum Step {
     Exit(usize),
 }
 
+/// Which pairings of programs and inputs the runs are created for.
+#[derive(Debug, Clone, Copy)]
+pub struct Pairings<'a> {
+    /// The inputs that the programs run on, or all inputs of the experiment.
+    pub inputs: Option<&'a [FieldRef]>,
+
+    /// Every pairing runs from `.0` up to `.1` times its repetitions.
+    pub rounds: (usize, usize),
+}
+
+impl Default for Pairings<'_> {
+    fn default() -> Self {
+        Self {
+            inputs: None,
+            rounds: (0, 1),
+        }
+    }
+}
+
+/// Create the runs of the `pairings` and the runs that postprocess their
+/// output, appending them to `runs`.
+///
+/// The `runs` are all runs of the experiment, so that the new runs get the
+/// next ids.
+pub fn create_runs(
+    exp: &Experiment,
+    runs: &mut Vec<Run>,
+    pairings: Pairings,
+    fs: &impl FileOperations,
+) -> anyhow::Result<()> {
+    // Collapse the programs into an ordered structure for faster processing.
+    let mut in_degrees = vec![0usize; exp.programs.len()];
+
+    for prog in &exp.programs {
+        for next_prog in &prog.next {
+            in_degrees[*next_prog] += 1;
+        }
+    }
+
+    let mut visitation = vec![0usize; exp.programs.len()];
+
+    for (prog, degree) in in_degrees.iter().enumerate() {
+        if *degree == 0 {
+            dfs(&mut visitation, prog, runs, pairings, exp, fs)?;
+        }
+    }
+
+    for (prog, visit) in visitation.iter().enumerate() {
+        if *visit != 1 {
+            bailc!(
+                "A cycle was found in the program dependencies.",;
+                "The `next` field in the program definitions created a circular dependency",;
+                "Fix the dependencies for {:?}",exp.programs[prog].name
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// A depth first search for creating the program tree.
-pub(super) fn dfs(
+fn dfs(
     visitation: &mut [usize],
     start: usize,
     runs: &mut Vec<Run>,
+    pairings: Pairings,
     exp: &Experiment,
     fs: &impl FileOperations,
 ) -> anyhow::Result<()> {
@@ -55,24 +123,54 @@ pub(super) fn dfs(
             let mut children = Vec::new();
 
             if parent.is_none() {
-                for (input_name, input) in &exp.inputs {
-                    let child = generate_new_run(
-                        runs.len(),
-                        node,
-                        RunInput {
-                            file: input.input.clone(),
-                            arguments: input.arguments.clone(),
-                        },
-                        Some(input_name.clone()),
-                        input.metadata.group.clone(),
-                        exp.programs[node].limits,
-                        None,
-                        exp,
-                        fs,
-                    )?;
+                let program = &exp.programs[node];
+                let selected: Vec<_> = exp
+                    .inputs
+                    .iter()
+                    .filter(|(name, _)| pairings.inputs.is_none_or(|inputs| inputs.contains(name)))
+                    .filter(|(name, input)| program.runs_on(name, input))
+                    .collect();
 
-                    children.push((runs.len(), child.output_path.clone()));
-                    runs.push(child);
+                if selected.is_empty() && !exp.inputs.is_empty() && pairings.inputs.is_none() {
+                    warn!(
+                        "The `include` and `exclude` of program {} select none of the inputs",
+                        program.name
+                    );
+                }
+
+                for (input_name, input) in selected {
+                    check_placeholders(program, input_name, input)?;
+
+                    let repetitions = input
+                        .repetitions
+                        .or(exp.programs[node].repetitions)
+                        .unwrap_or(1);
+                    let (from, to) = pairings.rounds;
+
+                    for repetition in repetitions * from..repetitions * to {
+                        let child = generate_new_run(
+                            runs.len(),
+                            node,
+                            RunInput {
+                                file: input.input.clone(),
+                                arguments: input.arguments.clone(),
+                                files: input.files.clone(),
+                                generator: input.generator.clone(),
+                                env: input.env.clone(),
+                            },
+                            Some(input_name.clone()),
+                            input.metadata.group.clone(),
+                            exp.programs[node].limits,
+                            None,
+                            // Only repeated pairings are told apart by their repetition.
+                            (repetitions * to > 1).then_some(repetition),
+                            exp,
+                            fs,
+                        )?;
+
+                        children.push((runs.len(), child.output_path.clone()));
+                        runs.push(child);
+                    }
                 }
             } else if let Some(pchildren) = parent {
                 for pchild in pchildren {
@@ -82,11 +180,15 @@ pub(super) fn dfs(
                         RunInput {
                             file: Some(pchild.1),
                             arguments: runs[pchild.0].input.arguments.clone(),
+                            files: runs[pchild.0].input.files.clone(),
+                            generator: None,
+                            env: runs[pchild.0].input.env.clone(),
                         },
                         None,
                         None, // no groups for children
                         runs[pchild.0].limits,
                         Some(pchild.0),
+                        runs[pchild.0].repetition,
                         exp,
                         fs,
                     )?;
@@ -108,3 +210,47 @@ pub(super) fn dfs(
 
     Ok(())
 }
+
+/// Check that the input of a root run has the files that the arguments, setup,
+/// teardown and environment variables refer to with placeholders.
+fn check_placeholders(
+    program: &InternalProgram,
+    input_name: &str,
+    input: &InternalInput,
+) -> anyhow::Result<()> {
+    let arguments = || {
+        program
+            .arguments
+            .iter()
+            .chain(&input.arguments)
+            .chain(&program.setup)
+            .chain(&program.teardown)
+            .chain(program.env.values())
+            .chain(input.env.values())
+    };
+
+    let uses_input = arguments()
+        .any(|arg| arg.contains(INPUT_PLACEHOLDER) || arg.contains(INPUT_STEM_PLACEHOLDER));
+
+    if uses_input && input.input.is_none() && input.generator.is_none() {
+        bailc!(
+            "The arguments of program {} refer to the input file", program.name;
+            "The input {input_name} has no file",;
+            "Give the input a `file`, `glob`, `fetch` or `generator`, or remove `{INPUT_PLACEHOLDER}`",
+        );
+    }
+
+    let missing = arguments()
+        .flat_map(|arg| referenced_files(arg))
+        .find(|file| !input.files.contains_key(*file));
+
+    if let Some(file) = missing {
+        bailc!(
+            "The arguments of program {} refer to the file {file:?}", program.name;
+            "The input {input_name} has no file named {file:?}",;
+            "Add {file:?} to the `files` of the input",
+        );
+    }
+
+    Ok(())
+}
@@ -15,10 +15,11 @@ use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
 
-use crate::experiments::dfs::dfs;
+use crate::experiments::dfs::create_runs;
+use crate::experiments::dfs::Pairings;
 
 /// Search through the run dependency graph to create the linear-connected runs
-mod dfs;
+pub mod dfs;
 
 /// Generating new runs
 pub mod run;
@@ -69,8 +70,32 @@ impl ExperimentExt for Experiment {
         // First we will explode all programs from the initial set to their final set.
         let expanded_programs = expand_programs(&conf.programs, conf, fs)?;
 
+        if let Some(reference) = conf
+            .agreement
+            .as_ref()
+            .and_then(|agreement| agreement.reference.as_ref())
+        {
+            if !expanded_programs
+                .iter()
+                .any(|program| program.original_name() == reference)
+            {
+                bailc!(
+                    "The reference program {reference} does not exist", ;
+                    "The consensus of the agreement check is the output of this program",;
+                    "Set `reference` in [agreement] to the name of a program",
+                );
+            }
+        }
+
         // Now we will expand all inputs in a similar manner.
-        let expanded_inputs = expand_inputs(&conf.inputs, &conf.parameters, fs)?;
+        let expanded_inputs = expand_inputs(
+            &conf.inputs,
+            &conf.parameters,
+            &conf.zip,
+            &conf.search,
+            &conf.constraints,
+            fs,
+        )?;
 
         // Modifications to the slurm configurations
         let slurm = if let Some(mut slurm_conf) = conf.slurm.clone() {
@@ -114,36 +139,18 @@ impl ExperimentExt for Experiment {
 
             chunks: Vec::new(),
             groups,
+            sampling_interval: conf.sampling_interval,
+            performance_counters: conf.performance_counters,
+            seed: conf.seed,
+            compare: conf.compare.resolve(fs)?,
+            agreement: conf.agreement.clone(),
+            clear_env: conf.clear_env,
+            tuning: None,
             runs: Vec::new(),
         };
 
-        // Collapse the programs into an ordered structure for faster processing.
-        let mut in_degrees = vec![0usize; experiment.programs.len()];
-
-        for prog in &experiment.programs {
-            for next_prog in &prog.next {
-                in_degrees[*next_prog] += 1;
-            }
-        }
-
-        let mut visitation = vec![0usize; experiment.programs.len()];
         let mut runs = Vec::new();
-
-        for (prog, degree) in in_degrees.iter().enumerate() {
-            if *degree == 0 {
-                dfs(&mut visitation, prog, &mut runs, &experiment, fs)?;
-            }
-        }
-
-        for (prog, visit) in visitation.iter().enumerate() {
-            if *visit != 1 {
-                bailc!(
-                    "A cycle was found in the program dependencies.",;
-                    "The `next` field in the program definitions created a circular dependency",;
-                    "Fix the dependencies for {:?}",experiment.programs[prog].name
-                );
-            }
-        }
+        create_runs(&experiment, &mut runs, Pairings::default(), fs)?;
 
         experiment.runs = runs;
 
@@ -9,6 +9,9 @@ use gourd_lib::file_system::FileOperations;
 ///
 /// This should be used by all code paths adding runs to the experiment.
 /// This does *not* set the parent and child.
+///
+/// The seed of the run follows from its `repetition` and the seed of the
+/// experiment.
 #[allow(clippy::too_many_arguments)]
 pub fn generate_new_run(
     run_id: usize,
@@ -16,8 +19,9 @@ pub fn generate_new_run(
     run_input: RunInput,
     input: Option<FieldRef>,
     input_group: Option<String>,
-    limits: ResourceLimits,
+    limits: Option<ResourceLimits>,
     parent: Option<usize>,
+    repetition: Option<usize>,
     experiment: &Experiment,
     fs: &impl FileOperations,
 ) -> Result<Run> {
@@ -34,11 +38,26 @@ pub fn generate_new_run(
                 .metrics_folder
                 .join(format!("{}/{}/{}/metrics", experiment.seq, program, run_id)),
         )?,
+        custom_metrics_path: Some(fs.truncate_and_canonicalize(
+            &experiment.metrics_folder.join(format!(
+                "{}/{}/{}/custom_metrics",
+                experiment.seq, program, run_id
+            )),
+        )?),
         output_path: fs.truncate_and_canonicalize(
             &experiment
                 .output_folder
                 .join(format!("{}/{}/{}/stdout", experiment.seq, program, run_id)),
         )?,
+        samples_path: match experiment.sampling_interval {
+            None => None,
+            Some(_) => Some(
+                fs.truncate_and_canonicalize(&experiment.metrics_folder.join(format!(
+                    "{}/{}/{}/samples.csv",
+                    experiment.seq, program, run_id
+                )))?,
+            ),
+        },
         work_dir: fs.truncate_and_canonicalize_folder(
             &experiment
                 .output_folder
@@ -61,5 +80,9 @@ pub fn generate_new_run(
         generated_from_input: input,
         parent,
         group: input_group,
+        repetition,
+        seed: experiment
+            .seed
+            .map(|seed| seed.wrapping_add(repetition.unwrap_or(0) as u64)),
     })
 }
@@ -1,8 +1,23 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use gourd_lib::config::compare::Agreement;
+use gourd_lib::config::compare::Comparison;
+use gourd_lib::config::search::SearchConfig;
+use gourd_lib::config::search::SearchStrategy;
+use gourd_lib::config::slurm::UserResourceLimits;
+use gourd_lib::config::DistributionSpec;
+use gourd_lib::config::Generator;
+use gourd_lib::config::Parameter;
+use gourd_lib::config::Parametrized;
+use gourd_lib::experiment::GeneratedInput;
+use gourd_lib::experiment::InternalProgram;
 use tempdir::TempDir;
 
 use super::*;
+use crate::test_utils::parameter;
+use crate::test_utils::test_config;
 use crate::test_utils::REAL_FS;
 
 #[test]
@@ -21,14 +36,7 @@ fn latest_id_invalid_folder() {
 #[test]
 fn latest_id_correct() {
     let tempdir = TempDir::new("tests").unwrap();
-    let mut config: Config = Config::from_file(
-        Path::new("src/gourd/experiments/tests/test_resources/config_id_testing.toml"),
-        &REAL_FS,
-    )
-    .unwrap();
-    config.output_path = PathBuf::from(tempdir.path());
-    config.metrics_path = PathBuf::from(tempdir.path());
-    config.experiments_folder = PathBuf::from(tempdir.path());
+    let config = test_config(&tempdir);
 
     // test other files in dir that should be ignored
     fs::create_dir(tempdir.path().join("39.lock")).unwrap();
@@ -48,3 +56,513 @@ fn latest_id_correct() {
     let id = Experiment::latest_id_from_folder(tempdir.path()).unwrap();
     assert_eq!(id, Some(8));
 }
+
+#[test]
+fn repetitions_and_seeds() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+    config.repetitions = Some(3);
+    config.seed = Some(10);
+    config.programs.get_mut("c").unwrap().repetitions = Some(2);
+    config.inputs.get_mut("e").unwrap().repetitions = Some(1);
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let runs: Vec<(String, Option<usize>, Option<u64>)> = experiment
+        .runs
+        .iter()
+        .map(|run| {
+            (
+                format!(
+                    "{} on {}",
+                    experiment.programs[run.program].name,
+                    run.generated_from_input.as_ref().unwrap()
+                ),
+                run.repetition,
+                run.seed,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        runs,
+        vec![
+            ("b on d".to_string(), Some(0), Some(10)),
+            ("b on d".to_string(), Some(1), Some(11)),
+            ("b on d".to_string(), Some(2), Some(12)),
+            ("b on e".to_string(), None, Some(10)),
+            ("c on d".to_string(), Some(0), Some(10)),
+            ("c on d".to_string(), Some(1), Some(11)),
+            ("c on e".to_string(), None, Some(10)),
+        ]
+    );
+
+    config.repetitions = Some(0);
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn program_parameters() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+    config.parameters = Some(BTreeMap::from([(
+        "threads".to_string(),
+        parameter(&["1", "4"]),
+    )]));
+
+    let b = config.programs.get_mut("b").unwrap();
+    b.arguments = vec!["-t".to_string(), "param|threads".to_string()];
+    b.resource_limits = Some(UserResourceLimits {
+        time_limit: Parametrized::Value(Duration::from_secs(60)),
+        cpus: Parametrized::Parameter("param|threads".to_string()),
+        mem_per_cpu: Parametrized::Value(128),
+    });
+    config.programs.get_mut("c").unwrap().next = vec!["b".to_string()];
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let programs: Vec<(&str, &[String], usize, &[usize])> = experiment
+        .programs
+        .iter()
+        .map(|program| {
+            (
+                program.name.as_str(),
+                program.arguments.as_slice(),
+                program.limits.unwrap_or_default().cpus,
+                program.next.as_slice(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        programs,
+        vec![
+            (
+                "b_threads_0_i_param",
+                &["-t".to_string(), "1".to_string()][..],
+                1,
+                &[][..]
+            ),
+            (
+                "b_threads_1_i_param",
+                &["-t".to_string(), "4".to_string()][..],
+                4,
+                &[][..]
+            ),
+            ("c", &[][..], 1, &[0, 1][..]),
+        ]
+    );
+
+    // The reference is the program in the configuration, in all its versions.
+    config.agreement = Some(Agreement {
+        reference: Some("b".to_string()),
+    });
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_ok());
+
+    config.agreement = Some(Agreement {
+        reference: Some("b_threads_0_i_param".to_string()),
+    });
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+    config.agreement = None;
+
+    // An input that uses the parameter of a program would run on every version.
+    config.inputs.get_mut("d").unwrap().arguments = vec!["param|threads".to_string()];
+    let error =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap_err();
+    assert!(format!("{error:?}").contains("Parameter threads is used by program b"));
+    config.inputs.get_mut("d").unwrap().arguments = vec![];
+
+    config.programs.get_mut("b").unwrap().resource_limits = Some(UserResourceLimits {
+        time_limit: Parametrized::Value(Duration::from_secs(60)),
+        cpus: Parametrized::Parameter("param|missing".to_string()),
+        mem_per_cpu: Parametrized::Value(128),
+    });
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+
+    config.programs.get_mut("b").unwrap().resource_limits = Some(UserResourceLimits {
+        time_limit: Parametrized::Value(Duration::from_secs(60)),
+        cpus: Parametrized::Value(0),
+        mem_per_cpu: Parametrized::Value(128),
+    });
+    let error =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap_err();
+    assert!(format!("{error:?}").contains("The resource limit cpus is 0"));
+}
+
+#[test]
+fn program_scoped_parameters() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    let b = config.programs.get_mut("b").unwrap();
+    b.arguments = vec!["param|alpha".to_string(), "param|beta".to_string()];
+    b.parameters = Some(BTreeMap::from([
+        ("alpha".to_string(), parameter(&["0.1", "0.2"])),
+        ("beta".to_string(), parameter(&["1", "2"])),
+    ]));
+    b.zip = vec![vec!["alpha".to_string(), "beta".to_string()]];
+
+    let c = config.programs.get_mut("c").unwrap();
+    c.arguments = vec!["param|alpha".to_string()];
+    c.parameters = Some(BTreeMap::from([("alpha".to_string(), parameter(&["5"]))]));
+
+    config.constraints = vec!["alpha < 0.2 || beta == 2".to_string()];
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let programs: Vec<(&str, &[String])> = experiment
+        .programs
+        .iter()
+        .map(|program| (program.name.as_str(), program.arguments.as_slice()))
+        .collect();
+
+    assert_eq!(
+        programs,
+        vec![
+            (
+                "b_alpha_0_beta_0_i_param",
+                &["0.1".to_string(), "1".to_string()][..]
+            ),
+            (
+                "b_alpha_1_beta_1_i_param",
+                &["0.2".to_string(), "2".to_string()][..]
+            ),
+            ("c_alpha_0_i_param", &["5".to_string()][..]),
+        ]
+    );
+
+    // The inputs cannot use the parameters of a program.
+    config.inputs.get_mut("d").unwrap().arguments = vec!["param|alpha".to_string()];
+    config.parameters = Some(BTreeMap::new());
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn program_random_search() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    let b = config.programs.get_mut("b").unwrap();
+    b.arguments = vec!["param|rate".to_string()];
+    b.parameters = Some(BTreeMap::from([(
+        "rate".to_string(),
+        Parameter {
+            values: None,
+            uniform: Some(DistributionSpec {
+                low: 0.5,
+                high: 1.0,
+            }),
+            ..parameter(&[])
+        },
+    )]));
+
+    config.search = Some(SearchConfig {
+        strategy: SearchStrategy::Random,
+        samples: Some(3),
+        seed: 7,
+    });
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let sampled: Vec<&InternalProgram> = experiment
+        .programs
+        .iter()
+        .filter(|program| program.original_name() == "b")
+        .collect();
+
+    assert_eq!(
+        sampled
+            .iter()
+            .map(|program| program.name.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "b_sample_0_i_param",
+            "b_sample_1_i_param",
+            "b_sample_2_i_param"
+        ]
+    );
+
+    for program in sampled {
+        let rate: f64 = program.arguments[0].parse().unwrap();
+        assert!((0.5..=1.0).contains(&rate));
+    }
+}
+
+#[test]
+fn input_selectors() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    config.parameters = Some(BTreeMap::from([("x".to_string(), parameter(&["1", "2"]))]));
+
+    let e = config.inputs.get_mut("e").unwrap();
+    e.arguments = vec!["param|x".to_string()];
+    e.group = Some("hard".to_string());
+
+    let mut f = config.programs["b"].clone();
+    f.include = vec!["*".to_string()];
+    f.exclude = vec!["e_x_1*".to_string()];
+    config.programs.insert("f".to_string(), f);
+
+    config.programs.get_mut("b").unwrap().include = vec!["e".to_string()];
+    config.programs.get_mut("c").unwrap().exclude = vec!["group|hard".to_string()];
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let runs: Vec<String> = experiment
+        .runs
+        .iter()
+        .map(|run| {
+            format!(
+                "{} on {}",
+                experiment.programs[run.program].name,
+                run.generated_from_input.as_ref().unwrap()
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        runs,
+        vec![
+            "b on e_x_0_i_param",
+            "b on e_x_1_i_param",
+            "c on d",
+            "f on d",
+            "f on e_x_0_i_param",
+        ]
+    );
+
+    config.programs.get_mut("c").unwrap().include = vec!["[".to_string()];
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn input_placeholder_needs_a_file() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    let b = config.programs.get_mut("b").unwrap();
+    b.arguments = vec!["--instance".to_string(), "{input}".to_string()];
+    b.stdin = false;
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+    assert!(!experiment.programs[0].stdin);
+
+    config.inputs.get_mut("e").unwrap().file = None;
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn named_input_files() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    for file in [
+        "a.graph", "b.graph", "a.query", "b.query", "c.query", "model",
+    ] {
+        fs::write(tempdir.path().join(file), "").unwrap();
+    }
+
+    let path = |file: &str| tempdir.path().join(file).to_string_lossy().to_string();
+
+    config.programs.remove("c");
+    config.programs.get_mut("b").unwrap().arguments =
+        vec!["{file:graph}".to_string(), "{file:query}".to_string()];
+
+    config.inputs.remove("e");
+    let d = config.inputs.get_mut("d").unwrap();
+    d.file = None;
+    d.files = BTreeMap::from([
+        ("graph".to_string(), path("*.graph")),
+        ("query".to_string(), path("*.query")),
+        ("model".to_string(), path("model")),
+    ]);
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let canonical = |file: &str| tempdir.path().join(file).canonicalize().unwrap();
+
+    let inputs: Vec<(&str, &BTreeMap<String, PathBuf>)> = experiment
+        .runs
+        .iter()
+        .map(|run| {
+            (
+                run.generated_from_input.as_deref().unwrap(),
+                &run.input.files,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        inputs,
+        vec![
+            (
+                "d_i_a",
+                &BTreeMap::from([
+                    ("graph".to_string(), canonical("a.graph")),
+                    ("model".to_string(), canonical("model")),
+                    ("query".to_string(), canonical("a.query")),
+                ])
+            ),
+            (
+                "d_i_b",
+                &BTreeMap::from([
+                    ("graph".to_string(), canonical("b.graph")),
+                    ("model".to_string(), canonical("model")),
+                    ("query".to_string(), canonical("b.query")),
+                ])
+            ),
+        ]
+    );
+
+    // The program refers to a file that the input does not have.
+    config.inputs.get_mut("d").unwrap().files.remove("query");
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn generated_inputs() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    config.programs.remove("c");
+    config.inputs.remove("e");
+    let d = config.inputs.get_mut("d").unwrap();
+    d.file = None;
+    d.generator = Some(Generator {
+        command: vec![
+            "./Cargo.toml".to_string(),
+            "--seed".to_string(),
+            "{seed}".to_string(),
+        ],
+        count: 3,
+        seed_start: 7,
+    });
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    let generator = Path::new("./Cargo.toml")
+        .canonicalize()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let runs: Vec<(&str, GeneratedInput)> = experiment
+        .runs
+        .iter()
+        .map(|run| {
+            (
+                run.generated_from_input.as_deref().unwrap(),
+                run.input.generator.clone().unwrap(),
+            )
+        })
+        .collect();
+
+    let generated = |seed: u64| GeneratedInput {
+        command: vec![generator.clone(), "--seed".to_string(), seed.to_string()],
+        seed,
+    };
+
+    assert_eq!(
+        runs,
+        vec![
+            ("d_seed_7", generated(7)),
+            ("d_seed_8", generated(8)),
+            ("d_seed_9", generated(9)),
+        ]
+    );
+
+    // The generated inputs remember their input, but they are not globbed.
+    assert!(experiment.inputs.values().all(|input| {
+        input.metadata.origin.as_deref() == Some("d") && input.metadata.glob_from.is_none()
+    }));
+
+    // The last seed is the largest there is.
+    let generator = config
+        .inputs
+        .get_mut("d")
+        .unwrap()
+        .generator
+        .as_mut()
+        .unwrap();
+    generator.seed_start = u64::MAX - 2;
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_ok());
+
+    let generator = config
+        .inputs
+        .get_mut("d")
+        .unwrap()
+        .generator
+        .as_mut()
+        .unwrap();
+    generator.count = 4;
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+
+    config.inputs.get_mut("d").unwrap().file = Some(PathBuf::from("./Cargo.toml"));
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+}
+
+#[test]
+fn expected_output_by_stem() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = test_config(&tempdir);
+
+    for file in ["a.in", "b.in", "a.out", "c.out"] {
+        fs::write(tempdir.path().join(file), "").unwrap();
+    }
+
+    let path = |file: &str| tempdir.path().join(file).to_string_lossy().to_string();
+
+    config.inputs.remove("e");
+    let d = config.inputs.get_mut("d").unwrap();
+    d.file = None;
+    d.glob = Some(path("*.in"));
+    d.expected = Some(path("*.out"));
+
+    config.compare = Comparison::Numeric { tolerance: -1.0 };
+    assert!(Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).is_err());
+
+    config.compare = Comparison::Whitespace;
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    assert_eq!(
+        experiment.inputs["d_i_a"].expected,
+        Some(tempdir.path().join("a.out").canonicalize().unwrap())
+    );
+    assert_eq!(experiment.inputs["d_i_b"].expected, None);
+    assert_eq!(experiment.compare, Comparison::Whitespace);
+}
+
+#[test]
+fn lockfile_without_custom_metrics() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let config = test_config(&tempdir);
+
+    let experiment =
+        Experiment::from_config(&config, Local::now(), Environment::Local, &REAL_FS).unwrap();
+
+    // A lockfile written before runs had a custom metrics file.
+    let lockfile: String = toml::to_string(&experiment)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("custom_metrics_path"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+
+    let loaded: Experiment = toml::from_str(&lockfile).unwrap();
+
+    assert_eq!(loaded.runs.len(), experiment.runs.len());
+    assert!(loaded
+        .runs
+        .iter()
+        .all(|run| run.custom_metrics_path.is_none()));
+}
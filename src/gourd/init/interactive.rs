@@ -1,9 +1,11 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::Context;
 use anyhow::Result;
 use gourd_lib::bailc;
+use gourd_lib::config::compare::Comparison;
 use gourd_lib::config::slurm::SlurmConfig;
 use gourd_lib::config::Config;
 use gourd_lib::constants::CMD_STYLE;
@@ -59,6 +61,18 @@ pub fn init_interactive(
         labels: None,
         input_schema: None,
         warn_on_label_overlap: false,
+        sampling_interval: None,
+        performance_counters: false,
+        repetitions: None,
+        seed: None,
+        search: None,
+        constraints: vec![],
+        zip: vec![],
+        tune: None,
+        compare: Comparison::default(),
+        agreement: None,
+        env: BTreeMap::new(),
+        clear_env: false,
     };
 
     let custom_paths = if script_mode {
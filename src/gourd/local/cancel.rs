@@ -0,0 +1,126 @@
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time::Instant;
+
+use anyhow::Result;
+#[cfg(not(unix))]
+use gourd_lib::bailc;
+#[cfg(unix)]
+use gourd_lib::constants::CANCEL_KILL_GRACE;
+#[cfg(unix)]
+use gourd_lib::constants::LIMIT_POLL_PERIOD;
+use gourd_lib::experiment::Experiment;
+use gourd_lib::file_system::FileOperations;
+#[cfg(unix)]
+use gourd_lib::measurement::Metrics;
+#[cfg(unix)]
+use gourd_lib::process::RecordedProcess;
+#[cfg(unix)]
+use log::debug;
+#[cfg(unix)]
+use log::warn;
+
+/// Cancel local runs of an experiment, and mark them as cancelled so that they
+/// can be rerun.
+///
+/// The wrappers of the runs that are running and their programs are asked to
+/// terminate, and killed if they have not exited after [CANCEL_KILL_GRACE].
+/// Runs that have not started yet will not be started by the runner.
+#[cfg(unix)]
+pub fn cancel_runs(
+    experiment: &Experiment,
+    runs: &[usize],
+    file_system: &impl FileOperations,
+) -> Result<()> {
+    let targets: Vec<libc::pid_t> = runs
+        .iter()
+        .flat_map(|run| running_processes(experiment, *run))
+        .collect();
+
+    debug!("Terminating the processes {targets:?}");
+
+    for target in &targets {
+        signal(*target, libc::SIGTERM);
+    }
+
+    let deadline = Instant::now() + CANCEL_KILL_GRACE;
+
+    while targets.iter().any(|target| signal(*target, 0)) && Instant::now() < deadline {
+        thread::sleep(LIMIT_POLL_PERIOD);
+    }
+
+    for target in targets.iter().filter(|target| signal(**target, 0)) {
+        warn!("Process {target} did not terminate in time, killing it");
+        signal(*target, libc::SIGKILL);
+    }
+
+    mark_cancelled(experiment, runs, file_system)
+}
+
+/// Cancel local runs of an experiment, and mark them as cancelled so that they
+/// can be rerun.
+#[cfg(not(unix))]
+pub fn cancel_runs(_: &Experiment, _: &[usize], _: &impl FileOperations) -> Result<()> {
+    bailc!("Cancelling local runs is not supported on this platform");
+}
+
+/// Record in the metrics of the runs that they were cancelled, and forget
+/// their processes.
+#[cfg(unix)]
+fn mark_cancelled(
+    experiment: &Experiment,
+    runs: &[usize],
+    file_system: &impl FileOperations,
+) -> Result<()> {
+    for run in runs {
+        file_system.try_write_toml(&experiment.runs[*run].metrics_path, &Metrics::Cancelled)?;
+
+        let _ = fs::remove_file(experiment.wrapper_pid_file(*run));
+        let _ = fs::remove_file(experiment.program_pid_file(*run));
+    }
+
+    Ok(())
+}
+
+/// The processes of a run that are still running, as targets of `kill`.
+///
+/// The wrapper is a single process, while the wrapper runs the program of a
+/// local run in its own process group together with anything it has spawned.
+/// Pids are only trusted while the recorded processes are still running, so
+/// that stale pid files do not lead to signalling unrelated processes that
+/// reuse their pids.
+#[cfg(unix)]
+fn running_processes(experiment: &Experiment, run: usize) -> Vec<libc::pid_t> {
+    let wrapper = RecordedProcess::read(&experiment.wrapper_pid_file(run))
+        .filter(RecordedProcess::is_running);
+    let program = RecordedProcess::read(&experiment.program_pid_file(run));
+
+    // The group is the program's as long as the program or its wrapper runs,
+    // since its id is not reused while the group has members.
+    let group = program
+        .filter(|program| program.is_running() || wrapper.is_some())
+        .map(|program| -(program.pid as libc::pid_t));
+
+    wrapper
+        .map(|wrapper| wrapper.pid as libc::pid_t)
+        .into_iter()
+        .chain(group)
+        .filter(|target| signal(*target, 0))
+        .collect()
+}
+
+/// Send a signal to a `kill` target, returning whether it exists.
+///
+/// Signal 0 only checks that the target exists.
+#[cfg(unix)]
+fn signal(target: libc::pid_t, signal: libc::c_int) -> bool {
+    // SAFETY: sending a signal has no memory safety requirements.
+    unsafe { libc::kill(target, signal) == 0 }
+}
+
+#[cfg(all(test, unix))]
+#[path = "tests/cancel.rs"]
+mod tests;
@@ -1,24 +1,84 @@
 use std::env;
+use std::num::NonZeroUsize;
+use std::thread;
 
 use anyhow::Result;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
+use log::debug;
 use log::trace;
 
 use self::runner::run_locally;
+use self::runner::Budget;
+use self::runner::Runner;
+use crate::cli::def::LocalArgs;
 use crate::status::DynamicStatus;
 use crate::wrapper::wrap;
 
-/// The (first iteration) thread pool implementation.
+/// The work queue that runs the tasks.
 pub mod runner;
 
+/// Cancelling the runs of local experiments.
+pub mod cancel;
+
+/// Running experiments in the background.
+pub mod supervisor;
+
+/// The resources that the runs can take on this computer, as requested on the
+/// command line and otherwise as much as the computer has.
+pub fn local_budget(args: &LocalArgs) -> Budget {
+    let cores = thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+
+    let jobs = match args.jobs {
+        Some(jobs) => jobs,
+        None if args.sequential => NonZeroUsize::MIN,
+        None => cores,
+    };
+
+    let budget = Budget {
+        jobs,
+        cpus: args.cpus.unwrap_or(cores).get(),
+        memory: args.memory.map(NonZeroUsize::get).or_else(physical_memory),
+    };
+
+    debug!("Running locally within {budget:?}");
+
+    budget
+}
+
+/// The memory of this computer in MB, if it can be found out.
+#[cfg(unix)]
+fn physical_memory() -> Option<usize> {
+    // SAFETY: sysconf only reads system configuration.
+    let (pages, page_size) = unsafe {
+        (
+            libc::sysconf(libc::_SC_PHYS_PAGES),
+            libc::sysconf(libc::_SC_PAGESIZE),
+        )
+    };
+
+    if pages <= 0 || page_size <= 0 {
+        return None;
+    }
+
+    usize::try_from(pages as u64 * page_size as u64 / (1024 * 1024)).ok()
+}
+
+/// The memory of this computer in MB, if it can be found out.
+#[cfg(not(unix))]
+fn physical_memory() -> Option<usize> {
+    None
+}
+
 /// Run an experiment locally, as specified in the config file.
+///
+/// Returns how many runs will have completed once the started runs have, and
+/// the runner that runs them in the background.
 pub async fn run_local(
     experiment: &mut Experiment,
     fs: &impl FileOperations,
-    force: bool,
-    sequential: bool,
-) -> Result<usize> {
+    budget: Budget,
+) -> Result<(usize, Runner)> {
     let status = experiment.status(fs)?;
     let pre_fin = status.iter().filter(|r| r.1.is_completed()).count();
 
@@ -29,7 +89,7 @@ pub async fn run_local(
     experiment.save(fs)?;
 
     let len = cmds.len();
-    run_locally(cmds, force, sequential).await?;
+    let runner = run_locally(cmds, budget).await?;
 
-    Ok(len + pre_fin)
+    Ok((len + pre_fin, runner))
 }
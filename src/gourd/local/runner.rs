@@ -1,78 +1,240 @@
+use std::collections::HashMap;
+use std::fs;
 use std::io;
-use std::process;
+use std::num::NonZeroUsize;
+use std::path::Path;
 use std::process::Command;
 use std::process::Output;
+use std::process::Stdio;
+use std::sync::Arc;
 
 use anyhow::Context;
 use anyhow::Result;
-use gourd_lib::bailc;
-use gourd_lib::constants::NAME_STYLE;
-use gourd_lib::constants::PRIMARY_STYLE;
-use gourd_lib::constants::TASK_LIMIT;
+use gourd_lib::config::slurm::ResourceLimits;
+use gourd_lib::measurement::Metrics;
+use gourd_lib::process::RecordedProcess;
+use log::debug;
 use log::error;
 use log::trace;
+use log::warn;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
 use tokio::task::JoinSet;
 
-/// Run a list of tasks locally in a multithreaded way.
-pub async fn run_locally(tasks: Vec<Command>, force: bool, sequential: bool) -> Result<()> {
-    if tasks.len() > TASK_LIMIT && !force && !sequential {
-        bailc!(
-          "task limit exceeded", ;
-          "{PRIMARY_STYLE}gourd{PRIMARY_STYLE:#} will not run more than \
-          {TASK_LIMIT} jobs on local, doing so may possibly exhaust your file descriptors", ;
-          "if you are {NAME_STYLE}absolutely{NAME_STYLE:#} sure that you \
-          want to run {} tasks use the {PRIMARY_STYLE}--force{PRIMARY_STYLE:#} \
-          option", tasks.len()
-        )
+use crate::wrapper::WrappedRun;
+
+/// The resources that the runs executing at the same time can take together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// How many runs can execute at the same time.
+    pub jobs: NonZeroUsize,
+
+    /// How many cpus the runs can take.
+    pub cpus: usize,
+
+    /// How much memory in MB the runs can take, if it is bounded.
+    pub memory: Option<usize>,
+}
+
+impl Budget {
+    /// Whether runs taking `cpus` and `memory` together fit in the budget.
+    pub fn fits(&self, cpus: usize, memory: usize) -> bool {
+        cpus <= self.cpus && self.memory.is_none_or(|budget| memory <= budget)
     }
+}
 
-    #[cfg(not(tarpaulin_include))] // Tarpaulin can't calculate the coverage correctly
-    tokio::spawn(async move {
-        /// Error in case of wrapper failure.
-        fn handle_output(join: io::Result<Output>) {
-            if let Ok(exit) = join {
-                if !exit.status.success() {
-                    error!("Failed to run gourd wrapper: {:?}", exit.status);
-                    error!(
-                        "Wrapper returned: {}",
-                        String::from_utf8(exit.stderr).unwrap()
-                    );
-                    process::exit(1);
-                }
-            } else {
-                error!("Couldn't start the wrapper: {join:?}");
-                error!("Ensure that the wrapper is accesible. (see man gourd)");
-                process::exit(1);
+/// The runs of a local experiment, running in the background.
+#[derive(Debug)]
+pub struct Runner {
+    /// The task that runs them, which finishes once they all have.
+    pub handle: JoinHandle<()>,
+
+    /// Notified when no more runs should be started.
+    stop: Arc<Notify>,
+}
+
+impl Runner {
+    /// Start no more runs. The runs that already started still finish, and
+    /// the failures of their wrappers are still recorded.
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+}
+
+/// The cpus and memory in MB that a run with these `limits` takes.
+fn demand(limits: &ResourceLimits) -> (usize, usize) {
+    (limits.cpus, limits.cpus * limits.mem_per_cpu)
+}
+
+/// The metrics recorded for a run, if there are any.
+fn recorded_metrics(metrics_path: &Path) -> Option<Metrics> {
+    fs::read_to_string(metrics_path)
+        .ok()
+        .and_then(|metrics| toml::from_str::<Metrics>(&metrics).ok())
+}
+
+/// Record in the metrics of a run that its wrapper failed, unless the wrapper
+/// already did so itself or the run was cancelled.
+fn record_wrapper_failure(metrics_path: &Path, reason: String) -> Result<()> {
+    if let Some(Metrics::WrapperFailed { .. } | Metrics::Cancelled) = recorded_metrics(metrics_path)
+    {
+        return Ok(());
+    }
+
+    fs::write(
+        metrics_path,
+        toml::to_string(&Metrics::WrapperFailed { reason })
+            .context("Could not serialize the wrapper failure")?,
+    )
+    .context(format!(
+        "Could not write to the result file {:?}",
+        metrics_path
+    ))
+}
+
+/// Run a list of tasks locally, starting a task only once its resource limits
+/// fit in the `budget` next to the running tasks.
+///
+/// The tasks are started in order, except that a task that does not fit yet
+/// is passed by later tasks that do.
+/// A task that does not fit even on its own runs once nothing else is running,
+/// so any amount and any size of tasks can be run.
+/// When the wrapper of a task fails, this is recorded in the metrics of its
+/// run and the other tasks keep running.
+/// Tasks whose runs were cancelled before they started are skipped.
+///
+/// The tasks run in the background, the returned [Runner] finishes once they
+/// all have, or once the tasks that started have after it is stopped.
+pub async fn run_locally(tasks: Vec<WrappedRun>, budget: Budget) -> Result<Runner> {
+    let oversized = tasks
+        .iter()
+        .filter(|task| {
+            let (cpus, memory) = demand(&task.limits);
+            !budget.fits(cpus, memory)
+        })
+        .count();
+
+    if oversized > 0 {
+        warn!(
+            "{oversized} runs need more resources than this computer has, \
+            they will run on their own"
+        );
+    }
+
+    let stop = Arc::new(Notify::new());
+    let stopping = stop.clone();
+
+    let handle = tokio::spawn(async move {
+        /// Why the wrapper failed, if it failed.
+        fn failure(output: io::Result<Output>) -> Option<String> {
+            match output {
+                Ok(exit) if exit.status.success() => None,
+                Ok(exit) => Some(format!(
+                    "The wrapper exited with {}: {}",
+                    exit.status,
+                    String::from_utf8_lossy(&exit.stderr).trim()
+                )),
+                Err(err) => Some(format!(
+                    "Could not start the wrapper, ensure that it is accessible (see man gourd): \
+                    {err}"
+                )),
             }
         }
 
-        if sequential {
-            for mut task in tasks {
-                trace!("Running task: {:?}", task);
-                handle_output(task.output());
-            }
-        } else {
-            let mut set = JoinSet::new();
+        let mut queue = tasks;
+        let mut set = JoinSet::new();
+        let mut running = HashMap::new();
+        let (mut used_cpus, mut used_memory) = (0, 0);
 
-            for mut task in tasks {
-                trace!("Queueing task: {:?}", task);
-                set.spawn_blocking(move || task.output());
+        while !queue.is_empty() || !set.is_empty() {
+            let mut next = 0;
+
+            while next < queue.len() && set.len() < budget.jobs.get() {
+                let (cpus, memory) = demand(&queue[next].limits);
+
+                if !set.is_empty() && !budget.fits(used_cpus + cpus, used_memory + memory) {
+                    next += 1;
+                    continue;
+                }
+
+                let task = queue.remove(next);
+
+                if let Some(Metrics::Cancelled) = recorded_metrics(&task.metrics_path) {
+                    debug!("Skipping the cancelled run of {:?}", task.metrics_path);
+                    continue;
+                }
+
+                trace!("Running task: {:?}", task.command);
+
+                used_cpus += cpus;
+                used_memory += memory;
+
+                let spawned = set.spawn_blocking(move || run_task(task.command, &task.pid_path));
+                running.insert(spawned.id(), (task.metrics_path, cpus, memory));
             }
 
-            while let Some(result) = set.join_next().await {
-                if let Ok(join) = result {
-                    handle_output(join);
-                } else {
-                    error!("Could not join the child in the multithreaded runtime");
-                    process::exit(1);
+            let joined = tokio::select! {
+                joined = set.join_next_with_id() => joined,
+                _ = stopping.notified(), if !queue.is_empty() => {
+                    debug!("Starting none of the {} runs that have not started", queue.len());
+                    queue.clear();
+                    continue;
+                }
+            };
+
+            // A task that could not be joined failed like its wrapper did.
+            let (id, reason) = match joined {
+                Some(Ok((id, output))) => (id, failure(output)),
+                Some(Err(err)) => (err.id(), Some(format!("The wrapper task failed: {err}"))),
+                None => continue,
+            };
+
+            let Some((metrics_path, cpus, memory)) = running.remove(&id) else {
+                continue;
+            };
+
+            used_cpus -= cpus;
+            used_memory -= memory;
+
+            if let Some(reason) = reason {
+                debug!("The wrapper failed for {metrics_path:?}: {reason}");
+
+                if let Err(err) = record_wrapper_failure(&metrics_path, reason) {
+                    error!("Could not record the failure of the wrapper: {err:#}");
                 }
             }
         }
-
-        Result::<()>::Ok(())
     });
 
-    Ok(())
+    Ok(Runner { handle, stop })
+}
+
+/// Run the wrapper `command` of a task, recording its pid at `pid_path` while
+/// it runs.
+///
+/// The wrapper records the pid of the program next to it.
+fn run_task(mut command: Command, pid_path: &Path) -> io::Result<Output> {
+    // The pids only serve to cancel the run, so the run goes on even if they
+    // cannot be recorded.
+    if let Some(Err(err)) = pid_path.parent().map(fs::create_dir_all) {
+        debug!("Could not create the folder for the pid at {pid_path:?}: {err}");
+    }
+
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Err(err) = RecordedProcess::of(child.id()).record(pid_path) {
+        debug!("Could not record the pid of the wrapper at {pid_path:?}: {err}");
+    }
+
+    let output = child.wait_with_output();
+
+    let _ = fs::remove_file(pid_path);
+
+    output
 }
 
 #[cfg(test)]
@@ -0,0 +1,262 @@
+#[cfg(unix)]
+use std::env;
+use std::fs;
+#[cfg(unix)]
+use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::process;
+#[cfg(unix)]
+use std::process::Command;
+#[cfg(unix)]
+use std::process::Stdio;
+
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(not(unix))]
+use gourd_lib::bailc;
+use gourd_lib::ctx;
+use gourd_lib::experiment::Experiment;
+use gourd_lib::file_system::FileOperations;
+use gourd_lib::process::RecordedProcess;
+use log::debug;
+use log::info;
+
+#[cfg(unix)]
+use super::cancel::cancel_runs;
+#[cfg(unix)]
+use super::run_local;
+use super::runner::Budget;
+use crate::cli::def::LocalArgs;
+#[cfg(unix)]
+use crate::status::DynamicStatus;
+#[cfg(unix)]
+use crate::status::FsState;
+
+/// The file that the supervisor of an experiment logs to.
+pub fn supervisor_log_file(experiment: &Experiment) -> PathBuf {
+    experiment
+        .home
+        .join(format!("{}.supervisor.log", experiment.seq))
+}
+
+/// Start a supervisor that runs the unscheduled runs of a local experiment in
+/// the background, and record its pid.
+///
+/// The supervisor is a separate `gourd` process in its own session, so that
+/// it keeps running after the terminal is closed or the user logs out.
+#[cfg(unix)]
+pub fn detach(experiment: &Experiment, config: &Path, local: &LocalArgs) -> Result<u32> {
+    let log_path = supervisor_log_file(experiment);
+    let log = File::create(&log_path).with_context(ctx!(
+        "Could not create the log of the supervisor at {log_path:?}", ;
+        "",
+    ))?;
+
+    let mut command = Command::new(env::current_exe()?);
+
+    command
+        .arg("--script")
+        .arg("--config")
+        .arg(config)
+        .arg("supervise")
+        .arg(experiment.seq.to_string())
+        .args(local_args(local))
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    // SAFETY: setsid is async-signal-safe, and only detaches the child from
+    // the session of this terminal.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    let supervisor = command.spawn().with_context(ctx!(
+        "Could not start the supervisor of experiment {}", experiment.seq;
+        "",
+    ))?;
+
+    record_supervisor(experiment, supervisor.id())?;
+
+    Ok(supervisor.id())
+}
+
+/// Start a supervisor that runs the unscheduled runs of a local experiment in
+/// the background, and record its pid.
+#[cfg(not(unix))]
+pub fn detach(_: &Experiment, _: &Path, _: &LocalArgs) -> Result<u32> {
+    bailc!(
+        "Detached experiments are not supported on this platform", ;
+        "", ;
+        "Run the experiment without --detach",
+    );
+}
+
+/// The arguments of the `supervise` command that make it run like `local`.
+fn local_args(local: &LocalArgs) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if let Some(jobs) = local.jobs {
+        args.extend(["--jobs".to_string(), jobs.to_string()]);
+    }
+
+    if local.sequential {
+        args.push("--sequential".to_string());
+    }
+
+    if let Some(cpus) = local.cpus {
+        args.extend(["--cpus".to_string(), cpus.to_string()]);
+    }
+
+    if let Some(memory) = local.memory {
+        args.extend(["--memory".to_string(), memory.to_string()]);
+    }
+
+    args
+}
+
+/// Record the process with `pid` as the supervisor of the experiment.
+fn record_supervisor(experiment: &Experiment, pid: u32) -> Result<()> {
+    let pid_path = experiment.supervisor_pid_file();
+
+    RecordedProcess::of(pid)
+        .record(&pid_path)
+        .with_context(ctx!(
+            "Could not record the supervisor of experiment {} at {pid_path:?}", experiment.seq;
+            "",
+        ))
+}
+
+/// Run the unscheduled runs of a local experiment until they have finished, as
+/// the supervisor started by [detach].
+///
+/// When the supervisor is asked to terminate, it starts no more runs but waits
+/// for the runs that already started to finish, recording the failures of
+/// their wrappers. When it is asked again, it cancels the runs that are still
+/// running.
+#[cfg(unix)]
+pub async fn supervise(
+    experiment: &mut Experiment,
+    file_system: &impl FileOperations,
+    budget: Budget,
+) -> Result<()> {
+    use tokio::signal::unix::signal;
+    use tokio::signal::unix::SignalKind;
+
+    let pid = process::id();
+    record_supervisor(experiment, pid)?;
+
+    info!("Supervising experiment {} as process {pid}", experiment.seq);
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let (_, mut runner) = run_local(experiment, file_system, budget).await?;
+    let mut stopped = false;
+
+    loop {
+        tokio::select! {
+            result = &mut runner.handle => {
+                result.context("The runner of the experiment failed")?;
+                break;
+            }
+            _ = terminate.recv() => {
+                if stopped {
+                    info!("Cancelling the runs that are still running");
+                    cancel_running(experiment, file_system)?;
+                } else {
+                    info!("Stopping, waiting for the runs that already started to finish");
+                    runner.stop();
+                    stopped = true;
+                }
+            }
+        }
+    }
+
+    if stopped {
+        info!("Experiment {} stopped", experiment.seq);
+    } else {
+        info!("Experiment {} finished", experiment.seq);
+    }
+
+    if running_supervisor(experiment) == Some(pid) {
+        let _ = fs::remove_file(experiment.supervisor_pid_file());
+    }
+
+    Ok(())
+}
+
+/// Cancel the runs of a local experiment that are running.
+#[cfg(unix)]
+fn cancel_running(experiment: &Experiment, file_system: &impl FileOperations) -> Result<()> {
+    let running: Vec<usize> = experiment
+        .status(file_system)?
+        .iter()
+        .filter(|(_, status)| status.fs_status.completion == FsState::Running)
+        .map(|(id, _)| *id)
+        .collect();
+
+    cancel_runs(experiment, &running, file_system)
+}
+
+/// Run the unscheduled runs of a local experiment until they have finished, as
+/// the supervisor started by [detach].
+#[cfg(not(unix))]
+pub async fn supervise(_: &mut Experiment, _: &impl FileOperations, _: Budget) -> Result<()> {
+    bailc!("Detached experiments are not supported on this platform");
+}
+
+/// The pid of the supervisor of a detached local experiment, if it is still
+/// running.
+///
+/// A process that only reuses the pid of a supervisor that has since exited is
+/// not its supervisor.
+pub fn running_supervisor(experiment: &Experiment) -> Option<u32> {
+    RecordedProcess::read(&experiment.supervisor_pid_file())
+        .filter(RecordedProcess::is_running)
+        .map(|supervisor| supervisor.pid)
+}
+
+/// Stop the supervisor of a detached local experiment from starting more runs,
+/// returning its pid if it was running.
+///
+/// The supervisor keeps running until the runs that it started have finished.
+pub fn stop_supervisor(experiment: &Experiment) -> Result<Option<u32>> {
+    let Some(pid) = running_supervisor(experiment) else {
+        return Ok(None);
+    };
+
+    debug!(
+        "Asking the supervisor {pid} of experiment {} to stop",
+        experiment.seq
+    );
+
+    terminate(pid).with_context(ctx!(
+        "Could not stop the supervisor of experiment {}", experiment.seq;
+        "Its process id is {pid}",
+    ))?;
+
+    Ok(Some(pid))
+}
+
+/// Ask the process with this pid to terminate.
+#[cfg(unix)]
+fn terminate(pid: u32) -> Result<()> {
+    // SAFETY: sending a signal has no memory safety requirements.
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
+
+/// Ask the process with this pid to terminate.
+#[cfg(not(unix))]
+fn terminate(_: u32) -> Result<()> {
+    bailc!("Detached experiments are not supported on this platform");
+}
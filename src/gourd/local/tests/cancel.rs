@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Command;
+
+use super::*;
+use crate::test_utils::create_sample_experiment;
+
+/// Test that only recorded processes that are still running are signalled,
+/// and not later processes that reuse their pids.
+#[test]
+fn test_stale_pids() {
+    let (experiment, _) = create_sample_experiment(BTreeMap::new(), BTreeMap::new());
+    fs::create_dir_all(experiment.wrapper_pid_file(0).parent().unwrap()).unwrap();
+
+    let mut sleeping = Command::new("sleep").arg("30").spawn().unwrap();
+    let current = RecordedProcess::of(sleeping.id());
+
+    // The recorded process started at another time than the one with its pid.
+    let stale = RecordedProcess {
+        start: current.start.map(|start| start + 1),
+        ..current
+    };
+
+    stale.record(&experiment.wrapper_pid_file(0)).unwrap();
+    stale.record(&experiment.program_pid_file(0)).unwrap();
+
+    if current.start.is_some() {
+        assert_eq!(running_processes(&experiment, 0), vec![]);
+    }
+
+    current.record(&experiment.wrapper_pid_file(0)).unwrap();
+
+    assert_eq!(
+        running_processes(&experiment, 0),
+        vec![sleeping.id() as libc::pid_t]
+    );
+
+    sleeping.kill().unwrap();
+    sleeping.wait().unwrap();
+}
@@ -1,9 +1,17 @@
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::process::Command;
 
-use gourd_lib::constants::TASK_LIMIT;
+use gourd_lib::config::slurm::ResourceLimits;
+use gourd_lib::measurement::Metrics;
+use gourd_lib::process::RecordedProcess;
+use tempdir::TempDir;
 
 use crate::local::runner::run_locally;
+use crate::local::runner::Budget;
 use crate::test_utils::get_compiled_example;
+use crate::wrapper::WrappedRun;
 
 /// Run a naive fibonacci implementation using the local runner,
 /// assert that they run correctly
@@ -15,14 +23,14 @@ async fn runner_fibonacci_test() {
     let (out, _tmp) = get_compiled_example(include_str!("test_resources/fibonacci.rs"), None);
 
     let test_cases = vec![38u128, 36u128, 34u128, 30u128, 24u128];
-    let mut commands: Vec<Command> = vec![];
+    let mut commands: Vec<WrappedRun> = vec![];
     for value in test_cases {
         let mut cmd = Command::new(&out);
         cmd.arg(value.to_string());
-        commands.push(cmd);
+        commands.push(wrapped(cmd, ResourceLimits::default(), PathBuf::new()));
     }
 
-    let results = run_locally(commands, false, false).await;
+    let results = run_locally(commands, budget(4, 4)).await;
 
     assert!(results.is_ok(), "Executing children processes failed");
 }
@@ -30,28 +38,269 @@ async fn runner_fibonacci_test() {
 /// Test sleeping in the thread pool (don't drown tho)
 #[tokio::test]
 async fn runner_sleep_test() {
-    let mut commands: Vec<Command> = vec![];
+    let mut commands: Vec<WrappedRun> = vec![];
     for value in [4, 3, 2, 1, 2, 3] {
         let mut cmd = Command::new("sleep");
         cmd.arg(value.to_string());
-        commands.push(cmd);
+        commands.push(wrapped(cmd, ResourceLimits::default(), PathBuf::new()));
     }
 
-    let results = run_locally(commands, false, false).await;
+    let results = run_locally(commands, budget(4, 4)).await;
 
     assert!(results.is_ok(), "Executing children processes failed");
 }
 
-/// Test hitting the task limit
+/// The invocation of a `command` for a run with the `limits`.
+fn wrapped(command: Command, limits: ResourceLimits, metrics_path: PathBuf) -> WrappedRun {
+    WrappedRun {
+        command,
+        limits,
+        pid_path: metrics_path.with_extension("pid"),
+        metrics_path,
+    }
+}
+
+/// A budget of `jobs` runs and `cpus` cpus, with unbounded memory.
+fn budget(jobs: usize, cpus: usize) -> Budget {
+    Budget {
+        jobs: NonZeroUsize::new(jobs).unwrap(),
+        cpus,
+        memory: None,
+    }
+}
+
+/// Run a task for each of the `limits` within the `budget`, and return the
+/// most tasks that ran at the same time.
+async fn most_at_once(limits: &[ResourceLimits], budget: Budget) -> usize {
+    let tmp = TempDir::new("runner").unwrap();
+
+    // Every task holds a marker for a while, and notes how many markers there
+    // were once it had taken its own.
+    let commands = limits
+        .iter()
+        .enumerate()
+        .map(|(task, limits)| {
+            let mut cmd = Command::new("sh");
+            cmd.current_dir(tmp.path()).arg("-c").arg(format!(
+                "touch running_{task}; ls | grep -c '^running_' >> counts; sleep 0.05; \
+                rm running_{task}; touch done_{task}"
+            ));
+            wrapped(cmd, *limits, tmp.path().join(format!("metrics_{task}")))
+        })
+        .collect();
+
+    let runner = run_locally(commands, budget).await.unwrap();
+    runner.handle.await.unwrap();
+
+    let counts: Vec<usize> = fs::read_to_string(tmp.path().join("counts"))
+        .unwrap()
+        .lines()
+        .map(|count| count.parse().unwrap())
+        .collect();
+
+    assert_eq!(counts.len(), limits.len());
+    counts.into_iter().max().unwrap()
+}
+
+/// Test that no more than the allowed amount of tasks run at the same time,
+/// however many there are.
+#[tokio::test]
+async fn test_jobs_limit() {
+    let limits = vec![ResourceLimits::default(); 30];
+
+    assert_eq!(most_at_once(&limits, budget(3, 8)).await, 3);
+    assert_eq!(most_at_once(&limits, budget(1, 8)).await, 1);
+}
+
+/// Test that tasks only run at the same time when their cpus fit together.
+#[tokio::test]
+async fn test_cpus_limit() {
+    let limits = vec![
+        ResourceLimits {
+            cpus: 2,
+            ..ResourceLimits::default()
+        };
+        20
+    ];
+
+    assert_eq!(most_at_once(&limits, budget(8, 3)).await, 1);
+    assert_eq!(most_at_once(&limits, budget(8, 4)).await, 2);
+}
+
+/// Test that tasks only run at the same time when their memory fits together.
+#[tokio::test]
+async fn test_memory_limit() {
+    let limits = vec![
+        ResourceLimits {
+            mem_per_cpu: 600,
+            ..ResourceLimits::default()
+        };
+        20
+    ];
+
+    let budget = Budget {
+        memory: Some(1000),
+        ..budget(8, 8)
+    };
+
+    assert_eq!(most_at_once(&limits, budget).await, 1);
+}
+
+/// Test that tasks larger than the budget still run, on their own.
+#[tokio::test]
+async fn test_oversized_task() {
+    let limits = vec![
+        ResourceLimits {
+            cpus: 16,
+            ..ResourceLimits::default()
+        };
+        10
+    ];
+
+    assert_eq!(most_at_once(&limits, budget(8, 4)).await, 1);
+}
+
+/// Test that a failing wrapper is recorded for its run, and that the other
+/// runs still complete.
+#[tokio::test]
+async fn test_wrapper_failure() {
+    let tmp = TempDir::new("runner").unwrap();
+
+    let mut failing = Command::new("sh");
+    failing.arg("-c").arg("echo broken >&2; exit 3");
+
+    let mut missing = Command::new(tmp.path().join("no_such_wrapper"));
+    missing.arg("run");
+
+    let mut succeeding = Command::new("touch");
+    succeeding.arg(tmp.path().join("done"));
+
+    let commands = vec![
+        wrapped(
+            failing,
+            ResourceLimits::default(),
+            tmp.path().join("failing"),
+        ),
+        wrapped(
+            missing,
+            ResourceLimits::default(),
+            tmp.path().join("missing"),
+        ),
+        wrapped(
+            succeeding,
+            ResourceLimits::default(),
+            tmp.path().join("succeeding"),
+        ),
+    ];
+
+    let runner = run_locally(commands, budget(1, 1)).await.unwrap();
+    runner.handle.await.unwrap();
+
+    let reason = |name: &str| match fs::read_to_string(tmp.path().join(name))
+        .ok()
+        .and_then(|metrics| toml::from_str::<Metrics>(&metrics).ok())
+    {
+        Some(Metrics::WrapperFailed { reason }) => Some(reason),
+        _ => None,
+    };
+
+    assert!(tmp.path().join("done").exists());
+    assert!(reason("failing").unwrap().contains("broken"));
+    assert!(reason("missing")
+        .unwrap()
+        .contains("Could not start the wrapper"));
+    assert!(!tmp.path().join("succeeding").exists());
+}
+
+/// Test that the pid of a wrapper is recorded while it runs, and removed after.
+#[tokio::test]
+async fn test_wrapper_pid() {
+    let tmp = TempDir::new("runner").unwrap();
+    let pid_path = tmp.path().join("pids/0.pid");
+
+    let mut cmd = Command::new("sh");
+    // The pid is recorded once the wrapper has started, so it waits for it.
+    cmd.arg("-c").arg(format!(
+        "echo $$ > {0:?}.own; while [ ! -s {0:?} ]; do sleep 0.01; done; cp {0:?} {0:?}.seen",
+        pid_path
+    ));
+
+    let commands = vec![WrappedRun {
+        command: cmd,
+        limits: ResourceLimits::default(),
+        metrics_path: tmp.path().join("metrics"),
+        pid_path: pid_path.clone(),
+    }];
+
+    let runner = run_locally(commands, budget(1, 1)).await.unwrap();
+    runner.handle.await.unwrap();
+
+    let own = fs::read_to_string(pid_path.with_extension("pid.own")).unwrap();
+    let seen = RecordedProcess::read(&pid_path.with_extension("pid.seen")).unwrap();
+
+    assert_eq!(own.trim(), seen.pid.to_string());
+    assert!(!pid_path.exists());
+}
+
+/// Test that a run that was cancelled before it started is skipped.
 #[tokio::test]
-async fn test_limit() {
-    let mut commands: Vec<Command> = vec![];
-    for _ in 0..TASK_LIMIT + 1 {
-        let cmd = Command::new("sleep");
-        commands.push(cmd);
+async fn test_cancelled_task() {
+    let tmp = TempDir::new("runner").unwrap();
+    let metrics_path = tmp.path().join("cancelled");
+
+    fs::write(&metrics_path, toml::to_string(&Metrics::Cancelled).unwrap()).unwrap();
+
+    let mut cmd = Command::new("touch");
+    cmd.arg(tmp.path().join("started"));
+
+    let commands = vec![wrapped(
+        cmd,
+        ResourceLimits::default(),
+        metrics_path.clone(),
+    )];
+
+    let runner = run_locally(commands, budget(1, 1)).await.unwrap();
+    runner.handle.await.unwrap();
+
+    assert!(!tmp.path().join("started").exists());
+    assert_eq!(
+        toml::from_str::<Metrics>(&fs::read_to_string(&metrics_path).unwrap()).unwrap(),
+        Metrics::Cancelled
+    );
+}
+
+/// Test that a stopped runner starts no more runs, but still records the
+/// failures of the runs that already started.
+#[tokio::test]
+async fn test_stopped_runner() {
+    let tmp = TempDir::new("runner").unwrap();
+
+    let mut slow = Command::new("sh");
+    slow.current_dir(tmp.path())
+        .arg("-c")
+        .arg("touch started; sleep 0.3; echo slow >&2; exit 3");
+
+    let mut next = Command::new("touch");
+    next.arg(tmp.path().join("next"));
+
+    let commands = vec![
+        wrapped(slow, ResourceLimits::default(), tmp.path().join("slow")),
+        wrapped(next, ResourceLimits::default(), tmp.path().join("next")),
+    ];
+
+    let runner = run_locally(commands, budget(1, 1)).await.unwrap();
+
+    while !tmp.path().join("started").exists() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
     }
 
-    let results = run_locally(commands, false, false).await;
+    runner.stop();
+    runner.handle.await.unwrap();
 
-    assert!(results.is_err(), "Executing children processes failed");
+    let metrics = fs::read_to_string(tmp.path().join("slow")).unwrap();
+    assert!(matches!(
+        toml::from_str::<Metrics>(&metrics).unwrap(),
+        Metrics::WrapperFailed { reason } if reason.contains("slow")
+    ));
+    assert!(!tmp.path().join("next").exists());
 }
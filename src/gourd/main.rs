@@ -73,6 +73,9 @@ pub mod chunks;
 /// Analysing runs - collecting metrics, exporting, plotting.
 pub mod analyse;
 
+/// Tuning the parameters of an experiment based on the results of its runs.
+pub mod tune;
+
 /// Convenience functions for unit tests.
 #[cfg(test)]
 pub mod test_utils;
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use gourd_lib::experiment::Experiment;
+use gourd_lib::experiment::FieldRef;
+use gourd_lib::file_system::FileOperations;
+use log::debug;
+
+use crate::post::verdict::compare;
+use crate::status::ExperimentStatus;
+
+/// How the programs that ran on an input agree on its output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputAgreement {
+    /// The name of the input.
+    pub input: FieldRef,
+
+    /// The succeeded runs on the input, grouped by their output.
+    pub classes: Vec<Vec<usize>>,
+
+    /// The class with the consensus output, if there is one.
+    pub consensus: Option<usize>,
+}
+
+impl InputAgreement {
+    /// Whether all the runs on the input gave the same output.
+    pub fn agrees(&self) -> bool {
+        self.classes.len() <= 1
+    }
+
+    /// Whether the output of a run differs from the consensus, or `None` if
+    /// there is no consensus or the run was not compared.
+    pub fn dissents(&self, run: usize) -> Option<bool> {
+        let consensus = &self.classes[self.consensus?];
+
+        self.classes
+            .iter()
+            .any(|class| class.contains(&run))
+            .then(|| !consensus.contains(&run))
+    }
+}
+
+/// Groups the succeeded runs of every input by their output, according to the
+/// comparison of the experiment.
+///
+/// Returns nothing if the experiment does not check for agreement. Runs that
+/// were rerun are replaced by their reruns, and postprocessing runs are not
+/// compared.
+pub fn check_agreement(
+    experiment: &Experiment,
+    statuses: &ExperimentStatus,
+    fs: &impl FileOperations,
+) -> Result<Vec<InputAgreement>> {
+    let Some(agreement) = &experiment.agreement else {
+        return Ok(Vec::new());
+    };
+
+    let mut by_input: BTreeMap<&FieldRef, Vec<usize>> = BTreeMap::new();
+
+    for (id, run) in experiment.runs.iter().enumerate() {
+        if let Some(input) = &run.generated_from_input {
+            if run.rerun.is_none() && statuses[&id].fs_status.completion.has_succeeded() {
+                by_input.entry(input).or_default().push(id);
+            }
+        }
+    }
+
+    let mut agreements = Vec::new();
+
+    for (input, runs) in by_input {
+        debug!("Comparing the outputs of {} runs on {input}", runs.len());
+
+        let mut classes: Vec<Vec<usize>> = Vec::new();
+
+        'runs: for run in runs {
+            for class in &mut classes {
+                if compare(
+                    &experiment.compare,
+                    &experiment.runs[run].output_path,
+                    &experiment.runs[class[0]].output_path,
+                    &experiment.runs[run].work_dir,
+                    fs,
+                )? {
+                    class.push(run);
+                    continue 'runs;
+                }
+            }
+
+            classes.push(vec![run]);
+        }
+
+        let consensus = match &agreement.reference {
+            Some(reference) => classes.iter().position(|class| {
+                class.iter().any(|run| {
+                    experiment.programs[experiment.runs[*run].program].original_name() == reference
+                })
+            }),
+            None => majority(&classes),
+        };
+
+        agreements.push(InputAgreement {
+            input: input.clone(),
+            classes,
+            consensus,
+        });
+    }
+
+    Ok(agreements)
+}
+
+/// The class with more runs than any other, if there is one.
+fn majority(classes: &[Vec<usize>]) -> Option<usize> {
+    let largest = classes.iter().map(Vec::len).max()?;
+    let mut candidates = classes
+        .iter()
+        .enumerate()
+        .filter(|(_, class)| class.len() == largest);
+
+    match (candidates.next(), candidates.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
@@ -15,24 +15,32 @@ pub fn assign_label(
 ) -> Result<Option<String>> {
     debug!("Assigning label to {:?}", source_file);
 
+    let text = fs.read_utf8(source_file)?;
+
+    Ok(match_label(
+        experiment,
+        &text,
+        &format!("The source file {source_file:?}"),
+    ))
+}
+
+/// The label with the highest priority whose regex matches the `text`, which
+/// comes from `source`.
+pub fn match_label(experiment: &Experiment, text: &str, source: &str) -> Option<String> {
     let mut result_label: Option<String> = None;
 
-    let text = fs.read_utf8(source_file)?;
     let label_map = &experiment.labels.map;
     let mut keys = label_map.keys().collect::<Vec<&String>>();
     keys.sort_unstable_by(|a, b| label_map[*b].priority.cmp(&label_map[*a].priority));
 
     for l in keys {
         let label = &label_map[l];
-        if label.regex.is_match(&text) {
+        if label.regex.is_match(text) {
             if let Some(ref r) = result_label {
                 trace!("{text} matches multiple labels: {r} and {l}");
 
                 if experiment.labels.warn_on_label_overlap {
-                    warn!(
-                        "The source file {:?} matches multiple labels: {} and {}",
-                        source_file, r, l
-                    );
+                    warn!("{} matches multiple labels: {} and {}", source, r, l);
                 }
             } else {
                 trace!("{text} matches {l}");
@@ -41,5 +49,5 @@ pub fn assign_label(
         }
     }
 
-    Ok(result_label)
+    result_label
 }
@@ -1,5 +1,11 @@
+/// Functionality for checking that programs agree on their output.
+pub mod agreement;
+
 /// Functionality for running afterscripts.
 pub mod afterscript;
 
 /// Functionality for assigning labels.
 pub mod labels;
+
+/// Functionality for checking the output of runs.
+pub mod verdict;
@@ -1,2 +1,5 @@
 /// Tests for the functionality of afterscripts and labels.
 pub mod afterscript;
+
+/// Tests for checking the output of runs.
+pub mod verdict;
@@ -0,0 +1,103 @@
+use std::fs;
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+
+use gourd_lib::config::compare::Comparison;
+use gourd_lib::file_system::FileSystemInteractor;
+use tempdir::TempDir;
+
+use crate::post::verdict::cached_verdict;
+use crate::post::verdict::compare;
+use crate::post::verdict::Verdict;
+
+const FS: FileSystemInteractor = FileSystemInteractor { dry_run: false };
+
+#[test]
+fn test_compare_modes() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+
+    let output = tmp_dir.path().join("output");
+    let expected = tmp_dir.path().join("expected");
+
+    fs::write(&output, "distance 3.1415\n  path a b\n").unwrap();
+    fs::write(&expected, "distance 3.14 path a b").unwrap();
+
+    let verdict = |comparison: Comparison| {
+        compare(&comparison, &output, &expected, tmp_dir.path(), &FS).unwrap()
+    };
+
+    assert!(!verdict(Comparison::Exact));
+    assert!(!verdict(Comparison::Whitespace));
+    assert!(verdict(Comparison::Numeric { tolerance: 0.01 }));
+    assert!(!verdict(Comparison::Numeric { tolerance: 0.001 }));
+
+    fs::write(&expected, "distance 3.1415 path a b").unwrap();
+
+    assert!(!verdict(Comparison::Exact));
+    assert!(verdict(Comparison::Whitespace));
+
+    fs::write(&expected, "distance 3.1415\n  path a b\n").unwrap();
+
+    assert!(verdict(Comparison::Exact));
+
+    // The words that are not numbers are compared exactly.
+    fs::write(&expected, "distance 3.1415 path a c").unwrap();
+
+    assert!(!verdict(Comparison::Numeric { tolerance: 1.0 }));
+}
+
+#[test]
+fn test_compare_checker() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+
+    let output = tmp_dir.path().join("output");
+    let expected = tmp_dir.path().join("expected");
+    let checker = tmp_dir.path().join("checker.sh");
+
+    fs::write(&output, "42").unwrap();
+    fs::write(&expected, "42").unwrap();
+    fs::write(
+        &checker,
+        "#!/bin/sh\necho checking >&2\ncmp -s \"$1\" \"$2\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&checker, Permissions::from_mode(0o755)).unwrap();
+
+    let comparison = Comparison::Checker {
+        command: vec![checker.to_string_lossy().to_string()],
+    };
+
+    assert!(compare(&comparison, &output, &expected, tmp_dir.path(), &FS).unwrap());
+
+    fs::write(&output, "41").unwrap();
+
+    assert!(!compare(&comparison, &output, &expected, tmp_dir.path(), &FS).unwrap());
+
+    let missing = Comparison::Checker {
+        command: vec![tmp_dir.path().join("missing").to_string_lossy().to_string()],
+    };
+
+    assert!(compare(&missing, &output, &expected, tmp_dir.path(), &FS).is_err());
+}
+
+#[test]
+fn test_cached_verdict() {
+    let tmp_dir = TempDir::new("testing").unwrap();
+
+    let cache = tmp_dir.path().join("verdict");
+
+    assert_eq!(cached_verdict(&cache, &FS), None);
+
+    fs::write(&cache, "correct\n").unwrap();
+    assert_eq!(cached_verdict(&cache, &FS), Some(Verdict::Correct));
+
+    fs::write(&cache, "incorrect").unwrap();
+    assert_eq!(cached_verdict(&cache, &FS), Some(Verdict::Incorrect));
+
+    // A truncated cache is compared again.
+    fs::write(&cache, "").unwrap();
+    assert_eq!(cached_verdict(&cache, &FS), None);
+
+    fs::write(&cache, "corr").unwrap();
+    assert_eq!(cached_verdict(&cache, &FS), None);
+}
@@ -0,0 +1,162 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+
+use anyhow::Context;
+use anyhow::Result;
+use gourd_lib::config::compare::Comparison;
+use gourd_lib::constants::ERROR_STYLE;
+use gourd_lib::constants::PRIMARY_STYLE;
+use gourd_lib::constants::VERDICT_FILE;
+use gourd_lib::ctx;
+use gourd_lib::experiment::Experiment;
+use gourd_lib::file_system::FileOperations;
+use log::debug;
+use log::trace;
+
+/// Whether a run gave the expected output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The output is the expected output.
+    Correct,
+
+    /// The output is not the expected output.
+    Incorrect,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The alternate form is styled for the status.
+        match (self, f.alternate()) {
+            (Verdict::Correct, false) => write!(f, "correct"),
+            (Verdict::Incorrect, false) => write!(f, "incorrect"),
+            (Verdict::Correct, true) => write!(f, "{PRIMARY_STYLE}correct{PRIMARY_STYLE:#}"),
+            (Verdict::Incorrect, true) => write!(f, "{ERROR_STYLE}incorrect{ERROR_STYLE:#}"),
+        }
+    }
+}
+
+/// Compares the output of a completed run with the expected output of its
+/// input.
+///
+/// Returns `None` if the input has no expected output. The verdict is cached in
+/// the working directory of the run, so that a checker runs only once.
+pub fn check_output(
+    run_id: usize,
+    experiment: &Experiment,
+    fs: &impl FileOperations,
+) -> Result<Option<Verdict>> {
+    let run = &experiment.runs[run_id];
+
+    let Some(expected) = run
+        .generated_from_input
+        .as_ref()
+        .and_then(|input| experiment.inputs.get(input))
+        .and_then(|input| input.expected.as_ref())
+    else {
+        return Ok(None);
+    };
+
+    let cache = run.work_dir.join(VERDICT_FILE);
+
+    if let Some(verdict) = cached_verdict(&cache, fs) {
+        trace!("Read the verdict of run {run_id} from {cache:?}");
+
+        return Ok(Some(verdict));
+    }
+
+    debug!("Comparing the output of run {run_id} with {expected:?}");
+
+    let correct = compare(
+        &experiment.compare,
+        &run.output_path,
+        expected,
+        &run.work_dir,
+        fs,
+    )?;
+
+    let verdict = if correct {
+        Verdict::Correct
+    } else {
+        Verdict::Incorrect
+    };
+
+    fs.write_utf8_truncate(&cache, &verdict.to_string())?;
+
+    Ok(Some(verdict))
+}
+
+/// The verdict cached in a file, if the file holds one.
+///
+/// A missing, truncated or otherwise unreadable cache holds no verdict, so that
+/// the output is compared again.
+pub fn cached_verdict(cache: &Path, fs: &impl FileOperations) -> Option<Verdict> {
+    let cached = fs.read_utf8(cache).ok()?;
+
+    match cached.trim() {
+        "correct" => Some(Verdict::Correct),
+        "incorrect" => Some(Verdict::Incorrect),
+        other => {
+            debug!("Ignoring the cached verdict {other:?} in {cache:?}");
+            None
+        }
+    }
+}
+
+/// Whether the `output` file is the `expected` file according to the
+/// `comparison`.
+pub fn compare(
+    comparison: &Comparison,
+    output: &Path,
+    expected: &Path,
+    work_dir: &Path,
+    fs: &impl FileOperations,
+) -> Result<bool> {
+    if let Comparison::Checker { command } = comparison {
+        // The checker runs while the status is displayed, so its messages are
+        // only logged.
+        let checked = Command::new(&command[0])
+            .args(&command[1..])
+            .arg(output)
+            .arg(expected)
+            .current_dir(work_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .with_context(ctx!(
+                "Could not run the checker {:?}", command;
+                "Check that the checker exists and is executable",
+            ))?;
+
+        let messages = String::from_utf8_lossy(&checked.stderr);
+
+        if !messages.trim().is_empty() {
+            debug!("The checker {command:?} wrote: {}", messages.trim());
+        }
+
+        return Ok(checked.status.success());
+    }
+
+    let output = fs.read_utf8(output)?;
+    let expected = fs.read_utf8(expected)?;
+
+    Ok(match comparison {
+        Comparison::Exact => output == expected,
+        Comparison::Whitespace => output.split_whitespace().eq(expected.split_whitespace()),
+        Comparison::Numeric { tolerance } => {
+            let output: Vec<&str> = output.split_whitespace().collect();
+            let expected: Vec<&str> = expected.split_whitespace().collect();
+
+            output.len() == expected.len()
+                && output.iter().zip(&expected).all(|(word, expected)| {
+                    match (word.parse::<f64>(), expected.parse::<f64>()) {
+                        (Ok(number), Ok(expected)) => (number - expected).abs() <= *tolerance,
+                        _ => word == expected,
+                    }
+                })
+        }
+        Comparison::Checker { .. } => unreachable!("The checker was handled above"),
+    })
+}
@@ -13,6 +13,8 @@ use std::fmt::Formatter;
 
 use gourd_lib::config::slurm::ResourceLimits;
 use gourd_lib::experiment::Experiment;
+use gourd_lib::measurement::LimitExceeded;
+use gourd_lib::measurement::Termination;
 
 /// The status of a single run
 #[derive(Ord, PartialOrd, Eq, PartialEq, Clone)]
@@ -32,6 +34,21 @@ pub(super) enum RerunStatus {
 
     /// Failed with an exit code
     FailedExitCode(i32),
+
+    /// Was stopped by the wrapper for exceeding a resource limit
+    FailedLimitExceeded(LimitExceeded),
+
+    /// Was killed by a signal
+    FailedSignal(Termination),
+
+    /// The setup command of the program failed, so it did not run
+    FailedSetup(Termination),
+
+    /// The wrapper failed, for the reason given
+    FailedWrapper(String),
+
+    /// The run was cancelled before it finished
+    Cancelled,
 }
 
 impl Display for RerunStatus {
@@ -42,6 +59,11 @@ impl Display for RerunStatus {
             RerunStatus::FinishedSuccessLabel(l) => write!(f, "Finished with label {}", l),
             RerunStatus::FailedErrorLabel(l) => write!(f, "Failed with label {}", l),
             RerunStatus::FailedExitCode(c) => write!(f, "Failed with exit code {}", c),
+            RerunStatus::FailedLimitExceeded(l) => write!(f, "Failed, {}", l),
+            RerunStatus::FailedSignal(t) => write!(f, "Failed, {}", t),
+            RerunStatus::FailedSetup(t) => write!(f, "Setup failed, {}", t),
+            RerunStatus::FailedWrapper(r) => write!(f, "Wrapper failed: {}", r),
+            RerunStatus::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -52,7 +74,7 @@ fn update_program_resource_limits(
     experiment: &mut Experiment,
     new_rss: ResourceLimits,
 ) -> Result<()> {
-    experiment.programs[experiment.runs[run_id].program].limits = new_rss;
+    experiment.programs[experiment.runs[run_id].program].limits = Some(new_rss);
 
     Ok(())
 }
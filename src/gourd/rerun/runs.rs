@@ -5,12 +5,16 @@ use gourd_lib::bailc;
 use gourd_lib::ctx;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
+use gourd_lib::measurement::Measurement;
+use gourd_lib::measurement::Termination;
 use inquire::Select;
 
 use crate::init::interactive::ask;
 use crate::rerun::slurm::check_multiple_runs_failed;
 use crate::status::DynamicStatus;
 use crate::status::ExperimentStatus;
+use crate::status::FsState;
+use crate::status::Status;
 
 /// Get the list of runs to rerun from the rerun options.
 pub fn get_runs_from_rerun_options(
@@ -72,17 +76,43 @@ pub(super) fn get_what_runs_to_rerun_from_experiment(
         return Ok(failed_runs);
     }
 
-    let choices: Vec<String> = vec![
+    let signalled_runs: Vec<usize> = failed_runs
+        .iter()
+        .copied()
+        .filter(|id| killed_by_signal(&statuses[id]))
+        .collect();
+
+    let mut choices: Vec<String> = vec![
         format!("Rerun only failed ({} runs)", failed_runs.len()),
         format!("Rerun all finished ({} runs)", all_not_rerun.len()),
     ];
+
+    if !signalled_runs.is_empty() {
+        choices.push(format!(
+            "Rerun only killed by a signal ({} runs)",
+            signalled_runs.len()
+        ));
+    }
+
     match ask(Select::new("What would you like to do?", choices.clone()).prompt())?.as_str() {
         x if x == choices[1] => Ok::<Vec<usize>, anyhow::Error>(all_not_rerun),
         x if x == choices[0] => Ok::<Vec<usize>, anyhow::Error>(failed_runs),
+        x if choices.get(2).is_some_and(|c| x == c) => Ok(signalled_runs),
         x => unreachable!("got: {:?}", x),
     }
 }
 
+/// Check if the program of a run was killed by a signal.
+fn killed_by_signal(status: &Status) -> bool {
+    matches!(
+        &status.fs_status.completion,
+        FsState::Completed(measurement @ Measurement {
+            termination: Termination::Signalled { .. },
+            ..
+        }) if !measurement.setup_failed()
+    )
+}
+
 /// Get the list of runs that have failed and are re_runnable.
 pub(super) fn re_runnable(
     ids: impl Iterator<Item = usize>,
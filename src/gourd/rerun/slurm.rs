@@ -10,6 +10,8 @@ use gourd_lib::constants::RERUN_LIST_PROMPT_CUTOFF;
 use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
+use gourd_lib::measurement::LimitExceeded;
+use gourd_lib::measurement::Measurement;
 use inquire::Select;
 use log::debug;
 use log::trace;
@@ -23,6 +25,7 @@ use crate::rerun::update_program_resource_limits;
 use crate::rerun::RerunStatus;
 use crate::status::DynamicStatus;
 use crate::status::ExperimentStatus;
+use crate::status::FsState;
 use crate::status::SlurmState;
 
 /// Ask the user if they want to change any resource limits for the current
@@ -33,26 +36,44 @@ pub fn query_changing_resource_limits(
     selected_runs: &[usize],
     file_system: &mut impl FileOperations,
 ) -> Result<()> {
-    if experiment.env == Environment::Slurm && !script_mode {
+    if !script_mode {
         let statuses = experiment.status(file_system)?;
         let (out_of_memory, out_of_time) =
             selected_runs
                 .iter()
                 .map(|r| statuses[r].clone())
-                .fold((0, 0), |(oom, oot), s| match s.slurm_status {
-                    Some(s) => match s.completion {
-                        SlurmState::OutOfMemory => (oom + 1, oot),
-                        SlurmState::Timeout => (oom, oot + 1),
+                .fold((0, 0), |(oom, oot), s| {
+                    match (s.slurm_status.map(|x| x.completion), s.fs_status.completion) {
+                        (Some(SlurmState::OutOfMemory), _)
+                        | (
+                            _,
+                            FsState::Completed(Measurement {
+                                limit_exceeded: Some(LimitExceeded::Memory),
+                                ..
+                            }),
+                        ) => (oom + 1, oot),
+                        (Some(SlurmState::Timeout), _)
+                        | (
+                            _,
+                            FsState::Completed(Measurement {
+                                limit_exceeded: Some(LimitExceeded::Time),
+                                ..
+                            }),
+                        ) => (oom, oot + 1),
                         _ => (oom, oot),
-                    },
-                    None => (oom, oot),
+                    }
                 });
 
-        if query_yes_no(&format!(
-            "{} runs ran out of memory and {} runs ran out of time. \
+        // Locally the limits only matter when runs were stopped for exceeding them.
+        let relevant = experiment.env == Environment::Slurm || out_of_memory + out_of_time > 0;
+
+        if relevant
+            && query_yes_no(&format!(
+                "{} runs ran out of memory and {} runs ran out of time. \
                      Do you want to change the resource limits for their programs?",
-            out_of_memory, out_of_time
-        ))? {
+                out_of_memory, out_of_time
+            ))?
+        {
             query_changing_limits_for_programs(selected_runs, experiment)?;
         }
     }
@@ -100,6 +121,43 @@ pub(super) fn check_single_run_failed(
             }
         }
 
+        RerunStatus::FailedLimitExceeded(l) => {
+            debug!(
+                "Scheduling rerun for run #{} that was stopped, {}",
+                specific_run, l
+            );
+            Ok(*specific_run)
+        }
+
+        RerunStatus::FailedSignal(t) => {
+            debug!("Scheduling rerun for run #{} that was {}", specific_run, t);
+            Ok(*specific_run)
+        }
+
+        RerunStatus::FailedSetup(t) => {
+            debug!(
+                "Scheduling rerun for run #{} whose setup failed, {}",
+                specific_run, t
+            );
+            Ok(*specific_run)
+        }
+
+        RerunStatus::FailedWrapper(r) => {
+            debug!(
+                "Scheduling rerun for run #{} whose wrapper failed: {}",
+                specific_run, r
+            );
+            Ok(*specific_run)
+        }
+
+        RerunStatus::Cancelled => {
+            debug!(
+                "Scheduling rerun for run #{} that was cancelled",
+                specific_run
+            );
+            Ok(*specific_run)
+        }
+
         RerunStatus::FailedExitCode(c) => {
             debug!(
                 "Scheduling rerun for run #{} that failed with exit code {}",
@@ -194,7 +252,7 @@ pub fn query_changing_limits_for_programs(
                             continue;
                         }
                         let new_rss = query_update_resource_limits(
-                            &experiment.runs[*run_id].limits,
+                            &experiment.runs[*run_id].limits.unwrap_or_default(),
                             false,
                             None,
                             None,
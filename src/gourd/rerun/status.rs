@@ -1,5 +1,6 @@
 use anyhow::Result;
 use gourd_lib::experiment::Experiment;
+use gourd_lib::measurement::Termination;
 
 use crate::rerun::RerunStatus;
 use crate::status::ExperimentStatus;
@@ -14,12 +15,20 @@ pub(super) fn status_of_single_run(
     let runs_status = &statuses[run_id];
 
     // 2. check if run has completed
-    match runs_status.fs_status.completion {
+    match &runs_status.fs_status.completion {
         FsState::Pending | FsState::Running => Ok(RerunStatus::NotFinished),
 
+        FsState::WrapperFailed(reason) => Ok(RerunStatus::FailedWrapper(reason.clone())),
+
+        FsState::Cancelled => Ok(RerunStatus::Cancelled),
+
         FsState::Completed(m) => {
             // 3. check if the run failed
-            if m.exit_code == 0 {
+            if m.setup_failed() {
+                Ok(RerunStatus::FailedSetup(m.termination))
+            } else if let Some(limit) = m.limit_exceeded {
+                Ok(RerunStatus::FailedLimitExceeded(limit))
+            } else if m.termination.is_success() {
                 // run the afterscript to get a label
                 if let Some(Some(label)) = &runs_status.fs_status.afterscript_completion {
                     let lb = experiment.get_label(label)?;
@@ -31,8 +40,10 @@ pub(super) fn status_of_single_run(
                 } else {
                     Ok(RerunStatus::FinishedExitZero)
                 }
+            } else if let Termination::Exited { code } = m.termination {
+                Ok(RerunStatus::FailedExitCode(code))
             } else {
-                Ok(RerunStatus::FailedExitCode(m.exit_code))
+                Ok(RerunStatus::FailedSignal(m.termination))
             }
         }
     }
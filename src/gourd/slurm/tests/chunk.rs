@@ -18,11 +18,25 @@ fn get_unscheduled_runs_test() {
         afterscript: None,
         postprocess_job: None,
         resource_limits: None,
+        repetitions: None,
+        parameters: None,
+        zip: vec![],
+        include: vec![],
+        exclude: vec![],
+        stdin: true,
+        setup: vec![],
+        teardown: vec![],
+        env: BTreeMap::new(),
     };
 
     let input = UserInput {
         input: None,
         arguments: vec![],
+        repetitions: None,
+        files: BTreeMap::new(),
+        generator: None,
+        expected: None,
+        env: BTreeMap::new(),
     };
 
     let (mut experiment, _conf) = create_sample_experiment(
@@ -67,11 +81,25 @@ fn create_chunks_basic_test() {
         afterscript: None,
         postprocess_job: None,
         resource_limits: None,
+        repetitions: None,
+        parameters: None,
+        zip: vec![],
+        include: vec![],
+        exclude: vec![],
+        stdin: true,
+        setup: vec![],
+        teardown: vec![],
+        env: BTreeMap::new(),
     };
 
     let input = UserInput {
         input: None,
         arguments: vec![],
+        repetitions: None,
+        files: BTreeMap::new(),
+        generator: None,
+        expected: None,
+        env: BTreeMap::new(),
     };
 
     let (mut experiment, _conf) = create_sample_experiment(
@@ -136,6 +164,15 @@ fn create_chunks_greedy_test() {
         afterscript: None,
         postprocess_job: None,
         resource_limits: None,
+        repetitions: None,
+        parameters: None,
+        zip: vec![],
+        include: vec![],
+        exclude: vec![],
+        stdin: true,
+        setup: vec![],
+        teardown: vec![],
+        env: BTreeMap::new(),
     };
 
     let prog_b = UserProgram {
@@ -144,16 +181,35 @@ fn create_chunks_greedy_test() {
         afterscript: None,
         postprocess_job: None,
         resource_limits: None,
+        repetitions: None,
+        parameters: None,
+        zip: vec![],
+        include: vec![],
+        exclude: vec![],
+        stdin: true,
+        setup: vec![],
+        teardown: vec![],
+        env: BTreeMap::new(),
     };
 
     let input_a = UserInput {
         input: Some(FetchedPath(PathBuf::new().join("a"))),
         arguments: vec![],
+        repetitions: None,
+        files: BTreeMap::new(),
+        generator: None,
+        expected: None,
+        env: BTreeMap::new(),
     };
 
     let input_b = UserInput {
         input: Some(FetchedPath(PathBuf::new().join("b"))),
         arguments: vec![],
+        repetitions: None,
+        files: BTreeMap::new(),
+        generator: None,
+        expected: None,
+        env: BTreeMap::new(),
     };
 
     let (mut experiment, _conf) = create_sample_experiment(
@@ -8,9 +8,12 @@ use log::trace;
 use log::warn;
 
 use super::FileSystemBasedStatus;
+use super::LabelSource;
 use super::StatusProvider;
 use crate::post::afterscript::run_afterscript;
 use crate::post::labels::assign_label;
+use crate::post::labels::match_label;
+use crate::post::verdict::check_output;
 use crate::status::FsState;
 
 /// Provide job status information based on the files system information.
@@ -46,6 +49,8 @@ where
                 Some(inner) => match inner {
                     Metrics::Done(metrics) => FsState::Completed(metrics),
                     Metrics::NotCompleted => FsState::Running,
+                    Metrics::WrapperFailed { reason } => FsState::WrapperFailed(reason),
+                    Metrics::Cancelled => FsState::Cancelled,
                 },
                 None => FsState::Pending,
             };
@@ -67,9 +72,54 @@ where
                 };
             }
 
+            let mut label_source =
+                matches!(afterscript_completion, Some(Some(_))).then_some(LabelSource::Afterscript);
+
+            let mut verdict = None;
+
+            if completion.has_succeeded() {
+                verdict = match check_output(run_id, experiment, fs) {
+                    Ok(verdict) => verdict,
+                    Err(e) => {
+                        warn!("Could not check the output of run #{}: {e}", run_id);
+                        None
+                    }
+                };
+            }
+
+            // Without a label from the afterscript, the verdict can assign one.
+            if let (None | Some(None), Some(verdict)) = (&afterscript_completion, verdict) {
+                if let Some(label) = match_label(
+                    experiment,
+                    &verdict.to_string(),
+                    &format!("The verdict of run #{run_id}"),
+                ) {
+                    afterscript_completion = Some(Some(label));
+                    label_source = Some(LabelSource::Verdict);
+                }
+            }
+
+            // A run that did not exit with code 0 can be labelled by how it
+            // terminated instead.
+            if let (None, FsState::Completed(measurement)) = (&afterscript_completion, &completion)
+            {
+                if !measurement.termination.is_success() {
+                    if let Some(label) = match_label(
+                        experiment,
+                        &measurement.termination.to_string(),
+                        &format!("The termination of run #{run_id}"),
+                    ) {
+                        afterscript_completion = Some(Some(label));
+                        label_source = Some(LabelSource::Termination);
+                    }
+                }
+            }
+
             let status = FileSystemBasedStatus {
                 completion,
                 afterscript_completion,
+                label_source,
+                verdict,
             };
 
             statuses.insert(run_id, status);
@@ -9,6 +9,7 @@ use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
 use gourd_lib::measurement::Measurement;
+use gourd_lib::measurement::Termination;
 use indicatif::MultiProgress;
 use log::debug;
 
@@ -16,6 +17,7 @@ use self::fs_based::FileBasedProvider;
 use self::printing::display_statuses;
 use self::slurm_based::SlurmBasedProvider;
 use crate::cli::printing::generate_progress_bar;
+use crate::post::verdict::Verdict;
 use crate::slurm::interactor::SlurmCli;
 use crate::status::slurm_files::SlurmFileOutput;
 use crate::status::slurm_files::SlurmFileStatus;
@@ -87,7 +89,9 @@ impl SlurmState {
 }
 
 /// This possible status of a job, reported by the file system.
-#[derive(Debug, Clone, Copy, PartialEq)]
+// Most jobs end up completed, so boxing the measurement would not save memory.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FsState {
     /// The job has not yet started.
     Pending,
@@ -97,17 +101,34 @@ pub enum FsState {
 
     /// The job completed.
     Completed(Measurement),
+
+    /// The wrapper failed before the program could complete, for the reason
+    /// given.
+    WrapperFailed(String),
+
+    /// The job was cancelled before it finished.
+    Cancelled,
 }
 
 impl FsState {
     /// Check if this state means that the run is completed.
     pub fn is_completed(&self) -> bool {
-        matches!(self, FsState::Completed(_))
+        matches!(
+            self,
+            FsState::Completed(_) | FsState::WrapperFailed(_) | FsState::Cancelled
+        )
     }
 
     /// Check if this state means that the run has succeded.
     pub fn has_succeeded(&self) -> bool {
-        matches!(self, FsState::Completed(Measurement { exit_code: 0, .. }))
+        matches!(
+            self,
+            FsState::Completed(Measurement {
+                termination: Termination::Exited { code: 0 },
+                limit_exceeded: None,
+                ..
+            })
+        )
     }
 }
 
@@ -118,8 +139,27 @@ pub struct FileSystemBasedStatus {
     pub completion: FsState,
 
     /// If the afterscript completed successfully, this will contain the label,
-    /// if one was assigned.
+    /// if one was assigned. The verdict can also assign a label.
     pub afterscript_completion: Option<Option<String>>,
+
+    /// What assigned the label of the run, if it has one.
+    pub label_source: Option<LabelSource>,
+
+    /// Whether the run gave the expected output, if its input has one.
+    pub verdict: Option<Verdict>,
+}
+
+/// What assigned the label of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelSource {
+    /// The afterscript of the program.
+    Afterscript,
+
+    /// Whether the run gave the expected output.
+    Verdict,
+
+    /// How a failed run terminated.
+    Termination,
 }
 
 /// Structure of slurm based status
@@ -166,7 +206,12 @@ impl Status {
     /// Check if we know this job to have failed.
     pub fn has_failed(&self, experiment: &Experiment) -> bool {
         let a = match self.fs_status.completion {
-            FsState::Completed(Measurement { exit_code, .. }) => exit_code != 0,
+            FsState::Completed(Measurement {
+                termination,
+                limit_exceeded,
+                ..
+            }) => !termination.is_success() || limit_exceeded.is_some(),
+            FsState::WrapperFailed(_) | FsState::Cancelled => true,
             _ => false,
         };
         let b = match self.slurm_status {
@@ -2,6 +2,7 @@ use std::cmp::max;
 use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::io::Write;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use anyhow::Context;
@@ -19,12 +20,20 @@ use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::experiment::FieldRef;
 use gourd_lib::experiment::Run;
+use gourd_lib::measurement::read_samples;
+use gourd_lib::measurement::Measurement;
+use gourd_lib::measurement::Sample;
+use gourd_lib::measurement::Termination;
+use log::debug;
 use log::info;
 
 use super::ExperimentStatus;
 use super::FsState;
+use super::LabelSource;
 use super::SlurmState;
 use super::Status;
+use crate::post::agreement::InputAgreement;
+use crate::post::verdict::Verdict;
 
 #[cfg(not(tarpaulin_include))] // There are no meaningful tests for an enum's Display implementation
 
@@ -58,8 +67,24 @@ impl Display for FsState {
         match self {
             FsState::Pending => write!(f, "pending?"),
             FsState::Running => write!(f, "running!"),
-            FsState::Completed(metrics) => {
-                if metrics.exit_code == 0 {
+            FsState::WrapperFailed(reason) if f.alternate() => {
+                write!(f, "{ERROR_STYLE}wrapper failed{ERROR_STYLE:#}: {reason}")
+            }
+            FsState::WrapperFailed(_) => write!(f, "{ERROR_STYLE}wrapper failed{ERROR_STYLE:#}"),
+            FsState::Cancelled => write!(f, "{WARNING_STYLE}cancelled{WARNING_STYLE:#}"),
+            FsState::Completed(metrics) if metrics.setup_failed() => {
+                write!(
+                    f,
+                    "{ERROR_STYLE}setup failed, {}{ERROR_STYLE:#}",
+                    metrics.termination
+                )
+            }
+            FsState::Completed(Measurement {
+                limit_exceeded: Some(limit),
+                ..
+            }) => write!(f, "{WARNING_STYLE}{limit}{WARNING_STYLE:#}"),
+            FsState::Completed(metrics) => match metrics.termination {
+                Termination::Exited { code: 0 } => {
                     if f.alternate() {
                         write!(
                             f,
@@ -77,14 +102,24 @@ impl Display for FsState {
                             humantime::Duration::from(metrics.wall_micros)
                         )
                     }
-                } else {
-                    write!(
-                        f,
-                        "{}failed, code: {}{:#}",
-                        ERROR_STYLE, metrics.exit_code, ERROR_STYLE
-                    )
                 }
-            }
+                Termination::Exited { code } => {
+                    write!(f, "{}failed, code: {}{:#}", ERROR_STYLE, code, ERROR_STYLE)
+                }
+                termination @ Termination::Signalled { .. } => {
+                    write!(f, "{ERROR_STYLE}failed, {termination}{ERROR_STYLE:#}")
+                }
+            },
+        }
+    }
+}
+
+impl Display for LabelSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelSource::Afterscript => write!(f, "afterscript"),
+            LabelSource::Verdict => write!(f, "verdict"),
+            LabelSource::Termination => write!(f, "termination"),
         }
     }
 }
@@ -115,14 +150,66 @@ impl Display for Status {
                 }
             }
 
-            if let FsState::Completed(measurement) = self.fs_status.completion {
+            if let FsState::Completed(measurement) = &self.fs_status.completion {
+                for (hook, termination) in [
+                    ("setup", measurement.setup),
+                    ("teardown", measurement.teardown),
+                ] {
+                    match termination {
+                        Some(termination) if termination.is_success() => writeln!(
+                            f,
+                            "{NAME_STYLE}{hook}{NAME_STYLE:#}: \
+                            {PRIMARY_STYLE}{termination}{PRIMARY_STYLE:#}"
+                        )?,
+                        Some(termination) => writeln!(
+                            f,
+                            "{NAME_STYLE}{hook}{NAME_STYLE:#}: \
+                            {ERROR_STYLE}{termination}{ERROR_STYLE:#}"
+                        )?,
+                        None => {}
+                    }
+                }
+
                 if let Some(rusage) = measurement.rusage {
                     write!(f, "{NAME_STYLE}metrics{NAME_STYLE:#}:\n{rusage}")?;
                 }
+
+                if let Some(counters) = &measurement.counters {
+                    writeln!(f, "{NAME_STYLE}performance counters{NAME_STYLE:#}:")?;
+
+                    for (name, value) in counters.named() {
+                        match value {
+                            Some(value) => {
+                                writeln!(f, "  {NAME_STYLE}{name}{NAME_STYLE:#}: {value}")?
+                            }
+                            None => writeln!(
+                                f,
+                                "  {NAME_STYLE}{name}{NAME_STYLE:#}: \
+                                {TERTIARY_STYLE}unavailable{TERTIARY_STYLE:#}"
+                            )?,
+                        }
+                    }
+                }
+
+                if !measurement.custom_metrics.is_empty() {
+                    writeln!(f, "{NAME_STYLE}custom metrics{NAME_STYLE:#}:")?;
+
+                    for (name, value) in &measurement.custom_metrics {
+                        writeln!(f, "  {NAME_STYLE}{name}{NAME_STYLE:#}: {value}")?;
+                    }
+                }
+            }
+
+            if let Some(verdict) = self.fs_status.verdict {
+                writeln!(f, "{NAME_STYLE}verdict{NAME_STYLE:#}: {verdict:#}")?;
             }
         } else {
             // Short summary.
             write!(f, "{}", self.fs_status.completion)?;
+
+            if let Some(verdict) = self.fs_status.verdict {
+                write!(f, ", {verdict:#}")?;
+            }
         }
 
         Ok(())
@@ -139,6 +226,14 @@ pub fn display_statuses(
     statuses: &ExperimentStatus,
     full: bool,
 ) -> Result<usize> {
+    if experiment
+        .programs
+        .iter()
+        .any(|program| !program.include.is_empty() || !program.exclude.is_empty())
+    {
+        selection_matrix(f, experiment)?;
+    }
+
     if full || experiment.runs.len() <= SHORTEN_STATUS_CUTOFF {
         long_status(f, experiment, statuses)?;
     } else {
@@ -156,6 +251,154 @@ pub fn display_statuses(
     Ok(finished)
 }
 
+/// Display which inputs every program runs on, when programs select them with
+/// `include` and `exclude`.
+///
+/// The inputs are shown by the name they have in the configuration, with the
+/// number of inputs created from it that the program runs on.
+#[cfg(not(tarpaulin_include))] // We can't test stdout
+fn selection_matrix(f: &mut impl Write, experiment: &Experiment) -> Result<()> {
+    let postprocessing: Vec<usize> = experiment
+        .programs
+        .iter()
+        .flat_map(|program| program.next.clone())
+        .collect();
+
+    let mut origins: BTreeMap<&str, Vec<(&FieldRef, _)>> = BTreeMap::new();
+
+    for (name, input) in &experiment.inputs {
+        origins
+            .entry(input.metadata.origin.as_deref().unwrap_or(name))
+            .or_default()
+            .push((name, input));
+    }
+
+    let roots: Vec<_> = experiment
+        .programs
+        .iter()
+        .enumerate()
+        .filter(|(id, _)| !postprocessing.contains(id))
+        .map(|(_, program)| program)
+        .collect();
+
+    let width = roots
+        .iter()
+        .map(|program| program.name.len())
+        .max()
+        .unwrap_or(0);
+
+    writeln!(
+        f,
+        "{PRIMARY_STYLE}Inputs selected per program{PRIMARY_STYLE:#}:"
+    )?;
+
+    write!(f, "  {:width$}", "")?;
+    for origin in origins.keys() {
+        write!(f, " {NAME_STYLE}{origin}{NAME_STYLE:#}")?;
+    }
+    writeln!(f)?;
+
+    for program in roots {
+        write!(f, "  {:width$}", program.name)?;
+
+        for (origin, inputs) in &origins {
+            let count = inputs
+                .iter()
+                .filter(|(name, input)| program.runs_on(name, input))
+                .count();
+
+            let cell = if count == 0 {
+                "-".to_string()
+            } else {
+                count.to_string()
+            };
+
+            write!(f, " {cell:>0$}", origin.len())?;
+        }
+
+        writeln!(f)?;
+    }
+
+    writeln!(f)?;
+
+    Ok(())
+}
+
+/// Display the inputs on which the programs gave different outputs.
+///
+/// The runs are listed per output, the consensus output first.
+#[cfg(not(tarpaulin_include))] // We can't test stdout
+pub fn display_agreement(
+    f: &mut impl Write,
+    experiment: &Experiment,
+    agreements: &[InputAgreement],
+) -> Result<()> {
+    let agreeing = agreements
+        .iter()
+        .filter(|agreement| agreement.agrees())
+        .count();
+
+    writeln!(
+        f,
+        "{PRIMARY_STYLE}Output agreement{PRIMARY_STYLE:#}: \
+        the programs agree on {agreeing} of {} inputs",
+        agreements.len()
+    )?;
+
+    let format_runs = |class: &Vec<usize>| {
+        class
+            .iter()
+            .map(|run| {
+                format!(
+                    "{} (run {run})",
+                    experiment.programs[experiment.runs[*run].program].name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    for agreement in agreements.iter().filter(|agreement| !agreement.agrees()) {
+        writeln!(
+            f,
+            "  {NAME_STYLE}{}{NAME_STYLE:#}: {} different outputs",
+            agreement.input,
+            agreement.classes.len()
+        )?;
+
+        match agreement.consensus {
+            Some(consensus) => {
+                writeln!(
+                    f,
+                    "    consensus: {}",
+                    format_runs(&agreement.classes[consensus])
+                )?;
+
+                for (index, class) in agreement.classes.iter().enumerate() {
+                    if index != consensus {
+                        writeln!(
+                            f,
+                            "    {ERROR_STYLE}differs{ERROR_STYLE:#}:   {}",
+                            format_runs(class)
+                        )?;
+                    }
+                }
+            }
+            None => {
+                writeln!(f, "    {WARNING_STYLE}no consensus{WARNING_STYLE:#}")?;
+
+                for (index, class) in agreement.classes.iter().enumerate() {
+                    writeln!(f, "    output {}:  {}", index + 1, format_runs(class))?;
+                }
+            }
+        }
+    }
+
+    writeln!(f)?;
+
+    Ok(())
+}
+
 /// Display a shortened status for a lot of runs.
 #[cfg(not(tarpaulin_include))] // We can't test stdout
 fn short_status(
@@ -169,6 +412,7 @@ fn short_status(
     writeln!(f, "Showing shortened output...")?;
 
     let mut by_program: BTreeMap<String, (usize, usize, usize, usize)> = BTreeMap::new();
+    let mut incorrect: BTreeMap<String, usize> = BTreeMap::new();
 
     for (run_id, run_data) in runs.iter().enumerate() {
         if !by_program.contains_key(&run_data.program.to_string()) {
@@ -190,6 +434,10 @@ fn short_status(
                 for_this_prog.2 += 1;
             }
 
+            if status.fs_status.verdict == Some(Verdict::Incorrect) {
+                *incorrect.entry(run_data.program.to_string()).or_default() += 1;
+            }
+
             for_this_prog.3 += 1;
         }
     }
@@ -219,6 +467,13 @@ fn short_status(
             "  ... {} of which have {PRIMARY_STYLE}succeded{PRIMARY_STYLE:#}",
             completed - failed
         )?;
+        if let Some(incorrect) = incorrect.get(&prog) {
+            writeln!(
+                f,
+                "  ... {} of which gave an {ERROR_STYLE}incorrect{ERROR_STYLE:#} output",
+                incorrect
+            )?;
+        }
         if experiment.env == Environment::Slurm {
             writeln!(f, "  {} jobs still need to be scheduled", total - sched)?;
         }
@@ -229,7 +484,7 @@ fn short_status(
 
 /// For an input, decide how it's shown to a user.
 fn format_input_name(exp: &Experiment, run: &Run, grouped: bool) -> String {
-    if !grouped {
+    let name = if !grouped {
         if let Some(input_name) = &run.generated_from_input {
             input_name.clone()
         } else if let Some(parent_id) = run.parent {
@@ -243,7 +498,13 @@ fn format_input_name(exp: &Experiment, run: &Run, grouped: bool) -> String {
         // when this function was implemented this branch was unreachable,
         // but it is reasonable that this will change in the future, and not
         // panicking here seems reasonable.
-        format_input_name(exp, run, false)
+        return format_input_name(exp, run, false);
+    };
+
+    if let Some(repetition) = run.repetition {
+        format!("{name} #{repetition}")
+    } else {
+        name
     }
 }
 
@@ -377,12 +638,17 @@ fn display_runs(
                 PRIMARY_STYLE
             };
 
+            let source = status
+                .fs_status
+                .label_source
+                .map_or(String::new(), |source| source.to_string());
+
             write!(
                 f,
                 "  {: >numw$}a {:.<width$}.... \
                             label: {display_style}{label_text}{display_style:#}",
                 run_id,
-                "afterscript",
+                source,
                 numw = longest_index,
                 width = longest_input,
             )?;
@@ -441,6 +707,19 @@ pub fn display_job(
                 .unwrap_or("None".to_string())
         )?;
 
+        if let Some(generator) = &run.input.generator {
+            writeln!(
+                f,
+                "  {NAME_STYLE}generated by{NAME_STYLE:#}: {:?}",
+                generator.command
+            )?;
+            writeln!(
+                f,
+                "  {NAME_STYLE}generator seed{NAME_STYLE:#}: {}",
+                generator.seed
+            )?;
+        }
+
         writeln!(
             f,
             "  {NAME_STYLE}arguments{NAME_STYLE:#}: {:?}\n",
@@ -451,6 +730,14 @@ pub fn display_job(
             writeln!(f, "{NAME_STYLE}group{NAME_STYLE:#}: {}", group)?;
         }
 
+        if let Some(repetition) = run.repetition {
+            writeln!(f, "{NAME_STYLE}repetition{NAME_STYLE:#}: {}", repetition)?;
+        }
+
+        if let Some(seed) = run.seed {
+            writeln!(f, "{NAME_STYLE}seed{NAME_STYLE:#}: {}", seed)?;
+        }
+
         writeln!(
             f,
             "{NAME_STYLE}output path{NAME_STYLE:#}: {PATH_STYLE}{}{PATH_STYLE:#}",
@@ -471,7 +758,8 @@ pub fn display_job(
             writeln!(
                 f,
                 "scheduled on slurm as {TERTIARY_STYLE}{}{TERTIARY_STYLE:#}\nwith limits\n{}",
-                slurm_id, run.limits
+                slurm_id,
+                run.limits.unwrap_or_default()
             )?;
 
             if let Some(slurm_file) = &statuses[&id].slurm_file_text {
@@ -502,6 +790,16 @@ pub fn display_job(
 
         writeln!(f, "{status:#}")?;
 
+        if let Some(samples_path) = &run.samples_path {
+            match read_samples(samples_path) {
+                Ok(samples) if !samples.is_empty() => {
+                    display_sample_peaks(f, &samples)?;
+                }
+                Ok(_) => debug!("Run {id} has no resource usage samples yet"),
+                Err(e) => debug!("Could not read the samples of run {id}: {e:?}"),
+            }
+        }
+
         if let Some(Some(label_text)) = &status.fs_status.afterscript_completion {
             let display_style = if exp.labels.map[label_text].rerun_by_default {
                 ERROR_STYLE
@@ -509,10 +807,16 @@ pub fn display_job(
                 PRIMARY_STYLE
             };
 
+            let source = match status.fs_status.label_source {
+                Some(LabelSource::Afterscript) => "afterscript ran and assigned",
+                Some(LabelSource::Verdict) => "verdict assigned",
+                Some(LabelSource::Termination) => "termination assigned",
+                None => "assigned",
+            };
+
             writeln!(
                 f,
-                "{NAME_STYLE}afterscript ran and assigned \
-                    label{NAME_STYLE:#}: {display_style}{label_text}{display_style:#}",
+                "{NAME_STYLE}{source} label{NAME_STYLE:#}: {display_style}{label_text}{display_style:#}",
             )?;
 
             writeln!(f)?;
@@ -542,3 +846,42 @@ pub fn display_job(
         ))
     }
 }
+
+/// Display a summary of the resource usage samples of a run.
+fn display_sample_peaks(f: &mut impl Write, samples: &[Sample]) -> Result<()> {
+    let peaks = Sample::peaks(samples);
+
+    writeln!(
+        f,
+        "{NAME_STYLE}sampled usage{NAME_STYLE:#} ({} samples over {}):",
+        samples.len(),
+        humantime::Duration::from(Duration::from_millis(peaks.millis))
+    )?;
+    writeln!(
+        f,
+        "  {NAME_STYLE}peak memory{NAME_STYLE:#}: {} kB",
+        peaks.rss_kb
+    )?;
+    writeln!(
+        f,
+        "  {NAME_STYLE}peak threads{NAME_STYLE:#}: {}",
+        peaks.threads
+    )?;
+    writeln!(
+        f,
+        "  {NAME_STYLE}peak processes{NAME_STYLE:#}: {}",
+        peaks.processes
+    )?;
+    writeln!(
+        f,
+        "  {NAME_STYLE}bytes read{NAME_STYLE:#}: {}",
+        peaks.read_bytes
+    )?;
+    writeln!(
+        f,
+        "  {NAME_STYLE}bytes written{NAME_STYLE:#}: {}\n",
+        peaks.write_bytes
+    )?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -7,7 +8,9 @@ use anstyle::AnsiColor;
 use anstyle::Color;
 use anstyle::Style;
 use chrono::Local;
+use gourd_lib::config::compare::Comparison;
 use gourd_lib::config::Config;
+use gourd_lib::config::Parameter;
 use gourd_lib::config::UserInput;
 use gourd_lib::config::UserProgram;
 use gourd_lib::constants::style_from_fg;
@@ -56,6 +59,18 @@ pub fn create_sample_experiment(
         resource_limits: None,
         labels: Some(BTreeMap::new()),
         warn_on_label_overlap: false,
+        sampling_interval: None,
+        performance_counters: false,
+        repetitions: None,
+        seed: None,
+        search: None,
+        constraints: vec![],
+        zip: vec![],
+        tune: None,
+        compare: Comparison::default(),
+        agreement: None,
+        env: BTreeMap::new(),
+        clear_env: false,
     };
 
     (
@@ -64,6 +79,35 @@ pub fn create_sample_experiment(
     )
 }
 
+/// The configuration that the experiment tests start from, with its folders
+/// in `dir`.
+pub fn test_config(dir: &TempDir) -> Config {
+    let mut config = Config::from_file(
+        Path::new("src/gourd/experiments/tests/test_resources/config_id_testing.toml"),
+        &REAL_FS,
+    )
+    .unwrap();
+
+    config.output_path = dir.path().to_path_buf();
+    config.metrics_path = dir.path().to_path_buf();
+    config.experiments_folder = dir.path().to_path_buf();
+
+    config
+}
+
+/// A parameter that takes these values.
+pub fn parameter(values: &[&str]) -> Parameter {
+    Parameter {
+        sub: None,
+        values: Some(values.iter().map(|value| value.to_string()).collect()),
+        range: None,
+        linspace: None,
+        logspace: None,
+        uniform: None,
+        loguniform: None,
+    }
+}
+
 #[test]
 fn test_style() {
     assert_eq!(
@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use anyhow::Result;
+use chrono::Local;
+use gourd_lib::bailc;
+use gourd_lib::config::constraints::Constraint;
+use gourd_lib::config::parameters::check_sub_parameter_size_is_equal;
+use gourd_lib::config::parameters::generate_parameter_values;
+use gourd_lib::config::parameters::input_parameters;
+use gourd_lib::config::parameters::validate_parameters;
+use gourd_lib::config::parameters::validate_zipped;
+use gourd_lib::config::search::SearchRng;
+use gourd_lib::config::tune::TuneConfig;
+use gourd_lib::config::tune::TuneGoal;
+use gourd_lib::config::tune::TuneStrategy;
+use gourd_lib::config::Config;
+use gourd_lib::constants::CMD_STYLE;
+use gourd_lib::experiment::tuning::Configuration;
+use gourd_lib::experiment::tuning::Tuning;
+use gourd_lib::experiment::Environment;
+use gourd_lib::experiment::Experiment;
+use gourd_lib::experiment::FieldRef;
+use gourd_lib::file_system::FileOperations;
+use gourd_lib::measurement::Measurement;
+use log::info;
+use log::warn;
+
+use self::strategies::dimensions;
+use self::strategies::halving;
+use self::strategies::propose_tpe;
+use self::strategies::random_configurations;
+use self::strategies::ranked;
+use self::strategies::Values;
+use crate::experiments::dfs::create_runs;
+use crate::experiments::dfs::Pairings;
+use crate::experiments::ExperimentExt;
+use crate::status::ExperimentStatus;
+use crate::status::FsState;
+
+/// Choosing the next configurations of parameter values.
+pub mod strategies;
+
+/// What a step of tuning did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuneStep {
+    /// The runs of this many configurations have not all completed yet.
+    Waiting(usize),
+
+    /// This many runs were added to the experiment.
+    Added(usize),
+
+    /// Tuning has finished.
+    Finished,
+}
+
+/// Create an experiment that tunes the parameters of the inputs, with the runs
+/// of the first configurations of parameter values.
+///
+/// The inputs that use parameters become templates, every configuration gets
+/// its own copy of them. The other inputs are run once, as with `gourd run`.
+pub fn start_tuning(
+    conf: &Config,
+    env: Environment,
+    fs: &impl FileOperations,
+) -> Result<Experiment> {
+    let Some(tune) = conf.tune.clone() else {
+        bailc!(
+            "There is nothing to tune", ;
+            "The configuration has no [tune] section", ;
+            "Add one to say how to tune the parameters, see \
+            {CMD_STYLE}man gourd.toml{CMD_STYLE:#}",
+        );
+    };
+
+    check_tune_config(&tune)?;
+
+    let parameters = conf.parameters.clone().unwrap_or_default();
+    validate_parameters(&parameters)?;
+
+    let parameters = generate_parameter_values(&parameters)?;
+    check_sub_parameter_size_is_equal(&parameters)?;
+    validate_zipped(&conf.zip, &parameters)?;
+
+    for constraint in &conf.constraints {
+        Constraint::parse(constraint)?;
+    }
+
+    let mut templates = BTreeMap::new();
+    let mut fixed = BTreeMap::new();
+
+    for (name, input) in &conf.inputs {
+        if input_parameters(input, &parameters)?.is_empty() {
+            fixed.insert(name.clone(), input.clone());
+        } else {
+            templates.insert(name.clone(), input.clone());
+        }
+    }
+
+    if templates.is_empty() {
+        bailc!(
+            "There is nothing to tune", ;
+            "None of the inputs uses a parameter", ;
+            "Use the parameters in the arguments or paths of the inputs",
+        );
+    }
+
+    let mut plain = conf.clone();
+    plain.inputs = fixed;
+    plain.search = None;
+
+    let mut experiment = Experiment::from_config(&plain, Local::now(), env, fs)?;
+
+    let mut tuning = Tuning {
+        config: tune,
+        parameters,
+        zip: conf.zip.clone(),
+        constraints: conf.constraints.clone(),
+        templates,
+        configurations: vec![],
+        spent: 0,
+        finished: false,
+    };
+
+    let dims = dimensions(&tuning)?;
+    let initial = random_configurations(
+        &tuning,
+        &dims,
+        tuning.config.initial,
+        &mut SearchRng::new(tuning.config.seed),
+    )?;
+
+    if initial.is_empty() {
+        bailc!(
+            "There are no configurations to tune", ;
+            "The constraints exclude every combination of parameter values", ;
+            "",
+        );
+    }
+
+    for values in initial {
+        if !add_configuration(&mut experiment, &mut tuning, values, fs)? {
+            warn!(
+                "The budget of {} runs is spent by the first configurations",
+                tuning.config.budget
+            );
+            break;
+        }
+    }
+
+    info!(
+        "Tuning starts with {} configurations of parameter values",
+        tuning.configurations.len()
+    );
+
+    experiment.tuning = Some(tuning);
+
+    Ok(experiment)
+}
+
+/// Take the next step of tuning an experiment, once the runs of all its
+/// configurations have completed.
+///
+/// Scores the configurations, and adds the runs of the configurations that
+/// the strategy chooses next. Tuning finishes once the strategy has nothing
+/// more to try or the budget is spent.
+pub fn tune_step(
+    experiment: &mut Experiment,
+    statuses: &ExperimentStatus,
+    fs: &impl FileOperations,
+) -> Result<TuneStep> {
+    let Some(mut tuning) = experiment.tuning.clone() else {
+        bailc!(
+            "Experiment {} is not tuned", experiment.seq;
+            "", ;
+            "Start tuning with {CMD_STYLE}gourd tune local{CMD_STYLE:#} or \
+            {CMD_STYLE}gourd tune slurm{CMD_STYLE:#}",
+        );
+    };
+
+    if tuning.finished {
+        report_best(
+            &tuning,
+            &score_configurations(experiment, &tuning, statuses),
+        );
+
+        return Ok(TuneStep::Finished);
+    }
+
+    let waiting = tuning
+        .configurations
+        .iter()
+        .filter(|c| {
+            c.runs
+                .iter()
+                .any(|run| !statuses[&latest(experiment, *run)].is_completed())
+        })
+        .count();
+
+    if waiting > 0 {
+        return Ok(TuneStep::Waiting(waiting));
+    }
+
+    let scores = score_configurations(experiment, &tuning, statuses);
+    let before = tuning.spent;
+
+    match tuning.config.strategy {
+        TuneStrategy::Halving => {
+            let reduction = tuning.config.reduction;
+
+            for index in halving(&tuning, &scores) {
+                let round = tuning.configurations[index].round + 1;
+                let repetitions = reduction.saturating_pow(round as u32);
+
+                if !add_repetitions(experiment, &mut tuning, index, repetitions, fs)? {
+                    break;
+                }
+
+                tuning.configurations[index].round = round;
+            }
+        }
+
+        TuneStrategy::Tpe => {
+            let dims = dimensions(&tuning)?;
+            let mut rng = SearchRng::new(
+                tuning
+                    .config
+                    .seed
+                    .wrapping_add(tuning.configurations.len() as u64),
+            );
+
+            for values in propose_tpe(&tuning, &dims, &scores, tuning.config.batch, &mut rng)? {
+                if !add_configuration(experiment, &mut tuning, values, fs)? {
+                    break;
+                }
+            }
+        }
+    }
+
+    let added = tuning.spent - before;
+
+    if added == 0 {
+        tuning.finished = true;
+        report_best(&tuning, &scores);
+    }
+
+    experiment.tuning = Some(tuning);
+
+    if added == 0 {
+        Ok(TuneStep::Finished)
+    } else {
+        Ok(TuneStep::Added(added))
+    }
+}
+
+/// Tell the user what a step of tuning did.
+pub fn report_tune_step(step: TuneStep) {
+    match step {
+        TuneStep::Added(runs) => info!("Tuning added {runs} new runs"),
+        TuneStep::Finished => info!("Tuning finished"),
+        TuneStep::Waiting(configurations) => {
+            info!("The runs of {configurations} configurations have not completed yet")
+        }
+    }
+}
+
+/// Report the best configuration of a tuned experiment, once its runs have
+/// completed.
+pub fn report_best(tuning: &Tuning, scores: &[Option<f64>]) {
+    let indices: Vec<usize> = (0..tuning.configurations.len()).collect();
+
+    match ranked(&indices, scores).first() {
+        Some(best) if scores[*best].is_some() => {
+            let configuration = &tuning.configurations[*best];
+            let score = scores[*best].unwrap_or_default();
+            let score = match tuning.config.goal {
+                TuneGoal::Minimize => score,
+                TuneGoal::Maximize => -score,
+            };
+
+            info!(
+                "The best configuration has a mean {} of {score}, over {} runs:",
+                tuning.config.metric,
+                configuration.runs.len()
+            );
+
+            for (name, value) in &configuration.values {
+                info!("  {name} = {value}");
+            }
+        }
+
+        _ => warn!("None of the configurations completed successfully"),
+    }
+}
+
+/// The score of every configuration, lower is better.
+///
+/// The score is the mean of the metric over the runs of the configuration,
+/// a configuration that failed or did not report the metric has no score.
+pub fn score_configurations(
+    experiment: &Experiment,
+    tuning: &Tuning,
+    statuses: &ExperimentStatus,
+) -> Vec<Option<f64>> {
+    tuning
+        .configurations
+        .iter()
+        .map(|configuration| {
+            if configuration.runs.is_empty() {
+                return None;
+            }
+
+            let mut total = 0.0;
+
+            for run in &configuration.runs {
+                let status = &statuses[&latest(experiment, *run)];
+
+                if status.has_failed(experiment) {
+                    return None;
+                }
+
+                let FsState::Completed(measurement) = &status.fs_status.completion else {
+                    return None;
+                };
+
+                total += metric_value(measurement, &tuning.config.metric)?;
+            }
+
+            let mean = total / configuration.runs.len() as f64;
+
+            Some(match tuning.config.goal {
+                TuneGoal::Minimize => mean,
+                TuneGoal::Maximize => -mean,
+            })
+        })
+        .collect()
+}
+
+/// The value of the tuned `metric` in a measurement of a run.
+pub fn metric_value(measurement: &Measurement, metric: &str) -> Option<f64> {
+    match metric {
+        "wall_time" => Some(measurement.wall_micros.as_secs_f64()),
+        "cpu_time" => measurement
+            .rusage
+            .as_ref()
+            .map(|rusage| (rusage.utime + rusage.stime).as_secs_f64()),
+        custom => measurement.custom_metrics.get(custom).copied(),
+    }
+}
+
+/// Follows the reruns of a run to the newest one.
+fn latest(experiment: &Experiment, mut run: usize) -> usize {
+    while let Some(rerun) = experiment.runs[run].rerun {
+        run = rerun;
+    }
+
+    run
+}
+
+/// Checks that the tuning settings make sense.
+fn check_tune_config(tune: &TuneConfig) -> Result<()> {
+    if tune.budget == 0 || tune.initial == 0 {
+        bailc!(
+            "Invalid tuning configuration", ;
+            "Tuning needs a budget and an initial amount of configurations of at least 1", ;
+            "",
+        );
+    }
+
+    if tune.strategy == TuneStrategy::Halving && tune.reduction < 2 {
+        bailc!(
+            "Invalid tuning configuration", ;
+            "Successive halving needs a reduction of at least 2, not {}", tune.reduction;
+            "",
+        );
+    }
+
+    if tune.strategy == TuneStrategy::Tpe && tune.batch == 0 {
+        bailc!(
+            "Invalid tuning configuration", ;
+            "The Parzen estimator needs to propose a batch of at least 1 configuration", ;
+            "",
+        );
+    }
+
+    Ok(())
+}
+
+/// Adds a new configuration with its runs to the experiment, if the budget
+/// allows for them.
+fn add_configuration(
+    experiment: &mut Experiment,
+    tuning: &mut Tuning,
+    values: Values,
+    fs: &impl FileOperations,
+) -> Result<bool> {
+    let index = tuning.configurations.len();
+    let inputs = tuning.configuration_inputs(index, &values, fs)?;
+
+    let names: Vec<FieldRef> = inputs.keys().cloned().collect();
+    experiment.inputs.extend(inputs);
+
+    let start = experiment.runs.len();
+    let roots = add_runs(
+        experiment,
+        Pairings {
+            inputs: Some(&names),
+            rounds: (0, 1),
+        },
+        fs,
+    )?;
+    let added = experiment.runs.len() - start;
+
+    if tuning.spent + added > tuning.config.budget {
+        experiment.runs.truncate(start);
+
+        for name in &names {
+            experiment.inputs.remove(name);
+        }
+
+        return Ok(false);
+    }
+
+    for name in &names {
+        if let Some(group) = &experiment.inputs[name].metadata.group {
+            if !experiment.groups.contains(group) {
+                experiment.groups.push(group.clone());
+            }
+        }
+    }
+
+    tuning.spent += added;
+    tuning.configurations.push(Configuration {
+        values,
+        inputs: names,
+        runs: roots,
+        repetitions: 1,
+        round: 0,
+    });
+
+    Ok(true)
+}
+
+/// Adds runs to a configuration until every program runs `repetitions` times
+/// on each of its inputs, if the budget allows for them.
+fn add_repetitions(
+    experiment: &mut Experiment,
+    tuning: &mut Tuning,
+    index: usize,
+    repetitions: usize,
+    fs: &impl FileOperations,
+) -> Result<bool> {
+    let configuration = &tuning.configurations[index];
+
+    let start = experiment.runs.len();
+    let roots = add_runs(
+        experiment,
+        Pairings {
+            inputs: Some(&configuration.inputs),
+            rounds: (configuration.repetitions, repetitions),
+        },
+        fs,
+    )?;
+    let added = experiment.runs.len() - start;
+
+    if tuning.spent + added > tuning.config.budget {
+        experiment.runs.truncate(start);
+
+        return Ok(false);
+    }
+
+    tuning.spent += added;
+
+    let configuration = &mut tuning.configurations[index];
+    configuration.runs.extend(roots);
+    configuration.repetitions = repetitions;
+
+    Ok(true)
+}
+
+/// Adds the runs of the `pairings` to the experiment, the same way as the
+/// runs of a new experiment.
+///
+/// Returns the ids of the new runs that run on the inputs.
+fn add_runs(
+    experiment: &mut Experiment,
+    pairings: Pairings,
+    fs: &impl FileOperations,
+) -> Result<Vec<usize>> {
+    let start = experiment.runs.len();
+    let mut runs = std::mem::take(&mut experiment.runs);
+
+    let created = create_runs(experiment, &mut runs, pairings, fs);
+    experiment.runs = runs;
+    created?;
+
+    Ok((start..experiment.runs.len())
+        .filter(|run| experiment.runs[*run].parent.is_none())
+        .collect())
+}
+
+#[cfg(test)]
+#[path = "tests/mod.rs"]
+mod tests;
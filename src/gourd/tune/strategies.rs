@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+
+use anyhow::Context;
+use anyhow::Result;
+use gourd_lib::bailc;
+use gourd_lib::config::constraints::Constraint;
+use gourd_lib::config::parameters::format_value;
+use gourd_lib::config::parameters::input_parameters;
+use gourd_lib::config::search::SearchRng;
+use gourd_lib::constants::SEARCH_SAMPLE_ATTEMPTS;
+use gourd_lib::constants::TUNE_CANDIDATES;
+use gourd_lib::constants::TUNE_GOOD_FRACTION;
+use gourd_lib::experiment::tuning::Tuning;
+
+/// The value of every parameter and subparameter in a configuration.
+pub type Values = BTreeMap<String, String>;
+
+/// One dimension of the space that tuning searches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dimension {
+    /// A choice between options, each of which gives values to one or more
+    /// parameters: a parameter with values, all subparameters of a parameter,
+    /// or a zipped group.
+    Choice(Vec<Values>),
+
+    /// A number between `low` and `high` of the parameter `name`, sampled on a
+    /// logarithmic scale if `log`.
+    Continuous {
+        /// The name of the parameter.
+        name: String,
+
+        /// The lowest value.
+        low: f64,
+
+        /// The highest value.
+        high: f64,
+
+        /// Whether every order of magnitude is as likely.
+        log: bool,
+    },
+}
+
+impl Dimension {
+    /// Draw a value uniformly, and add it to the `values`.
+    fn sample_uniform(&self, values: &mut Values, rng: &mut SearchRng) {
+        match self {
+            Dimension::Choice(options) => {
+                values.extend(options[rng.below(options.len())].clone());
+            }
+            Dimension::Continuous { name, .. } => {
+                let (low, high) = self.bounds();
+
+                values.insert(
+                    name.clone(),
+                    self.format(low + (high - low) * rng.next_f64()),
+                );
+            }
+        }
+    }
+
+    /// The bounds of a continuous dimension, on the scale that it is sampled
+    /// on.
+    fn bounds(&self) -> (f64, f64) {
+        match self {
+            Dimension::Continuous {
+                low,
+                high,
+                log: true,
+                ..
+            } => (low.ln(), high.ln()),
+            Dimension::Continuous { low, high, .. } => (*low, *high),
+            Dimension::Choice(options) => (0.0, options.len() as f64),
+        }
+    }
+
+    /// Formats a point on the sampled scale as a parameter value.
+    fn format(&self, point: f64) -> String {
+        match self {
+            Dimension::Continuous { log: true, .. } => format_value(point.exp()),
+            _ => format_value(point),
+        }
+    }
+
+    /// Where a configuration lies in this dimension: the index of its option,
+    /// or its value on the sampled scale.
+    fn locate(&self, values: &Values) -> Option<f64> {
+        match self {
+            Dimension::Choice(options) => options
+                .iter()
+                .position(|option| option.iter().all(|(k, v)| values.get(k) == Some(v)))
+                .map(|i| i as f64),
+            Dimension::Continuous { name, log, .. } => {
+                let value: f64 = values.get(name)?.parse().ok()?;
+
+                Some(if *log { value.ln() } else { value })
+            }
+        }
+    }
+}
+
+/// The dimensions of the space that tuning searches: the parameters used by
+/// the templates of the inputs.
+pub fn dimensions(tuning: &Tuning) -> Result<Vec<Dimension>> {
+    let mut used = BTreeSet::new();
+
+    for template in tuning.templates.values() {
+        used.extend(input_parameters(template, &tuning.parameters)?);
+    }
+
+    let mut dims = Vec::new();
+    let mut zipped_seen = BTreeSet::new();
+
+    for name in &used {
+        if let Some(group) = tuning.zip.iter().find(|group| group.contains(name)) {
+            if !zipped_seen.insert(group.clone()) {
+                continue;
+            }
+
+            let count = tuning.parameters[&group[0]]
+                .values
+                .as_ref()
+                .map_or(0, |values| values.len());
+
+            dims.push(Dimension::Choice(
+                (0..count)
+                    .map(|i| {
+                        group
+                            .iter()
+                            .filter_map(|member| {
+                                let values = tuning.parameters.get(member)?.values.as_ref()?;
+
+                                Some((member.clone(), values[i].clone()))
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ));
+
+            continue;
+        }
+
+        let Some(param) = tuning.parameters.get(name) else {
+            bailc!(
+                "Invalid parameter specified", ;
+                "Did not find values for parameter {name}", ;
+                "",
+            );
+        };
+
+        let dim = if let Some(subparams) = &param.sub {
+            let count = subparams.values().next().map_or(0, |sub| sub.values.len());
+
+            Dimension::Choice(
+                (0..count)
+                    .map(|i| {
+                        subparams
+                            .iter()
+                            .map(|(sub_name, sub)| {
+                                (format!("{name}.{sub_name}"), sub.values[i].clone())
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            )
+        } else if let Some(values) = &param.values {
+            Dimension::Choice(
+                values
+                    .iter()
+                    .map(|value| Values::from([(name.clone(), value.clone())]))
+                    .collect(),
+            )
+        } else if let Some(spec) = param.uniform {
+            Dimension::Continuous {
+                name: name.clone(),
+                low: spec.low,
+                high: spec.high,
+                log: false,
+            }
+        } else if let Some(spec) = param.loguniform {
+            Dimension::Continuous {
+                name: name.clone(),
+                low: spec.low,
+                high: spec.high,
+                log: true,
+            }
+        } else {
+            bailc!(
+                "Parameter \"{name}\" has no values", ;
+                "Tuning needs values or a distribution to choose from", ;
+                "",
+            );
+        };
+
+        if matches!(&dim, Dimension::Choice(options) if options.is_empty()) {
+            bailc!(
+                "Parameter \"{name}\" has no values", ;
+                "Tuning needs values or a distribution to choose from", ;
+                "",
+            );
+        }
+
+        dims.push(dim);
+    }
+
+    Ok(dims)
+}
+
+/// Checks if a configuration satisfies all constraints that apply to it.
+fn satisfies(constraints: &[Constraint], values: &Values) -> Result<bool> {
+    for constraint in constraints {
+        if constraint.evaluate(values)? == Some(false) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Whether a configuration can still be proposed: it satisfies the
+/// constraints, and was neither tried nor proposed before.
+fn is_new(
+    values: &Values,
+    tuning: &Tuning,
+    constraints: &[Constraint],
+    proposed: &[Values],
+) -> Result<bool> {
+    Ok(satisfies(constraints, values)?
+        && !proposed.contains(values)
+        && !tuning.configurations.iter().any(|c| &c.values == values))
+}
+
+/// Draws up to `count` new configurations uniformly from the dimensions.
+pub fn random_configurations(
+    tuning: &Tuning,
+    dims: &[Dimension],
+    count: usize,
+    rng: &mut SearchRng,
+) -> Result<Vec<Values>> {
+    let mut proposed = Vec::new();
+
+    draw_uniform(tuning, dims, count, (&parse(tuning)?, &mut proposed), rng)?;
+
+    Ok(proposed)
+}
+
+/// Draws up to `count` more new configurations uniformly from the dimensions
+/// into `proposed` (Helper function).
+fn draw_uniform(
+    tuning: &Tuning,
+    dims: &[Dimension],
+    count: usize,
+    (constraints, proposed): (&[Constraint], &mut Vec<Values>),
+    rng: &mut SearchRng,
+) -> Result<()> {
+    let target = proposed.len() + count;
+
+    for _ in 0..count.saturating_mul(SEARCH_SAMPLE_ATTEMPTS) {
+        if proposed.len() == target {
+            break;
+        }
+
+        let mut values = Values::new();
+
+        for dim in dims {
+            dim.sample_uniform(&mut values, rng);
+        }
+
+        if is_new(&values, tuning, constraints, proposed)? {
+            proposed.push(values);
+        }
+    }
+
+    Ok(())
+}
+
+/// The configurations that move on to the next round of successive halving,
+/// best first: the best `1 / reduction` of the configurations of the current
+/// round.
+///
+/// Halving is done when a single configuration is left.
+pub fn halving(tuning: &Tuning, scores: &[Option<f64>]) -> Vec<usize> {
+    let round = tuning
+        .configurations
+        .iter()
+        .map(|c| c.round)
+        .max()
+        .unwrap_or(0);
+
+    let current: Vec<usize> = (0..tuning.configurations.len())
+        .filter(|i| tuning.configurations[*i].round == round)
+        .collect();
+
+    if current.len() <= 1 {
+        return vec![];
+    }
+
+    let keep = (current.len() / tuning.config.reduction.max(2)).max(1);
+
+    ranked(&current, scores).into_iter().take(keep).collect()
+}
+
+/// The `indices` of configurations sorted from best to worst, those without a
+/// score last.
+pub fn ranked(indices: &[usize], scores: &[Option<f64>]) -> Vec<usize> {
+    let mut sorted = indices.to_vec();
+
+    sorted.sort_by(|a, b| match (scores[*a], scores[*b]) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    sorted
+}
+
+/// Proposes up to `count` new configurations with a tree-structured Parzen
+/// estimator.
+///
+/// The scored configurations are split into the good ones and the bad ones,
+/// and for each proposal the candidate most likely among the good and least
+/// likely among the bad configurations is chosen.
+pub fn propose_tpe(
+    tuning: &Tuning,
+    dims: &[Dimension],
+    scores: &[Option<f64>],
+    count: usize,
+    rng: &mut SearchRng,
+) -> Result<Vec<Values>> {
+    let scored: Vec<usize> = (0..scores.len()).filter(|i| scores[*i].is_some()).collect();
+
+    // There is nothing to learn from yet.
+    if scored.len() < 2 {
+        return random_configurations(tuning, dims, count, rng);
+    }
+
+    let sorted = ranked(&scored, scores);
+    let good_count =
+        ((sorted.len() as f64 * TUNE_GOOD_FRACTION).ceil() as usize).clamp(1, sorted.len() - 1);
+
+    let locate = |indices: &[usize]| -> Vec<Vec<f64>> {
+        dims.iter()
+            .map(|dim| {
+                indices
+                    .iter()
+                    .filter_map(|i| dim.locate(&tuning.configurations[*i].values))
+                    .collect()
+            })
+            .collect()
+    };
+
+    let good = locate(&sorted[..good_count]);
+    let bad = locate(&sorted[good_count..]);
+
+    let constraints = parse(tuning)?;
+    let mut proposed = Vec::new();
+
+    for _ in 0..count {
+        let mut best: Option<(f64, Values)> = None;
+
+        for _ in 0..TUNE_CANDIDATES {
+            let mut values = Values::new();
+            let mut ratio = 0.0;
+
+            for (d, dim) in dims.iter().enumerate() {
+                let point = sample_parzen(dim, &good[d], rng);
+
+                ratio += parzen_density(dim, &good[d], point).ln()
+                    - parzen_density(dim, &bad[d], point).ln();
+
+                match dim {
+                    Dimension::Choice(options) => values.extend(options[point as usize].clone()),
+                    Dimension::Continuous { name, .. } => {
+                        values.insert(name.clone(), dim.format(point));
+                    }
+                }
+            }
+
+            if best.as_ref().is_none_or(|(r, _)| ratio > *r)
+                && is_new(&values, tuning, &constraints, &proposed)?
+            {
+                best = Some((ratio, values));
+            }
+        }
+
+        match best {
+            Some((_, values)) => proposed.push(values),
+            // The estimator keeps proposing known configurations, so explore
+            // instead.
+            None => draw_uniform(tuning, dims, 1, (&constraints, &mut proposed), rng)?,
+        }
+    }
+
+    Ok(proposed)
+}
+
+/// Draws a point from the Parzen estimator fitted to the `observed` points of
+/// a dimension, with a uniform prior.
+fn sample_parzen(dim: &Dimension, observed: &[f64], rng: &mut SearchRng) -> f64 {
+    match dim {
+        Dimension::Choice(options) => {
+            let weights: Vec<f64> = (0..options.len())
+                .map(|i| 1.0 + observed.iter().filter(|o| **o as usize == i).count() as f64)
+                .collect();
+
+            let mut target = rng.next_f64() * weights.iter().sum::<f64>();
+
+            for (i, weight) in weights.iter().enumerate() {
+                if target < *weight {
+                    return i as f64;
+                }
+                target -= weight;
+            }
+
+            (options.len() - 1) as f64
+        }
+        Dimension::Continuous { .. } => {
+            let (low, high) = dim.bounds();
+            let component = rng.below(observed.len() + 1);
+
+            // The last component is the prior.
+            if component == observed.len() {
+                return low + (high - low) * rng.next_f64();
+            }
+
+            // A normally distributed number, with the Box-Muller transform.
+            let normal =
+                (-2.0 * (1.0 - rng.next_f64()).ln()).sqrt() * (2.0 * PI * rng.next_f64()).cos();
+
+            (observed[component] + bandwidth(dim, observed) * normal).clamp(low, high)
+        }
+    }
+}
+
+/// The density of the Parzen estimator fitted to the `observed` points of a
+/// dimension at a `point`.
+fn parzen_density(dim: &Dimension, observed: &[f64], point: f64) -> f64 {
+    match dim {
+        Dimension::Choice(options) => {
+            let count = observed.iter().filter(|o| **o == point).count();
+
+            (1.0 + count as f64) / (options.len() + observed.len()) as f64
+        }
+        Dimension::Continuous { .. } => {
+            let (low, high) = dim.bounds();
+
+            if high <= low {
+                return 1.0;
+            }
+
+            let sigma = bandwidth(dim, observed);
+            let kernels: f64 = observed
+                .iter()
+                .map(|o| (-0.5 * ((point - o) / sigma).powi(2)).exp() / (sigma * (2.0 * PI).sqrt()))
+                .sum();
+
+            (kernels + 1.0 / (high - low)) / (observed.len() + 1) as f64
+        }
+    }
+}
+
+/// The width of the kernels of the Parzen estimator, narrower with more
+/// observations.
+fn bandwidth(dim: &Dimension, observed: &[f64]) -> f64 {
+    let (low, high) = dim.bounds();
+
+    ((high - low) * ((observed.len() + 1) as f64).powf(-0.2)).max(f64::MIN_POSITIVE)
+}
+
+/// Parses the constraints of the tuning.
+fn parse(tuning: &Tuning) -> Result<Vec<Constraint>> {
+    tuning
+        .constraints
+        .iter()
+        .map(|constraint| Constraint::parse(constraint))
+        .collect()
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use gourd_lib::measurement::Termination;
+use tempdir::TempDir;
+
+use super::*;
+use crate::status::DynamicStatus;
+use crate::status::FileSystemBasedStatus;
+use crate::status::Status;
+use crate::test_utils::REAL_FS;
+
+/// The tuning test configuration, with its folders in a temporary directory.
+fn tune_config(tempdir: &TempDir) -> Config {
+    let mut config = Config::from_file(
+        Path::new("src/gourd/tune/tests/test_resources/config_tune.toml"),
+        &REAL_FS,
+    )
+    .unwrap();
+
+    config.output_path = PathBuf::from(tempdir.path());
+    config.metrics_path = PathBuf::from(tempdir.path());
+    config.experiments_folder = PathBuf::from(tempdir.path());
+
+    config
+}
+
+/// Statuses in which every run has completed, taking as many seconds as the
+/// value of `x` in its arguments.
+fn completed(experiment: &Experiment) -> ExperimentStatus {
+    experiment
+        .runs
+        .iter()
+        .enumerate()
+        .map(|(id, run)| {
+            let seconds = run
+                .input
+                .arguments
+                .last()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(0);
+
+            (
+                id,
+                Status {
+                    slurm_status: None,
+                    fs_status: FileSystemBasedStatus {
+                        completion: FsState::Completed(Measurement {
+                            wall_micros: Duration::from_secs(seconds),
+                            termination: Termination::Exited { code: 0 },
+                            rusage: None,
+                            limit_exceeded: None,
+                            counters: None,
+                            custom_metrics: BTreeMap::new(),
+                            setup: None,
+                            teardown: None,
+                        }),
+                        afterscript_completion: None,
+                        label_source: None,
+                        verdict: None,
+                    },
+                    slurm_file_text: None,
+                },
+            )
+        })
+        .collect()
+}
+
+/// The values of `x` of the configurations, in order.
+fn tried(experiment: &Experiment) -> Vec<String> {
+    experiment
+        .tuning
+        .as_ref()
+        .unwrap()
+        .configurations
+        .iter()
+        .map(|c| c.values["x"].clone())
+        .collect()
+}
+
+#[test]
+fn successive_halving() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let config = tune_config(&tempdir);
+
+    let mut experiment = start_tuning(&config, Environment::Local, &REAL_FS).unwrap();
+
+    // Both programs run on the fixed input, and on the 8 configurations.
+    assert_eq!(2 + 8 * 2, experiment.runs.len());
+    assert_eq!(8, tried(&experiment).len());
+    assert!(!tried(&experiment).contains(&"5".to_string()));
+
+    let waiting = experiment.status(&REAL_FS).unwrap();
+    assert_eq!(
+        TuneStep::Waiting(8),
+        tune_step(&mut experiment, &waiting, &REAL_FS).unwrap()
+    );
+
+    // The best 8 / 3 = 2 configurations run 3 times.
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Added(2 * 2 * 2),
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+
+    let tuning = experiment.tuning.clone().unwrap();
+    let promoted: Vec<&Configuration> = tuning
+        .configurations
+        .iter()
+        .filter(|c| c.round == 1)
+        .collect();
+
+    assert_eq!(2, promoted.len());
+    assert!(promoted
+        .iter()
+        .all(|c| c.repetitions == 3 && c.runs.len() == 6));
+    assert_eq!(
+        vec!["1", "2"],
+        promoted
+            .iter()
+            .map(|c| c.values["x"].as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // The best one runs 9 times, and then it is the only one left.
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Added(6 * 2),
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Finished,
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+    assert!(experiment.tuning.as_ref().unwrap().finished);
+    assert_eq!(2 + 8 * 2 + 8 + 12, experiment.runs.len());
+}
+
+#[test]
+fn halving_keeps_repetitions() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = tune_config(&tempdir);
+    config.programs.get_mut("solver").unwrap().repetitions = Some(2);
+
+    let mut experiment = start_tuning(&config, Environment::Local, &REAL_FS).unwrap();
+
+    // The solver runs twice on the fixed input, and on the 8 configurations.
+    assert_eq!(3 + 8 * 3, experiment.runs.len());
+
+    // The best 2 configurations run 3 times as often, so 4 more times for the
+    // solver.
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Added(2 * (4 + 2)),
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+
+    let repetitions = |program: usize| -> BTreeSet<usize> {
+        experiment.runs[3 + 8 * 3..]
+            .iter()
+            .filter(|run| run.program == program)
+            .filter_map(|run| run.repetition)
+            .collect()
+    };
+    assert_eq!(BTreeSet::from([2, 3, 4, 5]), repetitions(1));
+    assert_eq!(BTreeSet::from([1, 2]), repetitions(0));
+}
+
+#[test]
+fn parzen_estimator_within_budget() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = tune_config(&tempdir);
+    config.programs.get_mut("solver").unwrap().next = vec!["check".to_string()];
+    config.tune = Some(TuneConfig {
+        strategy: TuneStrategy::Tpe,
+        budget: 10,
+        initial: 3,
+        metric: "wall_time".to_string(),
+        goal: TuneGoal::Minimize,
+        reduction: 3,
+        batch: 2,
+        seed: 7,
+    });
+
+    let mut experiment = start_tuning(&config, Environment::Local, &REAL_FS).unwrap();
+    assert_eq!(6, experiment.tuning.as_ref().unwrap().spent);
+
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Added(4),
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+
+    // Another configuration does not fit in the budget.
+    let statuses = completed(&experiment);
+    assert_eq!(
+        TuneStep::Finished,
+        tune_step(&mut experiment, &statuses, &REAL_FS).unwrap()
+    );
+
+    let values = tried(&experiment);
+    assert_eq!(5, values.len());
+    assert!(!values.contains(&"5".to_string()));
+    assert_eq!(5, values.iter().collect::<BTreeSet<_>>().len());
+
+    // The configurations keep their runs on the inputs, the checks run on their
+    // output.
+    for configuration in &experiment.tuning.as_ref().unwrap().configurations {
+        let [run] = configuration.runs[..] else {
+            panic!("expected a single run, got {:?}", configuration.runs);
+        };
+
+        let check = experiment
+            .runs
+            .iter()
+            .position(|r| r.parent == Some(run))
+            .unwrap();
+
+        assert_eq!(
+            experiment.runs[run].output_path,
+            experiment.runs[check].input.file.clone().unwrap()
+        );
+    }
+}
+
+#[test]
+fn scores_follow_the_goal() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = tune_config(&tempdir);
+    let tune = config.tune.as_mut().unwrap();
+    tune.initial = 3;
+    tune.metric = "quality".to_string();
+    tune.goal = TuneGoal::Maximize;
+
+    let experiment = start_tuning(&config, Environment::Local, &REAL_FS).unwrap();
+    let tuning = experiment.tuning.as_ref().unwrap();
+
+    let mut statuses = completed(&experiment);
+
+    // Without the custom metric there is no score.
+    assert_eq!(
+        vec![None; 3],
+        score_configurations(&experiment, tuning, &statuses)
+    );
+
+    for status in statuses.values_mut() {
+        if let FsState::Completed(measurement) = &mut status.fs_status.completion {
+            measurement
+                .custom_metrics
+                .insert("quality".to_string(), 2.5);
+        }
+    }
+
+    assert_eq!(
+        vec![Some(-2.5); 3],
+        score_configurations(&experiment, tuning, &statuses)
+    );
+}
+
+#[test]
+fn nothing_to_tune() {
+    let tempdir = TempDir::new("tests").unwrap();
+    let mut config = tune_config(&tempdir);
+
+    config.inputs.remove("instance");
+    assert!(start_tuning(&config, Environment::Local, &REAL_FS).is_err());
+
+    config.tune = None;
+    assert!(start_tuning(&config, Environment::Local, &REAL_FS).is_err());
+}
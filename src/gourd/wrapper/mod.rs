@@ -3,13 +3,13 @@ mod check_binary_linux;
 /// Binary verification for macos.
 mod check_binary_macos;
 
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
 use std::path::PathBuf;
 /// Verify if the architecture of a `binary` matched the `expected`
 /// architecture.
 use std::process::Command;
 
 use anyhow::Result;
+use gourd_lib::config::slurm::ResourceLimits;
 use gourd_lib::experiment::Experiment;
 use gourd_lib::file_system::FileOperations;
 use log::trace;
@@ -21,6 +21,22 @@ use crate::wrapper::check_binary_linux::verify_arch;
 #[cfg(target_os = "macos")]
 use crate::wrapper::check_binary_macos::verify_arch;
 
+/// The invocation of the wrapper for a run.
+#[derive(Debug)]
+pub struct WrappedRun {
+    /// The command that runs the wrapper.
+    pub command: Command,
+
+    /// The resource limits of the run.
+    pub limits: ResourceLimits,
+
+    /// Where the wrapper writes the metrics of the run.
+    pub metrics_path: PathBuf,
+
+    /// Where the pid of the wrapper is recorded while it runs.
+    pub pid_path: PathBuf,
+}
+
 /// Verify the architecture of the binary.
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
 fn verify_arch(_: &PathBuf, _: &str, _: &impl FileOperations) -> Result<()> {
@@ -36,7 +52,7 @@ pub fn wrap(
     status: &ExperimentStatus,
     arch: &str,
     fs: &impl FileOperations,
-) -> Result<Vec<Command>> {
+) -> Result<Vec<WrappedRun>> {
     let mut result = Vec::new();
 
     let binding = experiment.clone();
@@ -46,7 +62,7 @@ pub fn wrap(
 
     trace!("There are {} unscheduled runs", runs_to_iterate.len());
 
-    for (task_id, run) in runs_to_iterate.into_iter().map(|(_, r)| r).enumerate() {
+    for (task_id, (run_id, run)) in runs_to_iterate.into_iter().enumerate() {
         let program = &experiment.get_program(run)?;
 
         verify_arch(&program.binary, arch, fs)?;
@@ -57,7 +73,12 @@ pub fn wrap(
             .arg(format!("{}", chunk_index))
             .arg(format!("{}", task_id));
 
-        result.push(cmd);
+        result.push(WrappedRun {
+            command: cmd,
+            limits: run.limits.unwrap_or_default(),
+            metrics_path: run.metrics_path.clone(),
+            pid_path: experiment.wrapper_pid_file(run_id),
+        });
     }
 
     Ok(result)
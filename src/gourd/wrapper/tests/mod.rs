@@ -53,6 +53,15 @@ fn non_matching_arch() {
             afterscript: None,
             resource_limits: None,
             next: vec![],
+            repetitions: None,
+            parameters: None,
+            zip: vec![],
+            include: vec![],
+            exclude: vec![],
+            stdin: true,
+            setup: vec![],
+            teardown: vec![],
+            env: BTreeMap::new(),
         },
     );
 
@@ -66,6 +75,11 @@ fn non_matching_arch() {
             arguments: vec![],
             fetch: None,
             group: None,
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
 
@@ -111,6 +125,15 @@ fn matching_arch() {
             afterscript: None,
             resource_limits: None,
             next: vec![],
+            repetitions: None,
+            parameters: None,
+            zip: vec![],
+            include: vec![],
+            exclude: vec![],
+            stdin: true,
+            setup: vec![],
+            teardown: vec![],
+            env: BTreeMap::new(),
         },
     );
 
@@ -124,6 +147,11 @@ fn matching_arch() {
             fetch: None,
             group: None,
             arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
 
@@ -137,7 +165,7 @@ fn matching_arch() {
 
     assert_eq!(1, cmds.len());
     assert_eq!(
-        format!("{:?}", cmds[0]),
+        format!("{:?}", cmds[0].command),
         format!(
             "{:?}",
             Command::new(conf.wrapper)
@@ -146,4 +174,10 @@ fn matching_arch() {
                 .arg("0")
         )
     );
+    assert_eq!(
+        experiment.runs[0].limits.unwrap_or_default(),
+        cmds[0].limits
+    );
+    assert_eq!(experiment.runs[0].metrics_path, cmds[0].metrics_path);
+    assert_eq!(experiment.wrapper_pid_file(0), cmds[0].pid_path);
 }
@@ -0,0 +1,97 @@
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::bailc;
+use crate::config::maps::canon_command;
+use crate::file_system::FileOperations;
+
+/// How the output of a run is compared with the `expected` output of its
+/// input.
+///
+/// # Examples
+///
+/// ```toml
+/// [compare]
+/// mode = "numeric"
+/// tolerance = 1e-6
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "mode", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Comparison {
+    /// The output is exactly the expected output.
+    #[default]
+    Exact,
+
+    /// The output has the same words as the expected output, however they
+    /// are separated by whitespace.
+    Whitespace,
+
+    /// Like `whitespace`, but the numbers in the output can differ from the
+    /// expected numbers by at most `tolerance`.
+    Numeric {
+        /// How much a number can differ from the expected number.
+        tolerance: f64,
+    },
+
+    /// A command decides: it gets the output file and the expected file as its
+    /// last two arguments, and exits successfully if the output is correct.
+    Checker {
+        /// The checker and its arguments.
+        command: Vec<String>,
+    },
+}
+
+impl Comparison {
+    /// Checks the comparison, and makes the path of a checker absolute so that
+    /// it can be run from the working directory of a run.
+    pub fn resolve(&self, fs: &impl FileOperations) -> Result<Self> {
+        match self {
+            Comparison::Numeric { tolerance } if tolerance.is_nan() || *tolerance < 0.0 => {
+                bailc!(
+                    "The tolerance of the numeric comparison is {tolerance}", ;
+                    "The tolerance is how much numbers can differ from the expected numbers",;
+                    "Set `tolerance` to 0 or more",
+                );
+            }
+
+            Comparison::Checker { command } if command.is_empty() => {
+                bailc!(
+                    "The checker of the comparison has no command",;
+                    "The command is the checker followed by its arguments",;
+                    "Set `command`, for example to [\"./check\"]",
+                );
+            }
+
+            Comparison::Checker { command } => Ok(Comparison::Checker {
+                command: canon_command(command, fs)?,
+            }),
+
+            _ => Ok(self.clone()),
+        }
+    }
+}
+
+/// Checking that the programs of an experiment agree on the output of every
+/// input.
+///
+/// The outputs of the runs on an input are grouped with the [`Comparison`] of
+/// the experiment, and the runs whose output differs from the consensus are
+/// reported.
+///
+/// # Examples
+///
+/// ```toml
+/// [agreement]
+/// reference = "baseline"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Agreement {
+    /// The program whose output is the consensus.
+    ///
+    /// By default the consensus is the output of the majority of the runs.
+    #[serde(default)]
+    pub reference: Option<String>,
+}
@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+
+use crate::bailc;
+use crate::ctx;
+
+/// A boolean expression over parameter values that a combination of parameter
+/// values has to satisfy.
+///
+/// # Examples
+///
+/// ```toml
+/// constraints = [
+///     "depth <= side_length.x",
+///     "solver != 'exact' || size < 100",
+/// ]
+/// ```
+///
+/// Parameters are referred to by their name, and subparameters by
+/// `parameter.subparameter`. Values that are numbers are compared as numbers,
+/// other values as text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    /// The expression as written by the user.
+    text: String,
+
+    /// The parsed expression.
+    expression: Expression,
+}
+
+/// A parsed constraint expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    /// A number literal.
+    Number(f64),
+
+    /// A text literal.
+    Text(String),
+
+    /// `true` or `false`.
+    Bool(bool),
+
+    /// The value of a parameter or subparameter.
+    Variable(String),
+
+    /// `!expression`.
+    Not(Box<Expression>),
+
+    /// `-expression`.
+    Negate(Box<Expression>),
+
+    /// Two expressions combined by an operator.
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+/// An operator between two expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    /// `||` or `or`.
+    Or,
+    /// `&&` or `and`.
+    And,
+    /// `==`.
+    Equal,
+    /// `!=`.
+    NotEqual,
+    /// `<`.
+    Less,
+    /// `<=`.
+    LessEqual,
+    /// `>`.
+    Greater,
+    /// `>=`.
+    GreaterEqual,
+    /// `+`.
+    Add,
+    /// `-`.
+    Subtract,
+    /// `*`.
+    Multiply,
+    /// `/`.
+    Divide,
+    /// `%`.
+    Remainder,
+}
+
+/// The value of an evaluated expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// A number.
+    Number(f64),
+
+    /// Text.
+    Text(String),
+
+    /// A boolean.
+    Bool(bool),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{number}"),
+            Value::Text(text) => write!(f, "'{text}'"),
+            Value::Bool(boolean) => write!(f, "{boolean}"),
+        }
+    }
+}
+
+/// A token of a constraint expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A number literal.
+    Number(f64),
+
+    /// A quoted text literal.
+    Text(String),
+
+    /// A parameter name or keyword.
+    Word(String),
+
+    /// An operator or parenthesis.
+    Symbol(&'static str),
+}
+
+/// The symbols of the expression language, longest first.
+const SYMBOLS: [&str; 16] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")",
+];
+
+impl Constraint {
+    /// Parse a constraint expression.
+    pub fn parse(text: &str) -> Result<Self> {
+        let tokens = tokenize(text).with_context(ctx!(
+            "Could not parse the constraint \"{text}\"", ;
+            "Constraints are expressions such as \"depth <= side_length.x\"",
+        ))?;
+
+        let mut parser = Parser { tokens, next: 0 };
+
+        let expression = parser.or().and_then(|expression| {
+            if let Some(token) = parser.tokens.get(parser.next) {
+                Err(anyhow!("Unexpected {token:?} after the expression"))
+            } else {
+                Ok(expression)
+            }
+        });
+
+        Ok(Self {
+            text: text.to_string(),
+            expression: expression.with_context(ctx!(
+                "Could not parse the constraint \"{text}\"", ;
+                "Constraints are expressions such as \"depth <= side_length.x\"",
+            ))?,
+        })
+    }
+
+    /// The parameters and subparameters that this constraint refers to.
+    pub fn variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        self.expression.variables(&mut variables);
+        variables
+    }
+
+    /// Check if the parameter values satisfy this constraint.
+    ///
+    /// Returns `None` if the constraint refers to a parameter that has no
+    /// value, in which case the constraint does not apply.
+    pub fn evaluate(&self, values: &BTreeMap<String, String>) -> Result<Option<bool>> {
+        if !self
+            .variables()
+            .iter()
+            .all(|name| values.contains_key(name))
+        {
+            return Ok(None);
+        }
+
+        match self.expression.evaluate(values).with_context(ctx!(
+            "Could not evaluate the constraint \"{}\"", self.text;
+            "For the parameter values {values:?}",
+        ))? {
+            Value::Bool(satisfied) => Ok(Some(satisfied)),
+            other => {
+                bailc!(
+                    "Could not evaluate the constraint \"{}\"", self.text;
+                    "It evaluates to {other} instead of true or false", ;
+                    "Compare the parameter to a value, for example \"x > 5\"",
+                );
+            }
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Expression {
+    /// Collect the variables that this expression refers to.
+    fn variables(&self, variables: &mut BTreeSet<String>) {
+        match self {
+            Expression::Variable(name) => {
+                variables.insert(name.clone());
+            }
+            Expression::Not(inner) | Expression::Negate(inner) => inner.variables(variables),
+            Expression::Binary(left, _, right) => {
+                left.variables(variables);
+                right.variables(variables);
+            }
+            Expression::Number(_) | Expression::Text(_) | Expression::Bool(_) => {}
+        }
+    }
+
+    /// Evaluate the expression for some parameter values.
+    fn evaluate(&self, values: &BTreeMap<String, String>) -> Result<Value> {
+        Ok(match self {
+            Expression::Number(number) => Value::Number(*number),
+            Expression::Text(text) => Value::Text(text.clone()),
+            Expression::Bool(boolean) => Value::Bool(*boolean),
+            Expression::Variable(name) => {
+                let value = values
+                    .get(name)
+                    .ok_or_else(|| anyhow!("The parameter {name} has no value"))?;
+
+                value
+                    .parse()
+                    .map(Value::Number)
+                    .unwrap_or(Value::Text(value.clone()))
+            }
+            Expression::Not(inner) => match inner.evaluate(values)? {
+                Value::Bool(boolean) => Value::Bool(!boolean),
+                other => return Err(anyhow!("Cannot negate {other}, it is not true or false")),
+            },
+            Expression::Negate(inner) => match inner.evaluate(values)? {
+                Value::Number(number) => Value::Number(-number),
+                other => return Err(anyhow!("Cannot make {other} negative, it is not a number")),
+            },
+            Expression::Binary(left, operator, right) => {
+                operator.apply(left.evaluate(values)?, right.evaluate(values)?)?
+            }
+        })
+    }
+}
+
+impl Operator {
+    /// Apply the operator to two values.
+    fn apply(self, left: Value, right: Value) -> Result<Value> {
+        use Operator::*;
+
+        Ok(match (self, left, right) {
+            (Or, Value::Bool(l), Value::Bool(r)) => Value::Bool(l || r),
+            (And, Value::Bool(l), Value::Bool(r)) => Value::Bool(l && r),
+
+            (Equal, l, r) => Value::Bool(l == r),
+            (NotEqual, l, r) => Value::Bool(l != r),
+
+            (Less | LessEqual | Greater | GreaterEqual, Value::Number(l), Value::Number(r)) => {
+                Value::Bool(self.compare(l.partial_cmp(&r)))
+            }
+            (Less | LessEqual | Greater | GreaterEqual, Value::Text(l), Value::Text(r)) => {
+                Value::Bool(self.compare(Some(l.cmp(&r))))
+            }
+
+            (Add, Value::Number(l), Value::Number(r)) => Value::Number(l + r),
+            (Subtract, Value::Number(l), Value::Number(r)) => Value::Number(l - r),
+            (Multiply, Value::Number(l), Value::Number(r)) => Value::Number(l * r),
+            (Divide, Value::Number(l), Value::Number(r)) => Value::Number(l / r),
+            (Remainder, Value::Number(l), Value::Number(r)) => Value::Number(l % r),
+
+            (operator, l, r) => {
+                return Err(anyhow!("Cannot apply {operator:?} to {l} and {r}"));
+            }
+        })
+    }
+
+    /// Whether an ordering satisfies this comparison.
+    fn compare(self, ordering: Option<std::cmp::Ordering>) -> bool {
+        use std::cmp::Ordering::*;
+
+        matches!(
+            (self, ordering),
+            (Operator::Less, Some(Less))
+                | (Operator::LessEqual, Some(Less | Equal))
+                | (Operator::Greater, Some(Greater))
+                | (Operator::GreaterEqual, Some(Greater | Equal))
+        )
+    }
+}
+
+/// Split a constraint expression into tokens.
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(next) = rest.chars().next() {
+        if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if next == '\'' || next == '"' {
+            let end = rest[1..]
+                .find(next)
+                .ok_or_else(|| anyhow!("The text {rest} is missing its closing quote"))?;
+
+            tokens.push(Token::Text(rest[1..=end].to_string()));
+            rest = &rest[end + 2..];
+        } else if next.is_ascii_digit() || next == '.' {
+            let bytes = rest.as_bytes();
+            let mut end = 0;
+
+            // Numbers such as `1.5e-3` have a sign in their exponent.
+            while end < bytes.len()
+                && (bytes[end].is_ascii_alphanumeric()
+                    || bytes[end] == b'.'
+                    || (matches!(bytes[end], b'+' | b'-') && matches!(bytes[end - 1], b'e' | b'E')))
+            {
+                end += 1;
+            }
+
+            tokens.push(Token::Number(
+                rest[..end]
+                    .parse()
+                    .map_err(|_| anyhow!("{} is not a number", &rest[..end]))?,
+            ));
+            rest = &rest[end..];
+        } else if next.is_alphabetic() || next == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+
+            tokens.push(Token::Word(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            return Err(anyhow!("Unexpected character '{next}'"));
+        }
+
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser for constraint expressions.
+#[derive(Debug)]
+struct Parser {
+    /// The tokens of the expression.
+    tokens: Vec<Token>,
+
+    /// The index of the next token.
+    next: usize,
+}
+
+impl Parser {
+    /// Consume the next token if it is one of the `symbols` or `words`.
+    fn accept(&mut self, symbols: &[&str], words: &[&str]) -> Option<String> {
+        let accepted = match self.tokens.get(self.next)? {
+            Token::Symbol(symbol) if symbols.contains(symbol) => symbol.to_string(),
+            Token::Word(word) if words.contains(&word.as_str()) => word.clone(),
+            _ => return None,
+        };
+
+        self.next += 1;
+
+        Some(accepted)
+    }
+
+    /// Parse `a || b`.
+    fn or(&mut self) -> Result<Expression> {
+        let mut left = self.and()?;
+
+        while self.accept(&["||"], &["or"]).is_some() {
+            left = Expression::Binary(Box::new(left), Operator::Or, Box::new(self.and()?));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse `a && b`.
+    fn and(&mut self) -> Result<Expression> {
+        let mut left = self.not()?;
+
+        while self.accept(&["&&"], &["and"]).is_some() {
+            left = Expression::Binary(Box::new(left), Operator::And, Box::new(self.not()?));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse `!a`.
+    fn not(&mut self) -> Result<Expression> {
+        if self.accept(&["!"], &["not"]).is_some() {
+            Ok(Expression::Not(Box::new(self.not()?)))
+        } else {
+            self.comparison()
+        }
+    }
+
+    /// Parse `a < b`.
+    fn comparison(&mut self) -> Result<Expression> {
+        let left = self.sum()?;
+
+        let operator = match self
+            .accept(&["==", "!=", "<=", ">=", "<", ">"], &[])
+            .as_deref()
+        {
+            Some("==") => Operator::Equal,
+            Some("!=") => Operator::NotEqual,
+            Some("<=") => Operator::LessEqual,
+            Some(">=") => Operator::GreaterEqual,
+            Some("<") => Operator::Less,
+            Some(">") => Operator::Greater,
+            _ => return Ok(left),
+        };
+
+        Ok(Expression::Binary(
+            Box::new(left),
+            operator,
+            Box::new(self.sum()?),
+        ))
+    }
+
+    /// Parse `a + b`.
+    fn sum(&mut self) -> Result<Expression> {
+        let mut left = self.product()?;
+
+        while let Some(symbol) = self.accept(&["+", "-"], &[]) {
+            let operator = if symbol == "+" {
+                Operator::Add
+            } else {
+                Operator::Subtract
+            };
+
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.product()?));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse `a * b`.
+    fn product(&mut self) -> Result<Expression> {
+        let mut left = self.unary()?;
+
+        while let Some(symbol) = self.accept(&["*", "/", "%"], &[]) {
+            let operator = match symbol.as_str() {
+                "*" => Operator::Multiply,
+                "/" => Operator::Divide,
+                _ => Operator::Remainder,
+            };
+
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.unary()?));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse `-a`.
+    fn unary(&mut self) -> Result<Expression> {
+        if self.accept(&["-"], &[]).is_some() {
+            Ok(Expression::Negate(Box::new(self.unary()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    /// Parse a literal, a parameter or a parenthesized expression.
+    fn atom(&mut self) -> Result<Expression> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or_else(|| anyhow!("The expression ends too early"))?;
+
+        self.next += 1;
+
+        Ok(match token {
+            Token::Number(number) => Expression::Number(number),
+            Token::Text(text) => Expression::Text(text),
+            Token::Word(word) if word == "true" => Expression::Bool(true),
+            Token::Word(word) if word == "false" => Expression::Bool(false),
+            Token::Word(word) if ["and", "or", "not"].contains(&word.as_str()) => {
+                return Err(anyhow!("Expected a value, found {word}"));
+            }
+            Token::Word(word) => Expression::Variable(word),
+            Token::Symbol("(") => {
+                let inner = self.or()?;
+
+                if self.accept(&[")"], &[]).is_none() {
+                    return Err(anyhow!("A parenthesis is not closed"));
+                }
+
+                inner
+            }
+            Token::Symbol(symbol) => return Err(anyhow!("Expected a value, found {symbol}")),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/constraints.rs"]
+mod tests;
@@ -2,9 +2,14 @@ use core::fmt;
 use std::time::Duration;
 
 use serde::de::Visitor;
+use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serializer;
 
+use super::Parametrized;
+use crate::constants::PARAMETER_ESCAPE;
+use crate::constants::SUB_PARAMETER_ESCAPE;
+
 /// Deserializing duration from a human-readable string.
 pub fn deserialize_human_time_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
 where
@@ -37,3 +42,55 @@ where
 pub fn serialize_duration<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
     S::serialize_str(ser, &humantime::format_duration(*duration).to_string())
 }
+
+/// Deserializing an optional duration from a human-readable string.
+pub fn deserialize_optional_human_time_duration<'de, D>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_human_time_duration(deserializer).map(Some)
+}
+
+/// Serialize an optional duration into a human-readable format
+pub fn serialize_optional_duration<S: Serializer>(
+    duration: &Option<Duration>,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_duration(duration, ser),
+        None => S::serialize_none(ser),
+    }
+}
+
+/// Deserializing a duration from a human-readable string, or a parameter.
+pub fn deserialize_parametrized_duration<'de, D>(
+    deserializer: D,
+) -> Result<Parametrized<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+
+    if text.starts_with(PARAMETER_ESCAPE) || text.starts_with(SUB_PARAMETER_ESCAPE) {
+        Ok(Parametrized::Parameter(text))
+    } else {
+        humantime::parse_duration(&text)
+            .map(Parametrized::Value)
+            .map_err(|parse_err| {
+                serde::de::Error::custom(format!("This is not a valid duration: {parse_err}"))
+            })
+    }
+}
+
+/// Serialize a duration into a human-readable format, or a parameter.
+pub fn serialize_parametrized_duration<S: Serializer>(
+    duration: &Parametrized<Duration>,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Parametrized::Value(duration) => serialize_duration(duration, ser),
+        Parametrized::Parameter(reference) => S::serialize_str(ser, reference),
+    }
+}
@@ -30,6 +30,28 @@ pub fn canon_path(path: &Path, fs: &impl FileOperations) -> Result<PathBuf> {
         .with_context(ctx!("",;"",))
 }
 
+/// Makes the program of a `command` absolute if it is a file, so that the
+/// command can be run from any directory. Other programs are looked up in the
+/// `PATH`.
+pub fn canon_command(command: &[String], fs: &impl FileOperations) -> Result<Vec<String>> {
+    let Some((program, arguments)) = command.split_first() else {
+        return Ok(Vec::new());
+    };
+
+    let program = if Path::new(program).is_file() {
+        canon_path(Path::new(program), fs)?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        program.clone()
+    };
+
+    Ok([program]
+        .into_iter()
+        .chain(arguments.iter().cloned())
+        .collect())
+}
+
 /// Takes the set of all inputs and expands the globbed arguments.
 ///
 /// # Examples
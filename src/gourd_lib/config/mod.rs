@@ -1,12 +1,14 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::bailc;
 use crate::constants::AFTERSCRIPT_DEFAULT;
 use crate::constants::CMD_STYLE;
 use crate::constants::EMPTY_ARGS;
@@ -14,6 +16,7 @@ use crate::constants::INTERNAL_PREFIX;
 use crate::constants::INTERNAL_SCHEMA_INPUTS;
 use crate::constants::LABEL_OVERLAP_DEFAULT;
 use crate::constants::RERUN_LABEL_BY_DEFAULT;
+use crate::constants::STDIN_DEFAULT;
 use crate::constants::WRAPPER_DEFAULT;
 use crate::error::ctx;
 use crate::file_system::FileOperations;
@@ -37,13 +40,30 @@ pub mod fetching;
 /// Slurm configuration.
 pub mod slurm;
 
+/// Searching the parameter space.
+pub mod search;
+
+/// Expressions that exclude combinations of parameter values.
+pub mod constraints;
+
+/// Tuning the parameters with `gourd tune`.
+pub mod tune;
+
+/// Comparing the output of runs with the expected output.
+pub mod compare;
+
 pub use regex::Regex;
 
+use crate::config::compare::Agreement;
+use crate::config::compare::Comparison;
+use crate::config::search::SearchConfig;
 use crate::config::slurm::ResourceLimits;
 use crate::config::slurm::SlurmConfig;
+use crate::config::slurm::UserResourceLimits;
+use crate::config::tune::TuneConfig;
 
 /// A pair of a path to a binary and cli arguments.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Hash, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct UserProgram {
     /// A path to the executable.
@@ -68,11 +88,58 @@ pub struct UserProgram {
 
     /// Resource limits to optionally overwrite default resource limits.
     #[serde(default)]
-    pub resource_limits: Option<ResourceLimits>,
+    pub resource_limits: Option<UserResourceLimits>,
 
     /// The programs to postprocess this one.
     #[serde(default)]
     pub next: Vec<String>,
+
+    /// How many times to run this program on every input, overrides the
+    /// global `repetitions`.
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
+    /// Parameters that only this program can use, these replace global
+    /// parameters with the same name.
+    #[serde(default, rename = "parameter")]
+    pub parameters: Option<BTreeMap<String, Parameter>>,
+
+    /// Groups of parameters used by this program whose values are zipped
+    /// instead of crossed.
+    #[serde(default)]
+    pub zip: Vec<Vec<String>>,
+
+    /// If not empty, this program only runs on the inputs that match one of
+    /// these selectors: the name of an input, a glob pattern of names, or
+    /// `group|<group name>`.
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// This program does not run on the inputs that match one of these
+    /// selectors.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Whether the input file is passed on the standard input of this
+    /// program, turn this off when the program gets the input file as
+    /// `{input}` in its arguments instead.
+    #[serde(default = "STDIN_DEFAULT")]
+    pub stdin: bool,
+
+    /// A command that prepares the working directory of every run before the
+    /// program starts, for example by copying fixtures into it.
+    #[serde(default)]
+    pub setup: Vec<String>,
+
+    /// A command that cleans up the working directory of every run after the
+    /// program has finished.
+    #[serde(default)]
+    pub teardown: Vec<String>,
+
+    /// Environment variables set for the runs of this program, these replace
+    /// global variables with the same name.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 /// An algorithm fetched from a git repository.
@@ -101,6 +168,29 @@ pub struct FetchedResource<const PERMISSIONS: u32> {
     pub store: PathBuf,
 }
 
+/// A command that generates the input files of a family of random inputs.
+///
+/// # Examples
+///
+/// ```toml
+/// [input.random_graphs]
+/// generator = { command = ["./gen", "--nodes", "100", "--seed", "{seed}"], count = 50 }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Hash, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Generator {
+    /// The command and its arguments, `{seed}` is replaced by the seed of the
+    /// generated input. The command writes the input to its `stdout`.
+    pub command: Vec<String>,
+
+    /// How many inputs to generate.
+    pub count: usize,
+
+    /// The seed of the first generated input, the next ones count up from it.
+    #[serde(default)]
+    pub seed_start: u64,
+}
+
 /// A pair of a path to an input and additional cli arguments.
 ///
 /// # Examples
@@ -139,6 +229,34 @@ pub struct UserInput {
     /// By default these will be empty.
     #[serde(default = "EMPTY_ARGS")]
     pub arguments: Vec<String>,
+
+    /// How many times to run every program on this input, overrides the
+    /// `repetitions` of the programs.
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
+    /// Generate the input file of every run with a command, instead of
+    /// reading it from a `file`.
+    #[serde(default)]
+    pub generator: Option<Generator>,
+
+    /// The output that the programs should give on this input, a path or a
+    /// glob whose files are matched with the input files by their stem.
+    #[serde(default)]
+    pub expected: Option<String>,
+
+    /// Named files that are passed to the programs as `{file:<name>}` in
+    /// their arguments.
+    ///
+    /// Every file can be a glob, the input is then instantiated once for
+    /// every file stem that all the globs match.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+
+    /// Environment variables set for the runs on this input, these replace
+    /// the variables of the programs with the same name.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 /// ### TOML struct that can be used to provide inputs.
@@ -183,7 +301,11 @@ pub struct InputSchema {
 /// `test 1 b`
 /// `test 2 a`
 /// `test 2 b`
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Hash, Eq)]
+///
+/// Instead of listing the `values`, they can be generated by exactly one of
+/// `range`, `linspace` or `logspace`. A random search can also sample them
+/// from a `uniform` or `loguniform` distribution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Parameter {
     /// Sub-parameters of this parameter.
@@ -195,6 +317,95 @@ pub struct Parameter {
     ///
     /// To be used exclusively without sub (parameter).
     pub values: Option<Vec<String>>,
+
+    /// Generate the values from `start` up to `stop` in steps of `step`.
+    pub range: Option<RangeSpec>,
+
+    /// Generate `count` evenly spaced values from `start` to `stop`.
+    pub linspace: Option<SpacingSpec>,
+
+    /// Generate `count` logarithmically spaced values from `start` to `stop`.
+    pub logspace: Option<SpacingSpec>,
+
+    /// Sample the values uniformly between `low` and `high`.
+    pub uniform: Option<DistributionSpec>,
+
+    /// Sample the values between `low` and `high` such that every order of
+    /// magnitude is as likely.
+    pub loguniform: Option<DistributionSpec>,
+}
+
+/// The values `start`, `start + step`, `start + 2 * step`, ... up to but
+/// excluding `stop`.
+///
+/// # Examples
+///
+/// ```toml
+/// [parameters.threads]
+/// range = { start = 1, stop = 9, step = 2 }
+/// ```
+///
+/// Has the values `1`, `3`, `5` and `7`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeSpec {
+    /// The first value.
+    pub start: f64,
+
+    /// The bound that the values do not reach.
+    pub stop: f64,
+
+    /// The distance between two values, negative to count down.
+    pub step: f64,
+}
+
+/// `count` values from `start` to `stop`, including both.
+///
+/// As a `linspace` the values are evenly spaced, as a `logspace` every value
+/// is the previous one multiplied by the same factor.
+///
+/// # Examples
+///
+/// ```toml
+/// [parameters.learning_rate]
+/// logspace = { start = 0.0001, stop = 1, count = 5 }
+/// ```
+///
+/// Has the values `0.0001`, `0.001`, `0.01`, `0.1` and `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpacingSpec {
+    /// The first value.
+    pub start: f64,
+
+    /// The last value.
+    pub stop: f64,
+
+    /// The amount of values.
+    pub count: usize,
+}
+
+/// The bounds of a continuous distribution that a random search samples a
+/// parameter from.
+///
+/// # Examples
+///
+/// ```toml
+/// [search]
+/// strategy = "random"
+/// samples = 50
+///
+/// [parameters.learning_rate]
+/// loguniform = { low = 0.00001, high = 0.1 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DistributionSpec {
+    /// The lowest value.
+    pub low: f64,
+
+    /// The highest value.
+    pub high: f64,
 }
 
 /// A subparameter.
@@ -229,6 +440,75 @@ pub struct SubParameter {
     pub values: Vec<String>,
 }
 
+/// A value that can also be taken from a parameter, by giving
+/// `"param|x"` or `"subparam|x.a"` instead of the value.
+///
+/// # Examples
+///
+/// ```toml
+/// [parameters.threads]
+/// values = ["1", "2", "4"]
+///
+/// [programs.test_program.resource_limits]
+/// time_limit = "1min"
+/// cpus = "param|threads"
+/// mem_per_cpu = 512
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Hash, Eq)]
+#[serde(untagged, expecting = "a value, or a parameter such as \"param|x\"")]
+pub enum Parametrized<T> {
+    /// The value itself.
+    Value(T),
+
+    /// The parameter to take the values from.
+    Parameter(String),
+}
+
+impl<T: Clone> Parametrized<T> {
+    /// Replace a parameter with its value in `values`, which maps the names of
+    /// parameters (and `param.sub` for subparameters) to their values.
+    pub fn substitute(
+        &self,
+        values: &BTreeMap<String, String>,
+        parse: impl Fn(&str) -> Result<T>,
+    ) -> Result<Self> {
+        if let Parametrized::Parameter(reference) = self {
+            if let Some(value) =
+                parameters::parameter_key(reference)?.and_then(|name| values.get(&name))
+            {
+                return Ok(Parametrized::Value(parse(value).with_context(ctx!(
+                    "Could not use the value {value:?} of {reference}", ;
+                    "Make sure that every value of the parameter is valid here",
+                ))?));
+            }
+        }
+
+        Ok(self.clone())
+    }
+
+    /// The parameter reference, if this is one.
+    pub fn reference(&self) -> Option<&str> {
+        match self {
+            Parametrized::Value(_) => None,
+            Parametrized::Parameter(reference) => Some(reference),
+        }
+    }
+
+    /// The value, if it is not (or no longer) a parameter.
+    pub fn value(&self, what: &str) -> Result<T> {
+        match self {
+            Parametrized::Value(value) => Ok(value.clone()),
+            Parametrized::Parameter(reference) => {
+                bailc!(
+                    "Could not find the values of {what}", ;
+                    "{what} is given as {reference:?}, which is not a parameter with values", ;
+                    "Declare the parameter in [parameters] and refer to it as \"param|name\"",
+                );
+            }
+        }
+    }
+}
+
 /// A label that can be assigned to a job based on the afterscript output.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Hash, Eq)]
 #[serde(deny_unknown_fields)]
@@ -316,6 +596,67 @@ pub struct Config {
     /// afterscript output.
     #[serde(default = "LABEL_OVERLAP_DEFAULT")]
     pub warn_on_label_overlap: bool,
+
+    /// If set, the wrapper samples the resource usage of every run at this
+    /// interval.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "duration::deserialize_optional_human_time_duration",
+        serialize_with = "duration::serialize_optional_duration"
+    )]
+    pub sampling_interval: Option<Duration>,
+
+    /// If set to true, the wrapper collects hardware and software performance
+    /// counters of every run.
+    #[serde(default)]
+    pub performance_counters: bool,
+
+    /// How many times to run every program on every input.
+    ///
+    /// By default every pairing runs once.
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
+    /// The seed of the first repetition, every next repetition gets the next
+    /// seed. If set, `{seed}` in the arguments is replaced by the seed of the
+    /// run.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// How to search the parameter space, by default the full grid.
+    pub search: Option<SearchConfig>,
+
+    /// Boolean expressions over the parameters, combinations of parameter
+    /// values for which any of them is false are not run.
+    #[serde(default)]
+    pub constraints: Vec<String>,
+
+    /// Groups of parameters whose values are zipped instead of crossed: the
+    /// first values of all of them are used together, then the second values,
+    /// and so on.
+    #[serde(default)]
+    pub zip: Vec<Vec<String>>,
+
+    /// How `gourd tune` tunes the parameters, required by `gourd tune`.
+    pub tune: Option<TuneConfig>,
+
+    /// How the output of runs is compared with the expected output of their
+    /// inputs.
+    #[serde(default)]
+    pub compare: Comparison,
+
+    /// Whether the programs are checked to agree on the output of every input.
+    pub agreement: Option<Agreement>,
+
+    /// Environment variables set for every run.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Whether runs start from an empty environment instead of inheriting the
+    /// environment of the wrapper.
+    #[serde(default)]
+    pub clear_env: bool,
 }
 
 // An implementation that provides a default value of `Config`,
@@ -335,6 +676,18 @@ impl Default for Config {
             resource_limits: None,
             labels: Some(BTreeMap::new()),
             warn_on_label_overlap: true,
+            sampling_interval: None,
+            performance_counters: false,
+            repetitions: None,
+            seed: None,
+            search: None,
+            constraints: vec![],
+            zip: vec![],
+            tune: None,
+            compare: Comparison::default(),
+            agreement: None,
+            env: BTreeMap::new(),
+            clear_env: false,
         }
     }
 }
@@ -1,36 +1,100 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::path::PathBuf;
 
 use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
+use log::info;
 use log::trace;
+use log::warn;
 
+use super::constraints::Constraint;
+use super::search::SearchRng;
+use super::DistributionSpec;
 use super::Parameter;
+use super::RangeSpec;
+use super::SpacingSpec;
 use super::UserInput;
+use super::UserProgram;
 use crate::bailc;
 use crate::constants::INTERNAL_PARAMETER;
 use crate::constants::INTERNAL_PREFIX;
 use crate::constants::PARAMETER_ESCAPE;
+use crate::constants::SEARCH_SAMPLE_ATTEMPTS;
 use crate::constants::SUB_PARAMETER_ESCAPE;
 use crate::ctx;
 
+/// The name, arguments and parameter values of an input or program while its
+/// parameters are expanded.
+type Combination = (String, Vec<String>, BTreeMap<String, String>);
+
+/// The arguments and parameter values of a sampled combination.
+type Sample = (Vec<String>, BTreeMap<String, String>);
+
+/// Where a parameter is used: its position in the arguments, if it is used in
+/// the arguments, and the subparameter, if a subparameter is used.
+type ParameterUse = (Option<usize>, Option<String>);
+
+/// The uses of every parameter used by an input or program.
+type ParameterUses = BTreeMap<String, Vec<ParameterUse>>;
+
 /// Check if the parameters are well-formed.
 pub fn validate_parameters(parameters: &BTreeMap<String, Parameter>) -> Result<()> {
     for (p_name, p) in parameters {
-        if p.sub.is_some() && p.values.is_some() {
+        let sources = [
+            p.sub.is_some(),
+            p.values.is_some(),
+            p.range.is_some(),
+            p.linspace.is_some(),
+            p.logspace.is_some(),
+            p.uniform.is_some(),
+            p.loguniform.is_some(),
+        ]
+        .into_iter()
+        .filter(|specified| *specified)
+        .count();
+
+        if sources > 1 {
             bailc!(
               "Parameter specified incorrectly", ;
-              "Parameter can have either values or subparameters, not both", ;
+              "Parameter can have only one of values, subparameters, range, linspace, logspace, \
+              uniform or loguniform", ;
               "Parameter name {}", p_name
             );
-        } else if p.sub.is_none() && p.values.is_none() {
+        } else if sources == 0 {
             bailc!(
               "Parameter specified incorrectly", ;
-              "Parameter must have either values or subparameters, currently has none", ;
+              "Parameter must have values, subparameters, range, linspace, logspace, uniform \
+              or loguniform, currently has none", ;
               "Parameter name {}", p_name
             );
         }
+
+        for (distribution, spec) in [("uniform", p.uniform), ("loguniform", p.loguniform)] {
+            if let Some(spec) = spec {
+                check_finite(p_name, distribution, &[spec.low, spec.high])?;
+
+                let DistributionSpec { low, high } = spec;
+
+                if low > high {
+                    bailc!(
+                      "Invalid {distribution} for parameter {p_name}", ;
+                      "The low bound {low} is higher than the high bound {high}", ;
+                      "",
+                    );
+                }
+            }
+        }
+
+        if p.loguniform.is_some_and(|spec| spec.low <= 0.0) {
+            bailc!(
+              "Invalid loguniform for parameter {p_name}", ;
+              "The bounds of a loguniform distribution have to be greater than 0", ;
+              "Use a uniform distribution to also include 0 or negative values",
+            );
+        }
     }
 
     Ok(())
@@ -73,67 +137,691 @@ pub fn validate_parameters(parameters: &BTreeMap<String, Parameter>) -> Result<(
 /// [inputs.test_input_x_1_y_1]
 /// arguments = [ "const", "2", "b", "60" ]
 /// ```
+///
+/// Parameters can also be used in the `file` and `glob` paths, as in
+/// `file = "instances/n{y}.txt"`.
+///
+/// The parameters in a `zipped` group are not crossed with each other, instead
+/// the first values of all of them are used together, then the second values,
+/// and so on.
+///
+/// Combinations of values that do not satisfy the `constraints` are left out.
 pub fn expand_parameters(
     inputs: BTreeMap<String, UserInput>,
     parameters: &BTreeMap<String, Parameter>,
+    zipped: &[Vec<String>],
+    constraints: &[Constraint],
 ) -> Result<BTreeMap<String, UserInput>> {
     let mut result: BTreeMap<String, UserInput> = BTreeMap::new();
 
+    let parameters = &generate_parameter_values(parameters)?;
+
     check_sub_parameter_size_is_equal(parameters)?;
+    validate_zipped(zipped, parameters)?;
 
     for (input_name, input) in inputs.iter() {
-        let mut map = BTreeMap::new();
-        let mut expandable_parameters = BTreeSet::new();
-
         // Find uses of parameters in inputs.
-        get_expandable_parameters(input, &mut map, &mut expandable_parameters)?;
+        let uses = get_input_parameters(input, parameters)?;
 
-        trace!("Expandable parameters for {input_name} are {expandable_parameters:#?}");
+        trace!("Expandable parameters for {input_name} are {uses:#?}");
 
         // If none of parameters was used in this input then there's no need to do
         // anything.
-        if expandable_parameters.is_empty() {
+        if uses.is_empty() {
             result.insert(input_name.clone(), input.clone());
             continue;
         }
 
-        let mut set: BTreeSet<(String, Vec<String>)> = BTreeSet::new();
-        set.insert((input_name.clone(), input.arguments.clone()));
+        for (name, arguments, values) in expand_combinations(
+            (input_name, &format!("input {input_name}")),
+            &input.arguments,
+            &uses,
+            (parameters, zipped),
+            constraints,
+        )? {
+            result.insert(
+                format!("{name}{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                substitute_input(input, arguments, &values),
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+/// Takes a program and all Parameters and expands the parametered arguments and
+/// resource limits of the program, the same way that [`expand_parameters`]
+/// expands inputs.
+///
+/// Besides the global `parameters` and `zipped` groups, the program can use
+/// its own parameters and zip them in its own groups. Its own parameters
+/// replace global parameters with the same name.
+///
+/// With `sampling`, a random search takes that many samples of the values
+/// like [`sample_parameters`] does for inputs, instead of expanding the grid.
+///
+/// A program that uses no parameters expands into itself.
+pub fn expand_program_parameters(
+    program_name: &str,
+    program: &UserProgram,
+    (parameters, zipped): (&BTreeMap<String, Parameter>, &[Vec<String>]),
+    constraints: &[Constraint],
+    sampling: Option<(usize, &mut SearchRng)>,
+) -> Result<Vec<(String, UserProgram)>> {
+    let mut visible = parameters.clone();
+    visible.extend(program.parameters.clone().unwrap_or_default());
+
+    let parameters = &generate_parameter_values(&visible)?;
+    let zipped = &[zipped, &program.zip].concat();
+
+    check_sub_parameter_size_is_equal(parameters)?;
+    validate_zipped(zipped, parameters)?;
+
+    let uses = get_program_parameters(program, parameters)?;
+
+    trace!("Expandable parameters for program {program_name} are {uses:#?}");
+
+    if uses.is_empty() {
+        return Ok(vec![(program_name.to_string(), program.clone())]);
+    }
+
+    let described = format!("program {program_name}");
 
-        for parameter_name in expandable_parameters {
-            if let Some(param) = parameters.get(&parameter_name) {
-                let indexes = &map[&parameter_name];
+    let combinations = match sampling {
+        Some(sampling) => sample_combinations(
+            &described,
+            &program.arguments,
+            &uses,
+            (parameters, zipped),
+            constraints,
+            sampling,
+        )?
+        .into_iter()
+        .enumerate()
+        .map(|(i, (arguments, values))| (format!("{program_name}_sample_{i}"), arguments, values))
+        .collect(),
+        None => expand_combinations(
+            (program_name, &described),
+            &program.arguments,
+            &uses,
+            (parameters, zipped),
+            constraints,
+        )?,
+    };
 
-                if indexes[0].1.is_some() {
-                    set = expand_sub_parameter(&parameter_name, param, set, indexes)?;
-                } else {
-                    set = expand_parameter(&parameter_name, param, set, indexes)?;
+    let mut expanded = Vec::new();
+
+    for (name, arguments, values) in combinations {
+        let mut program_copy = program.clone();
+        program_copy.arguments = arguments;
+        program_copy.env = program
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), substitute_placeholders(value, &values)))
+            .collect();
+        program_copy.resource_limits = program
+            .resource_limits
+            .as_ref()
+            .map(|limits| limits.substitute(&values))
+            .transpose()
+            .with_context(ctx!(
+                "In the resource limits of program {program_name}", ;
+                "",
+            ))?;
+
+        expanded.push((
+            format!("{name}{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+            program_copy,
+        ));
+    }
+
+    Ok(expanded)
+}
+
+/// Expands the parameters in `uses` into every combination of their values
+/// that satisfies the `constraints`, replacing them in the `arguments`
+/// (Helper function).
+///
+/// The combinations are named after `base_name`, and the user is told about
+/// them as `described`.
+fn expand_combinations(
+    (base_name, described): (&str, &str),
+    arguments: &[String],
+    uses: &ParameterUses,
+    (parameters, zipped): (&BTreeMap<String, Parameter>, &[Vec<String>]),
+    constraints: &[Constraint],
+) -> Result<Vec<Combination>> {
+    let mut set: BTreeSet<Combination> = BTreeSet::new();
+    set.insert((base_name.to_string(), arguments.to_vec(), BTreeMap::new()));
+
+    for (parameter_name, indexes) in uses {
+        if let Some(group) = zipped_group(zipped, uses, parameter_name) {
+            // The group is expanded once, when its first used parameter is reached.
+            if group.first() == Some(parameter_name) {
+                set = expand_zipped(&group, uses, parameters, set)?;
+            }
+        } else if let Some(param) = parameters.get(parameter_name) {
+            if param.uniform.is_some() || param.loguniform.is_some() {
+                bailc!(
+                    "Cannot expand parameter {parameter_name} into a grid", ;
+                    "Its values are sampled from a distribution, \
+                    which only a random search can do", ;
+                    "Add a [search] section with strategy = \"random\"",
+                );
+            }
+
+            if indexes[0].1.is_some() {
+                set = expand_sub_parameter(parameter_name, param, set, indexes)?;
+            } else {
+                set = expand_parameter(parameter_name, param, set, indexes)?;
+            }
+        } else {
+            bailc!(
+                "Invalid parameter specified", ;
+                "Did not find values for parameter specified in {}", described;
+                "For parameter \"{parameter_name}\"",
+            );
+        }
+    }
+
+    let combinations = set.len();
+    let mut kept = Vec::new();
+
+    for combination in set {
+        if satisfies(constraints, &combination.2)? {
+            kept.push(combination);
+        }
+    }
+
+    report_excluded(described, combinations - kept.len(), combinations);
+
+    Ok(kept)
+}
+
+/// Takes the set of all inputs and all Parameters and replaces every input that
+/// uses parameters with `samples` inputs, each with a different random
+/// combination of parameter values.
+///
+/// The new inputs are named `<input>_sample_<n>`, followed by the same suffix
+/// as the inputs of [`expand_parameters`].
+///
+/// Sampled combinations that do not satisfy the `constraints` are left out.
+pub fn sample_parameters(
+    inputs: BTreeMap<String, UserInput>,
+    (parameters, zipped): (&BTreeMap<String, Parameter>, &[Vec<String>]),
+    constraints: &[Constraint],
+    samples: usize,
+    rng: &mut SearchRng,
+) -> Result<BTreeMap<String, UserInput>> {
+    let mut result: BTreeMap<String, UserInput> = BTreeMap::new();
+
+    let parameters = &generate_parameter_values(parameters)?;
+
+    check_sub_parameter_size_is_equal(parameters)?;
+    validate_zipped(zipped, parameters)?;
+
+    for (input_name, input) in inputs.iter() {
+        let uses = get_input_parameters(input, parameters)?;
+
+        if uses.is_empty() {
+            result.insert(input_name.clone(), input.clone());
+            continue;
+        }
+
+        for (i, (arguments, values)) in sample_combinations(
+            &format!("input {input_name}"),
+            &input.arguments,
+            &uses,
+            (parameters, zipped),
+            constraints,
+            (samples, &mut *rng),
+        )?
+        .into_iter()
+        .enumerate()
+        {
+            result.insert(
+                format!("{input_name}_sample_{i}{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                substitute_input(input, arguments, &values),
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+/// Samples up to `samples` different combinations of the values of the
+/// parameters in `uses` that satisfy the `constraints`, replacing them in the
+/// `arguments` (Helper function).
+///
+/// The user is told about the combinations as `described`.
+fn sample_combinations(
+    described: &str,
+    arguments: &[String],
+    uses: &ParameterUses,
+    (parameters, zipped): (&BTreeMap<String, Parameter>, &[Vec<String>]),
+    constraints: &[Constraint],
+    (samples, rng): (usize, &mut SearchRng),
+) -> Result<Vec<Sample>> {
+    let mut sampled = Vec::new();
+    let mut seen = HashSet::new();
+    let mut attempts = 0;
+    let mut excluded = 0;
+
+    for _ in 0..samples.saturating_mul(SEARCH_SAMPLE_ATTEMPTS) {
+        if sampled.len() == samples {
+            break;
+        }
+
+        attempts += 1;
+
+        let mut arguments = arguments.to_vec();
+        let mut values = BTreeMap::new();
+
+        for (parameter_name, indexes) in uses {
+            if let Some(group) = zipped_group(zipped, uses, parameter_name) {
+                // All parameters of the group take the value at the same index.
+                if group.first() == Some(parameter_name) {
+                    let i = rng.below(zipped_values(&group[0], parameters)?.len());
+
+                    for member in &group {
+                        let value = &zipped_values(member, parameters)?[i];
+                        assign_value(member, value, &uses[member], &mut arguments)?;
+                        values.insert(member.clone(), value.clone());
+                    }
                 }
+            } else if let Some(param) = parameters.get(parameter_name) {
+                sample_parameter(
+                    parameter_name,
+                    param,
+                    indexes,
+                    (&mut arguments, &mut values),
+                    rng,
+                )?;
             } else {
                 bailc!(
                     "Invalid parameter specified", ;
-                    "Did not find values for parameter specified in input {}", input_name;
+                    "Did not find values for parameter specified in {}", described;
                     "For parameter \"{parameter_name}\"",
                 );
             }
         }
 
-        for (name, x) in set {
-            let mut input_copy = input.clone();
-            input_copy.arguments.clone_from(&x);
-            result.insert(
-                format!("{name}{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
-                input_copy,
+        if !satisfies(constraints, &values)? {
+            excluded += 1;
+        } else if seen.insert(values.clone()) {
+            sampled.push((arguments, values));
+        }
+    }
+
+    report_excluded(described, excluded, attempts);
+
+    if sampled.len() < samples {
+        warn!(
+            "Only found {} different combinations of parameter values for {described}, \
+            instead of {samples} samples",
+            sampled.len()
+        );
+    }
+
+    Ok(sampled)
+}
+
+/// The names of the parameters that the `input` uses, in its arguments and as
+/// placeholders in its paths.
+pub fn input_parameters(
+    input: &UserInput,
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<BTreeSet<String>> {
+    Ok(get_input_parameters(input, parameters)?
+        .into_keys()
+        .collect())
+}
+
+/// Copies an input with every parameter that it uses replaced by its value in
+/// `values`, which holds `x` and `x.a` for parameter `x` and subparameter
+/// `x.a`.
+pub fn assign_parameters(
+    input: &UserInput,
+    values: &BTreeMap<String, String>,
+) -> Result<UserInput> {
+    let arguments = input
+        .arguments
+        .iter()
+        .map(|argument| match parameter_key(argument)? {
+            Some(key) => values
+                .get(&key)
+                .cloned()
+                .ok_or(anyhow!("There is no value for parameter {key}"))
+                .with_context(ctx!("", ; "",)),
+            None => Ok(argument.clone()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(substitute_input(input, arguments, values))
+}
+
+/// Copies an input with the `arguments` of a combination of parameter values,
+/// and the `values` in its paths (Helper function).
+fn substitute_input(
+    input: &UserInput,
+    arguments: Vec<String>,
+    values: &BTreeMap<String, String>,
+) -> UserInput {
+    let mut input_copy = input.clone();
+
+    input_copy.arguments = arguments;
+    input_copy.file = input
+        .file
+        .as_ref()
+        .map(|file| PathBuf::from(substitute_placeholders(&file.to_string_lossy(), values)));
+    input_copy.glob = input
+        .glob
+        .as_ref()
+        .map(|glob| substitute_placeholders(glob, values));
+    input_copy.files = input
+        .files
+        .iter()
+        .map(|(name, file)| (name.clone(), substitute_placeholders(file, values)))
+        .collect();
+    input_copy.env = input
+        .env
+        .iter()
+        .map(|(name, value)| (name.clone(), substitute_placeholders(value, values)))
+        .collect();
+
+    input_copy
+}
+
+/// Replaces the `{x}` and `{x.a}` placeholders in the `text` with the values of
+/// parameter `x` and subparameter `x.a` (Helper function).
+fn substitute_placeholders(text: &str, values: &BTreeMap<String, String>) -> String {
+    values.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
+}
+
+/// Replaces a parameter in the arguments with a random value, and records the
+/// value (Helper function).
+fn sample_parameter(
+    parameter_name: &String,
+    param: &Parameter,
+    indexes: &Vec<ParameterUse>,
+    (arguments, values): (&mut [String], &mut BTreeMap<String, String>),
+    rng: &mut SearchRng,
+) -> Result<()> {
+    if let Some(subparams) = &param.sub {
+        let size_of_one = subparams.values().next().map_or(0, |sub| sub.values.len());
+
+        if size_of_one == 0 {
+            bailc!("Subparameters required for {parameter_name}", ; "", ; "",);
+        }
+
+        let i = rng.below(size_of_one);
+
+        for (sub_name, sub) in subparams {
+            values.insert(
+                format!("{parameter_name}.{sub_name}"),
+                sub.values[i].clone(),
             );
         }
+
+        for (pos, sub) in indexes {
+            let expanding = sub
+                .as_ref()
+                .ok_or(anyhow!("Invariant failed"))
+                .with_context(ctx!(
+                    "Cannot use a parameter with subparameters as value-based", ;
+                    "'{INTERNAL_PARAMETER}' reqiures a parameter that has the values \
+                    field defined and \"{parameter_name}\" does not",
+                ))?;
+
+            let value = &subparams
+                .get(expanding)
+                .ok_or(anyhow!("Invalid subparameter specified {expanding}"))
+                .with_context(ctx!(
+                    "For parameter {parameter_name}", ;
+                    "Ensure that it exists",
+                ))?
+                .values[i];
+
+            if let Some(pos) = pos {
+                arguments[*pos].clone_from(value);
+            }
+        }
+
+        return Ok(());
     }
 
-    Ok(result)
+    let value = match (&param.values, param.uniform, param.loguniform) {
+        (Some(values), _, _) if !values.is_empty() => values[rng.below(values.len())].clone(),
+        (_, Some(uniform), _) => {
+            format_value(uniform.low + (uniform.high - uniform.low) * rng.next_f64())
+        }
+        (_, _, Some(loguniform)) => format_value(
+            (loguniform.low.ln() + (loguniform.high.ln() - loguniform.low.ln()) * rng.next_f64())
+                .exp(),
+        ),
+        _ => {
+            bailc!(
+                "Parameter \"{parameter_name}\" used", ;
+                "You cannot use a parameter in a '{PARAMETER_ESCAPE}' while no values are specified",;
+                "",
+            );
+        }
+    };
+
+    assign_value(parameter_name, &value, indexes, arguments)?;
+
+    values.insert(parameter_name.clone(), value);
+
+    Ok(())
+}
+
+/// Checks if parameter values satisfy all constraints that apply to them
+/// (Helper function).
+fn satisfies(constraints: &[Constraint], values: &BTreeMap<String, String>) -> Result<bool> {
+    for constraint in constraints {
+        if constraint.evaluate(values)? == Some(false) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Tells the user how many combinations of parameter values the constraints
+/// excluded from an input or program (Helper function).
+fn report_excluded(described: &str, excluded: usize, combinations: usize) {
+    if excluded == combinations && excluded > 0 {
+        warn!("The constraints exclude every combination of parameter values of {described}");
+    } else if excluded > 0 {
+        info!(
+            "The constraints excluded {excluded} of {combinations} combinations of \
+            parameter values of {described}"
+        );
+    }
+}
+
+/// Parses the constraints of the configuration, and checks that they only
+/// refer to existing `parameters`.
+pub fn parse_constraints(
+    constraints: &[String],
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<Vec<Constraint>> {
+    let constraints = constraints
+        .iter()
+        .map(|constraint| Constraint::parse(constraint))
+        .collect::<Result<Vec<_>>>()?;
+
+    validate_constraints(&constraints, parameters)?;
+
+    Ok(constraints)
+}
+
+/// Checks that the constraints only refer to parameters and subparameters
+/// that exist.
+pub fn validate_constraints(
+    constraints: &[Constraint],
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<()> {
+    for constraint in constraints {
+        for variable in constraint.variables() {
+            let exists = match variable.split_once('.') {
+                Some((param_name, sub_name)) => parameters
+                    .get(param_name)
+                    .and_then(|param| param.sub.as_ref())
+                    .is_some_and(|sub| sub.contains_key(sub_name)),
+                None => parameters.contains_key(&variable),
+            };
+
+            if !exists {
+                bailc!(
+                  "Invalid constraint \"{constraint}\"", ;
+                  "There is no parameter or subparameter called {variable}", ;
+                  "Refer to subparameters as parameter.subparameter",
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Turns the `range`, `linspace` and `logspace` of the parameters into their
+/// `values`.
+pub fn generate_parameter_values(
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<BTreeMap<String, Parameter>> {
+    let mut generated = parameters.clone();
+
+    for (name, param) in generated.iter_mut() {
+        let values = if let Some(range) = param.range.take() {
+            generate_range(name, range)?
+        } else if let Some(linspace) = param.linspace.take() {
+            generate_linspace(name, linspace)?
+        } else if let Some(logspace) = param.logspace.take() {
+            generate_logspace(name, logspace)?
+        } else {
+            continue;
+        };
+
+        trace!("Generated the values {values:?} for parameter {name}");
+
+        param.values = Some(values);
+    }
+
+    Ok(generated)
+}
+
+/// Generates the values of a `range` (Helper function).
+fn generate_range(name: &str, range: RangeSpec) -> Result<Vec<String>> {
+    check_finite(name, "range", &[range.start, range.stop, range.step])?;
+
+    if range.step == 0.0 {
+        bailc!(
+          "Invalid range for parameter {name}", ;
+          "The step of a range cannot be 0", ;
+          "Use a positive step to count up, or a negative step to count down",
+        );
+    }
+
+    // The small margin keeps rounding errors from adding `stop` as a value.
+    let count = ((range.stop - range.start) / range.step - 1e-9).ceil();
+
+    if count < 1.0 {
+        let RangeSpec { start, stop, step } = range;
+
+        bailc!(
+          "Invalid range for parameter {name}", ;
+          "A range from {start} to {stop} in steps of {step} has no values", ;
+          "Make sure that the step goes from start towards stop",
+        );
+    }
+
+    Ok((0..count as usize)
+        .map(|i| format_value(range.start + i as f64 * range.step))
+        .collect())
+}
+
+/// Generates the values of a `linspace` (Helper function).
+fn generate_linspace(name: &str, linspace: SpacingSpec) -> Result<Vec<String>> {
+    check_finite(name, "linspace", &[linspace.start, linspace.stop])?;
+    check_count(name, "linspace", linspace.count)?;
+
+    Ok(spaced(linspace, |start, stop, fraction| {
+        start + (stop - start) * fraction
+    }))
+}
+
+/// Generates the values of a `logspace` (Helper function).
+fn generate_logspace(name: &str, logspace: SpacingSpec) -> Result<Vec<String>> {
+    check_finite(name, "logspace", &[logspace.start, logspace.stop])?;
+    check_count(name, "logspace", logspace.count)?;
+
+    if logspace.start <= 0.0 || logspace.stop <= 0.0 {
+        bailc!(
+          "Invalid logspace for parameter {name}", ;
+          "The start and stop of a logspace have to be greater than 0", ;
+          "Use a linspace to also include 0 or negative values",
+        );
+    }
+
+    Ok(spaced(logspace, |start, stop, fraction| {
+        (start.ln() + (stop.ln() - start.ln()) * fraction).exp()
+    }))
+}
+
+/// Generates `count` values from `start` to `stop` using `interpolate`, which
+/// is given how far along the value is from start to stop (Helper function).
+fn spaced(spec: SpacingSpec, interpolate: impl Fn(f64, f64, f64) -> f64) -> Vec<String> {
+    (0..spec.count)
+        .map(|i| match i {
+            0 => spec.start,
+            // The last value is exactly `stop`, without rounding errors.
+            i if i == spec.count - 1 => spec.stop,
+            i => interpolate(spec.start, spec.stop, i as f64 / (spec.count - 1) as f64),
+        })
+        .map(format_value)
+        .collect()
+}
+
+/// Ensures that a generator is given real numbers (Helper function).
+fn check_finite(name: &str, generator: &str, numbers: &[f64]) -> Result<()> {
+    if numbers.iter().any(|number| !number.is_finite()) {
+        bailc!(
+          "Invalid {generator} for parameter {name}", ;
+          "The bounds and step of a {generator} cannot be infinite or NaN", ;
+          "",
+        );
+    }
+
+    Ok(())
+}
+
+/// Ensures that a generator generates at least one value (Helper function).
+fn check_count(name: &str, generator: &str, count: usize) -> Result<()> {
+    if count == 0 {
+        bailc!(
+          "Invalid {generator} for parameter {name}", ;
+          "A {generator} has to generate at least one value", ;
+          "Set the count to at least 1",
+        );
+    }
+
+    Ok(())
+}
+
+/// Formats a generated value, without the noise of floating point arithmetic
+/// such as in `0.30000000000000004`.
+pub fn format_value(value: f64) -> String {
+    let rounded: f64 = format!("{value:.12e}").parse().unwrap_or(value);
+
+    rounded.to_string()
 }
 
 /// Checks if all sub parameters of each parameter specified in `parameters`
-/// are equal (Helper function).
-fn check_sub_parameter_size_is_equal(parameters: &BTreeMap<String, Parameter>) -> Result<()> {
+/// have the same amount of values.
+pub fn check_sub_parameter_size_is_equal(parameters: &BTreeMap<String, Parameter>) -> Result<()> {
     for (parameter_name, parameter) in parameters {
         if let Some(sub_parameters) = &parameter.sub {
             let sub_parameter_size = sub_parameters
@@ -159,18 +847,118 @@ fn check_sub_parameter_size_is_equal(parameters: &BTreeMap<String, Parameter>) -
     Ok(())
 }
 
-/// Gets names and positions of parameters in the provided `input`. (Helper
+/// Gets the parameters used in the `input`, in its arguments and as
+/// placeholders in its paths (Helper function).
+fn get_input_parameters(
+    input: &UserInput,
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<ParameterUses> {
+    let mut uses = ParameterUses::new();
+
+    get_expandable_parameters(&input.arguments, &mut uses)?;
+
+    if let Some(file) = &input.file {
+        get_placeholder_parameters(&file.to_string_lossy(), parameters, &mut uses);
+    }
+
+    if let Some(glob) = &input.glob {
+        get_placeholder_parameters(glob, parameters, &mut uses);
+    }
+
+    for value in input.files.values().chain(input.env.values()) {
+        get_placeholder_parameters(value, parameters, &mut uses);
+    }
+
+    Ok(uses)
+}
+
+/// The names of the parameters that the `program` uses, in its arguments,
+/// environment variables and resource limits.
+pub fn program_parameters(
+    program: &UserProgram,
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<BTreeSet<String>> {
+    Ok(get_program_parameters(program, parameters)?
+        .into_keys()
+        .collect())
+}
+
+/// Gets names and positions of parameters used in a program. (Helper function)
+fn get_program_parameters(
+    program: &UserProgram,
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<ParameterUses> {
+    let mut uses = ParameterUses::new();
+
+    get_expandable_parameters(&program.arguments, &mut uses)?;
+
+    for value in program.env.values() {
+        get_placeholder_parameters(value, parameters, &mut uses);
+    }
+
+    for reference in program
+        .resource_limits
+        .iter()
+        .flat_map(|limits| limits.parameters())
+    {
+        if let Some((param_name, sub_name)) = parse_parameter_reference(reference)? {
+            uses.entry(param_name).or_default().push((None, sub_name));
+        }
+    }
+
+    Ok(uses)
+}
+
+/// Gets names and positions of parameters in the provided `arguments`. (Helper
 /// function)
 ///
-/// Saves parameter names in `expandable_parameters` Set and
-/// `parameter_names_encountered` Set
+/// Saves map of Parameter name to the Indexes where it is used in `uses`
+fn get_expandable_parameters(arguments: &[String], uses: &mut ParameterUses) -> Result<()> {
+    for (pos, arg) in arguments.iter().enumerate() {
+        if let Some((param_name, subparam_name)) = parse_parameter_reference(arg)? {
+            uses.entry(param_name)
+                .or_default()
+                .push((Some(pos), subparam_name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the parameters used as `{x}` or `{x.a}` placeholders in the `text`
+/// (Helper function).
 ///
-/// Saves map of Index to Parameter name in `map`
-fn get_expandable_parameters(
-    input: &UserInput,
-    map: &mut BTreeMap<String, Vec<(usize, Option<String>)>>,
-    expandable_parameters: &mut BTreeSet<String>,
-) -> Result<()> {
+/// Anything in braces that is not the name of a parameter is left alone.
+fn get_placeholder_parameters(
+    text: &str,
+    parameters: &BTreeMap<String, Parameter>,
+    uses: &mut ParameterUses,
+) {
+    let mut rest = text;
+
+    while let Some((_, after_open)) = rest.split_once('{') {
+        let Some((placeholder, after_close)) = after_open.split_once('}') else {
+            break;
+        };
+
+        let (param_name, subparam_name) = match placeholder.split_once('.') {
+            Some((param_name, subparam_name)) => (param_name, Some(subparam_name.to_string())),
+            None => (placeholder, None),
+        };
+
+        if parameters.contains_key(param_name) {
+            uses.entry(param_name.to_string())
+                .or_default()
+                .push((None, subparam_name));
+        }
+
+        rest = after_close;
+    }
+}
+
+/// Parses a `param|x` or `subparam|x.a` reference into the name of the
+/// parameter and subparameter, if the `text` is a reference.
+pub fn parse_parameter_reference(text: &str) -> Result<Option<(String, Option<String>)>> {
     /// Ensures that the syntax is correct.
     fn constrict_syntax(inner: Option<&str>) -> Result<&str> {
         inner
@@ -178,46 +966,43 @@ fn get_expandable_parameters(
             .with_context(ctx!("", ; "", ))
     }
 
-    for (pos, arg) in input.arguments.iter().enumerate() {
-        if let Some(param_name) = arg.strip_prefix(PARAMETER_ESCAPE).to_owned() {
-            expandable_parameters.insert(param_name.to_string());
+    if let Some(param_name) = text.strip_prefix(PARAMETER_ESCAPE) {
+        Ok(Some((param_name.to_string(), None)))
+    } else if let Some(whole_subparam) = text.strip_prefix(SUB_PARAMETER_ESCAPE) {
+        let mut dot_iter = whole_subparam.split('.');
 
-            if let Some(vector) = map.get_mut(param_name) {
-                vector.push((pos, None))
-            } else {
-                map.insert(param_name.to_string(), vec![(pos, None)]);
-            }
-        } else if let Some(whole_subparam) = arg.strip_prefix(SUB_PARAMETER_ESCAPE) {
-            let mut dot_iter = whole_subparam.split('.');
-
-            let param_name = constrict_syntax(dot_iter.next())?.to_string();
+        let param_name = constrict_syntax(dot_iter.next())?.to_string();
 
-            let subparam_name = constrict_syntax(dot_iter.next())?.to_string();
-
-            if dot_iter.next().is_some() {
-                bailc!("Invalid parameter syntax", ; "", ; "",);
-            }
+        let subparam_name = constrict_syntax(dot_iter.next())?.to_string();
 
-            expandable_parameters.insert(param_name.clone());
-
-            if let Some(vector) = map.get_mut(&param_name) {
-                vector.push((pos, Some(subparam_name)));
-            } else {
-                map.insert(param_name, vec![(pos, Some(subparam_name))]);
-            }
+        if dot_iter.next().is_some() {
+            bailc!("Invalid parameter syntax", ; "", ; "",);
         }
+
+        Ok(Some((param_name, Some(subparam_name))))
+    } else {
+        Ok(None)
     }
+}
 
-    Ok(())
+/// The name that the value of a `param|x` or `subparam|x.a` reference is
+/// known by in a combination of parameter values, `x` or `x.a`.
+pub fn parameter_key(reference: &str) -> Result<Option<String>> {
+    Ok(parse_parameter_reference(reference)?.map(
+        |(param_name, subparam_name)| match subparam_name {
+            Some(subparam_name) => format!("{param_name}.{subparam_name}"),
+            None => param_name,
+        },
+    ))
 }
 
 /// Expands provided parameter (Helper function).
 fn expand_parameter(
     parameter_name: &String,
     param: &Parameter,
-    set: BTreeSet<(String, Vec<String>)>,
-    indexes: &Vec<(usize, Option<String>)>,
-) -> Result<BTreeSet<(String, Vec<String>)>> {
+    set: BTreeSet<Combination>,
+    indexes: &Vec<ParameterUse>,
+) -> Result<BTreeSet<Combination>> {
     let param_values = &param
         .values
         .clone()
@@ -229,38 +1014,172 @@ fn expand_parameter(
 
     let mut new_set = BTreeSet::new();
 
-    for (base_name, arguments) in set {
+    for (base_name, arguments, values) in set {
         // For each value...
         for (i, value) in param_values.iter().enumerate() {
             let mut arguments_clone = arguments.clone();
+            let mut values_clone = values.clone();
+            values_clone.insert(parameter_name.clone(), value.clone());
 
             // Everywhere where this parameter appears we replace it with
             // its value.
-            for index in indexes {
-                if index.1.is_some() {
-                    bailc!(
-                        "Ivariant failed", ;
-                        "You cannot use subparameters for this parameter anymore", ;
-                        "For parameter \"{parameter_name}\"",
-                    );
-                }
-                arguments_clone[index.0] = value.to_string();
+            assign_value(parameter_name, value, indexes, &mut arguments_clone)?;
+
+            new_set.insert((
+                format!("{base_name}_{parameter_name}_{i}"),
+                arguments_clone,
+                values_clone,
+            ));
+        }
+    }
+
+    Ok(new_set)
+}
+
+/// Replaces a parameter with its `value` everywhere where it is used in the
+/// `arguments` (Helper function).
+fn assign_value(
+    parameter_name: &String,
+    value: &String,
+    indexes: &Vec<ParameterUse>,
+    arguments: &mut [String],
+) -> Result<()> {
+    for (pos, sub) in indexes {
+        if sub.is_some() {
+            bailc!(
+                "Ivariant failed", ;
+                "You cannot use subparameters for this parameter anymore", ;
+                "For parameter \"{parameter_name}\"",
+            );
+        }
+
+        if let Some(pos) = pos {
+            arguments[*pos].clone_from(value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the used parameters of the zipped group that a parameter belongs to,
+/// if it belongs to one (Helper function).
+fn zipped_group(
+    zipped: &[Vec<String>],
+    uses: &ParameterUses,
+    parameter_name: &String,
+) -> Option<Vec<String>> {
+    zipped
+        .iter()
+        .find(|group| group.contains(parameter_name))
+        .map(|group| {
+            group
+                .iter()
+                .filter(|member| uses.contains_key(*member))
+                .cloned()
+                .collect::<BTreeSet<String>>()
+                .into_iter()
+                .collect()
+        })
+}
+
+/// The values of a parameter in a zipped group (Helper function).
+fn zipped_values<'a>(
+    parameter_name: &String,
+    parameters: &'a BTreeMap<String, Parameter>,
+) -> Result<&'a Vec<String>> {
+    match parameters
+        .get(parameter_name)
+        .and_then(|p| p.values.as_ref())
+    {
+        Some(values) => Ok(values),
+        None => {
+            bailc!(
+              "Cannot zip parameter {parameter_name}", ;
+              "Only parameters that exist and have (or generate) values can be zipped", ;
+              "Subparameters are already zipped with each other",
+            );
+        }
+    }
+}
+
+/// Expands the used parameters of a zipped group together, using their values
+/// at the same index (Helper function).
+fn expand_zipped(
+    group: &[String],
+    uses: &ParameterUses,
+    parameters: &BTreeMap<String, Parameter>,
+    set: BTreeSet<Combination>,
+) -> Result<BTreeSet<Combination>> {
+    let count = zipped_values(&group[0], parameters)?.len();
+
+    let mut new_set = BTreeSet::new();
+
+    for (base_name, arguments, values) in set {
+        for i in 0..count {
+            let mut name = base_name.clone();
+            let mut arguments_clone = arguments.clone();
+            let mut values_clone = values.clone();
+
+            for member in group {
+                let value = &zipped_values(member, parameters)?[i];
+
+                assign_value(member, value, &uses[member], &mut arguments_clone)?;
+                values_clone.insert(member.clone(), value.clone());
+                name.push_str(&format!("_{member}_{i}"));
             }
 
-            new_set.insert((format!("{base_name}_{parameter_name}_{i}"), arguments_clone));
+            new_set.insert((name, arguments_clone, values_clone));
         }
     }
 
     Ok(new_set)
 }
 
+/// Checks that the `zipped` groups consist of parameters with values, that
+/// every group has values of the same length, and that no parameter is in two
+/// groups.
+pub fn validate_zipped(
+    zipped: &[Vec<String>],
+    parameters: &BTreeMap<String, Parameter>,
+) -> Result<()> {
+    let mut seen = BTreeSet::new();
+
+    for group in zipped {
+        let mut lengths = BTreeSet::new();
+
+        for member in group {
+            lengths.insert(zipped_values(member, parameters)?.len());
+
+            if !seen.insert(member) {
+                bailc!(
+                  "Cannot zip parameter {member} twice", ;
+                  "A parameter can be in only one zipped group", ;
+                  "Merge the groups that contain {member}",
+                );
+            }
+        }
+
+        if lengths.len() > 1 {
+            let group = group.join(", ");
+
+            bailc!(
+              "Zipped parameters have different amounts of values", ;
+              "The parameters {group} are zipped, so they need the same amount of values", ;
+              "",
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// Expands provided sub parameter (Helper function).
 fn expand_sub_parameter(
     param_name: &String,
     param: &Parameter,
-    set: BTreeSet<(String, Vec<String>)>,
-    indexes: &Vec<(usize, Option<String>)>,
-) -> Result<BTreeSet<(String, Vec<String>)>> {
+    set: BTreeSet<Combination>,
+    indexes: &Vec<ParameterUse>,
+) -> Result<BTreeSet<Combination>> {
     let subparams = &param
         .sub
         .clone()
@@ -280,9 +1199,15 @@ fn expand_sub_parameter(
 
     let mut new_set = BTreeSet::new();
 
-    for (base_name, arguments) in set {
+    for (base_name, arguments, values) in set {
         for i in 0..size_of_one {
             let mut arguments_clone = arguments.clone();
+            let mut values_clone = values.clone();
+
+            for (sub_name, sub) in subparams {
+                values_clone.insert(format!("{param_name}.{sub_name}"), sub.values[i].clone());
+            }
+
             for sub_index in indexes {
                 let expanding = &sub_index
                     .1
@@ -294,18 +1219,24 @@ fn expand_sub_parameter(
                         field defined and \"{param_name}\" does not",
                     ))?;
 
-                arguments_clone[sub_index.0].clone_from(
-                    &subparams
-                        .get(expanding)
-                        .ok_or(anyhow!("Invalid subparameter specified {expanding}"))
-                        .with_context(ctx!(
-                        "For parameter {param_name}", ;
-                        "Ensure that it exists", ))?
-                        .values[i],
-                );
+                let value = &subparams
+                    .get(expanding)
+                    .ok_or(anyhow!("Invalid subparameter specified {expanding}"))
+                    .with_context(ctx!(
+                    "For parameter {param_name}", ;
+                    "Ensure that it exists", ))?
+                    .values[i];
+
+                if let Some(pos) = sub_index.0 {
+                    arguments_clone[pos].clone_from(value);
+                }
             }
 
-            new_set.insert((format!("{base_name}_{param_name}_{i}"), arguments_clone));
+            new_set.insert((
+                format!("{base_name}_{param_name}_{i}"),
+                arguments_clone,
+                values_clone,
+            ));
         }
     }
 
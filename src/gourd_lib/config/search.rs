@@ -0,0 +1,131 @@
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::bailc;
+
+/// How the parameter space of an experiment is searched.
+///
+/// # Examples
+///
+/// ```toml
+/// [search]
+/// strategy = "random"
+/// samples = 200
+/// seed = 42
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Which inputs to create from the parameters.
+    #[serde(default)]
+    pub strategy: SearchStrategy,
+
+    /// How many combinations of parameter values to sample for every input.
+    ///
+    /// Required by the random search.
+    pub samples: Option<usize>,
+
+    /// The seed of the random search, the same seed samples the same values.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// How many combinations of parameter values the `search` samples, or `None`
+/// when it runs every combination.
+pub fn search_samples(search: &Option<SearchConfig>) -> Result<Option<usize>> {
+    match search {
+        None
+        | Some(SearchConfig {
+            strategy: SearchStrategy::Grid,
+            samples: None,
+            ..
+        }) => Ok(None),
+
+        Some(SearchConfig {
+            strategy: SearchStrategy::Random,
+            samples: Some(samples),
+            ..
+        }) if *samples > 0 => Ok(Some(*samples)),
+
+        Some(SearchConfig {
+            strategy: SearchStrategy::Grid,
+            ..
+        }) => {
+            bailc!(
+                "Invalid search configuration",;
+                "A grid search runs every combination of parameter values",;
+                "Remove `samples`, or set `strategy = \"random\"` to sample them",
+            );
+        }
+
+        Some(SearchConfig {
+            strategy: SearchStrategy::Random,
+            ..
+        }) => {
+            bailc!(
+                "Invalid search configuration",;
+                "A random search needs to know how many samples to take",;
+                "Set `samples` to at least 1 in the [search] section",
+            );
+        }
+    }
+}
+
+/// A way to search the parameter space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchStrategy {
+    /// Run every combination of parameter values.
+    #[default]
+    Grid,
+
+    /// Run a random sample of the combinations of parameter values.
+    Random,
+}
+
+/// A small seeded random number generator (SplitMix64).
+///
+/// Implemented here so that a seed samples the same values with every version
+/// of `gourd`.
+// Not `Copy`, so that a copy cannot silently repeat the same random numbers.
+#[allow(missing_copy_implementations)]
+#[derive(Debug, Clone)]
+pub struct SearchRng {
+    /// The state of the generator.
+    state: u64,
+}
+
+impl SearchRng {
+    /// Create a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A generator of its own, seeded from this one.
+    pub fn split(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+
+    /// The next random 64 bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// A random number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A random index below `bound`, which has to be greater than 0.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_f64() * bound as f64) as usize
+    }
+}
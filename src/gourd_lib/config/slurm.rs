@@ -3,9 +3,15 @@ use std::fmt::Display;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use anyhow::Context;
+use anyhow::Result;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::Parametrized;
+use crate::bailc;
+use crate::ctx;
+
 /// The config options when running through Slurm
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -85,6 +91,98 @@ pub struct ResourceLimits {
     pub mem_per_cpu: usize,
 }
 
+/// The resource limits of a program, where every limit can also be taken from
+/// a parameter, as in `cpus = "param|threads"`.
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserResourceLimits {
+    /// Maximum time allowed _for each_ job.
+    #[serde(
+        deserialize_with = "super::duration::deserialize_parametrized_duration",
+        serialize_with = "super::duration::serialize_parametrized_duration"
+    )]
+    pub time_limit: Parametrized<Duration>,
+
+    /// CPUs to use per job
+    pub cpus: Parametrized<usize>,
+
+    /// Memory in MB to allocate per CPU per job
+    pub mem_per_cpu: Parametrized<usize>,
+}
+
+impl UserResourceLimits {
+    /// The parameters that the limits are taken from.
+    pub fn parameters(&self) -> Vec<&str> {
+        [
+            self.time_limit.reference(),
+            self.cpus.reference(),
+            self.mem_per_cpu.reference(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Take the limits that are parameters from their values in `values`.
+    pub fn substitute(&self, values: &BTreeMap<String, String>) -> Result<Self> {
+        Ok(UserResourceLimits {
+            time_limit: self.time_limit.substitute(values, |value| {
+                humantime::parse_duration(value).with_context(ctx!(
+                    "This is not a valid duration", ;
+                    "Durations are written like \"30s\" or \"1h 30min\"",
+                ))
+            })?,
+            cpus: self.cpus.substitute(values, |value| Ok(value.parse()?))?,
+            mem_per_cpu: self
+                .mem_per_cpu
+                .substitute(values, |value| Ok(value.parse()?))?,
+        })
+    }
+
+    /// The limits, once none of them are parameters anymore.
+    pub fn resolve(&self) -> Result<ResourceLimits> {
+        ResourceLimits {
+            time_limit: self.time_limit.value("the time limit")?,
+            cpus: self.cpus.value("the cpus")?,
+            mem_per_cpu: self.mem_per_cpu.value("the memory per cpu")?,
+        }
+        .checked()
+    }
+}
+
+impl ResourceLimits {
+    /// The limits, if none of them are 0.
+    ///
+    /// A program with a limit of 0 would be killed as soon as it starts.
+    pub fn checked(self) -> Result<Self> {
+        let zero = if self.time_limit.is_zero() {
+            "time_limit"
+        } else if self.cpus == 0 {
+            "cpus"
+        } else if self.mem_per_cpu == 0 {
+            "mem_per_cpu"
+        } else {
+            return Ok(self);
+        };
+
+        bailc!(
+            "The resource limit {zero} is 0", ;
+            "A program with a limit of 0 would be killed as soon as it starts", ;
+            "Give {zero} a value above 0",
+        );
+    }
+}
+
+impl From<ResourceLimits> for UserResourceLimits {
+    fn from(limits: ResourceLimits) -> Self {
+        UserResourceLimits {
+            time_limit: Parametrized::Value(limits.time_limit),
+            cpus: Parametrized::Value(limits.cpus),
+            mem_per_cpu: Parametrized::Value(limits.mem_per_cpu),
+        }
+    }
+}
+
 impl Default for ResourceLimits {
     fn default() -> Self {
         ResourceLimits {
@@ -0,0 +1,73 @@
+use super::*;
+
+/// Parameter values from pairs of names and values.
+fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_constraint_arithmetic_and_comparison() {
+    let constraint = Constraint::parse("depth * 2 <= side_length.x - 1").unwrap();
+
+    assert_eq!(
+        Some(true),
+        constraint
+            .evaluate(&values(&[("depth", "3"), ("side_length.x", "7")]))
+            .unwrap()
+    );
+    assert_eq!(
+        Some(false),
+        constraint
+            .evaluate(&values(&[("depth", "4"), ("side_length.x", "7")]))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_constraint_boolean_logic() {
+    let constraint =
+        Constraint::parse("not (solver == 'exact' and size > 1e2) || size % 2 == 0").unwrap();
+
+    let check = |solver, size| {
+        constraint
+            .evaluate(&values(&[("solver", solver), ("size", size)]))
+            .unwrap()
+            .unwrap()
+    };
+
+    assert!(check("heuristic", "1001"));
+    assert!(check("exact", "50"));
+    assert!(check("exact", "1000"));
+    assert!(!check("exact", "1001"));
+}
+
+#[test]
+fn test_constraint_does_not_apply() {
+    let constraint = Constraint::parse("x < y").unwrap();
+
+    assert_eq!(
+        BTreeSet::from(["x".to_string(), "y".to_string()]),
+        constraint.variables()
+    );
+    assert_eq!(None, constraint.evaluate(&values(&[("x", "1")])).unwrap());
+}
+
+#[test]
+fn test_constraint_invalid() {
+    assert!(Constraint::parse("x <").is_err());
+    assert!(Constraint::parse("(x < 1").is_err());
+    assert!(Constraint::parse("x < 1 2").is_err());
+    assert!(Constraint::parse("x # 1").is_err());
+    assert!(Constraint::parse("name == 'unclosed").is_err());
+
+    // Constraints have to be true or false.
+    let not_boolean = Constraint::parse("x + 1").unwrap();
+    assert!(not_boolean.evaluate(&values(&[("x", "1")])).is_err());
+
+    // Text cannot be ordered against numbers.
+    let mixed = Constraint::parse("x < 1").unwrap();
+    assert!(mixed.evaluate(&values(&[("x", "abc")])).is_err());
+}
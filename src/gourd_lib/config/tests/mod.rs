@@ -11,6 +11,7 @@ use std::path::PathBuf;
 
 use tempdir::TempDir;
 
+use crate::config::compare::Comparison;
 use crate::config::Config;
 use crate::config::UserInput;
 use crate::config::UserProgram;
@@ -37,6 +38,18 @@ fn breaking_changes_config_struct() {
         resource_limits: None,
         labels: Some(BTreeMap::new()),
         warn_on_label_overlap: false,
+        sampling_interval: None,
+        performance_counters: false,
+        repetitions: None,
+        seed: None,
+        search: None,
+        constraints: vec![],
+        zip: vec![],
+        tune: None,
+        compare: Comparison::default(),
+        agreement: None,
+        env: BTreeMap::new(),
+        clear_env: false,
     };
 }
 
@@ -76,6 +89,18 @@ fn breaking_changes_config_file_all_values() {
             resource_limits: None,
             labels: None,
             warn_on_label_overlap: false,
+            sampling_interval: None,
+            performance_counters: false,
+            repetitions: None,
+            seed: None,
+            search: None,
+            constraints: vec![],
+            zip: vec![],
+            tune: None,
+            compare: Comparison::default(),
+            agreement: None,
+            env: BTreeMap::new(),
+            clear_env: false,
         },
         Config::from_file(file_pathbuf.as_path(), &REAL_FS).expect("Unexpected config read error.")
     );
@@ -113,6 +138,18 @@ fn breaking_changes_config_file_required_values() {
             resource_limits: None,
             labels: None,
             warn_on_label_overlap: false,
+            sampling_interval: None,
+            performance_counters: false,
+            repetitions: None,
+            seed: None,
+            search: None,
+            constraints: vec![],
+            zip: vec![],
+            tune: None,
+            compare: Comparison::default(),
+            agreement: None,
+            env: BTreeMap::new(),
+            clear_env: false,
         },
         Config::from_file(file_pb.as_path(), &REAL_FS).expect("Unexpected config read error.")
     );
@@ -292,6 +329,15 @@ fn parse_valid_escape_hatch_file() {
                 afterscript: None,
                 resource_limits: None,
                 next: vec![],
+                repetitions: None,
+                parameters: None,
+                zip: vec![],
+                include: vec![],
+                exclude: vec![],
+                stdin: true,
+                setup: vec![],
+                teardown: vec![],
+                env: BTreeMap::new(),
             },
         )]
         .into_iter()
@@ -309,6 +355,11 @@ fn parse_valid_escape_hatch_file() {
                     fetch: None,
                     group: None,
                     arguments: vec!["hello".to_string()],
+                    repetitions: None,
+                    files: BTreeMap::new(),
+                    generator: None,
+                    expected: None,
+                    env: BTreeMap::new(),
                 },
             ),
             (
@@ -323,6 +374,11 @@ fn parse_valid_escape_hatch_file() {
                     fetch: None,
                     group: None,
                     arguments: vec!["hi".to_string()],
+                    repetitions: None,
+                    files: BTreeMap::new(),
+                    generator: None,
+                    expected: None,
+                    env: BTreeMap::new(),
                 },
             ),
         ]
@@ -334,6 +390,18 @@ fn parse_valid_escape_hatch_file() {
         wrapper: WRAPPER_DEFAULT(),
         labels: None,
         warn_on_label_overlap: false,
+        sampling_interval: None,
+        performance_counters: false,
+        repetitions: None,
+        seed: None,
+        search: None,
+        constraints: vec![],
+        zip: vec![],
+        tune: None,
+        compare: Comparison::default(),
+        agreement: None,
+        env: BTreeMap::new(),
+        clear_env: false,
     };
     assert_eq!(c1, c2);
 }
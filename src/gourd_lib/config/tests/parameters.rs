@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::DistributionSpec;
 use crate::config::SubParameter;
 
 #[test]
@@ -12,11 +13,16 @@ fn test_expand_parameters_ok_no_expandable() {
             fetch: None,
             group: None,
             arguments: vec!["nice".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
 
     let parameters = BTreeMap::new();
-    let expanded = expand_parameters(inputs.clone(), &parameters).unwrap();
+    let expanded = expand_parameters(inputs.clone(), &parameters, &[], &[]).unwrap();
     assert_eq!(expanded, inputs);
 }
 
@@ -31,6 +37,11 @@ fn test_expand_parameters_ok_parameter() {
             fetch: None,
             group: None,
             arguments: vec!["-e".to_string(), "param|x".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let mut parameters = BTreeMap::new();
@@ -39,9 +50,14 @@ fn test_expand_parameters_ok_parameter() {
         Parameter {
             sub: None,
             values: Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
-    let expanded = expand_parameters(inputs.clone(), &parameters).unwrap();
+    let expanded = expand_parameters(inputs.clone(), &parameters, &[], &[]).unwrap();
     let mut expected = BTreeMap::new();
     expected.insert(
         format!("first_x_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
@@ -51,6 +67,11 @@ fn test_expand_parameters_ok_parameter() {
             fetch: None,
             group: None,
             arguments: vec!["-e".to_string(), "a".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -61,6 +82,11 @@ fn test_expand_parameters_ok_parameter() {
             fetch: None,
             group: None,
             arguments: vec!["-e".to_string(), "b".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -71,6 +97,11 @@ fn test_expand_parameters_ok_parameter() {
             fetch: None,
             group: None,
             arguments: vec!["-e".to_string(), "c".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
 
@@ -93,6 +124,11 @@ fn test_expand_parameters_ok_parameter_doubled() {
                 "-f".to_string(),
                 "param|x".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let mut parameters = BTreeMap::new();
@@ -101,9 +137,14 @@ fn test_expand_parameters_ok_parameter_doubled() {
         Parameter {
             sub: None,
             values: Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
-    let expanded = expand_parameters(inputs.clone(), &parameters).unwrap();
+    let expanded = expand_parameters(inputs.clone(), &parameters, &[], &[]).unwrap();
     let mut expected = BTreeMap::new();
     expected.insert(
         format!("first_x_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
@@ -118,6 +159,11 @@ fn test_expand_parameters_ok_parameter_doubled() {
                 "-f".to_string(),
                 "a".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -133,6 +179,11 @@ fn test_expand_parameters_ok_parameter_doubled() {
                 "-f".to_string(),
                 "b".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -148,6 +199,11 @@ fn test_expand_parameters_ok_parameter_doubled() {
                 "-f".to_string(),
                 "c".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     assert_eq!(expanded, expected);
@@ -169,6 +225,11 @@ fn test_expand_parameters_ok_subparameter() {
                 "-x".to_string(),
                 "subparam|x.2".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let mut sub_parameters = BTreeMap::new();
@@ -190,9 +251,14 @@ fn test_expand_parameters_ok_subparameter() {
         Parameter {
             sub: Some(sub_parameters),
             values: None,
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
-    let expanded = expand_parameters(inputs, &parameters).unwrap();
+    let expanded = expand_parameters(inputs, &parameters, &[], &[]).unwrap();
     let mut expected = BTreeMap::new();
     expected.insert(
         format!("first_x_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
@@ -207,6 +273,11 @@ fn test_expand_parameters_ok_subparameter() {
                 "-x".to_string(),
                 "10".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -222,6 +293,11 @@ fn test_expand_parameters_ok_subparameter() {
                 "-x".to_string(),
                 "20".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -237,6 +313,11 @@ fn test_expand_parameters_ok_subparameter() {
                 "-x".to_string(),
                 "30".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     assert_eq!(expanded, expected);
@@ -260,6 +341,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "subparam|x.2".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let mut sub_parameters = BTreeMap::new();
@@ -282,6 +368,11 @@ fn test_expand_parameters_ok_both() {
         Parameter {
             sub: Some(sub_parameters),
             values: None,
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
     parameters.insert(
@@ -293,10 +384,15 @@ fn test_expand_parameters_ok_both() {
                 "yyy".to_string(),
                 "zzz".to_string(),
             ]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
 
-    let expanded = expand_parameters(inputs, &parameters).unwrap();
+    let expanded = expand_parameters(inputs, &parameters, &[], &[]).unwrap();
     let mut expected = BTreeMap::new();
     expected.insert(
         format!("first_x_0_y_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
@@ -313,6 +409,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "10".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -330,6 +431,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "20".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -347,6 +453,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "30".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -364,6 +475,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "10".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -381,6 +497,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "20".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -398,6 +519,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "30".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -415,6 +541,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "10".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -432,6 +563,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "20".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     expected.insert(
@@ -449,6 +585,11 @@ fn test_expand_parameters_ok_both() {
                 "-x".to_string(),
                 "30".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     assert_eq!(expanded, expected);
@@ -465,10 +606,15 @@ fn test_expand_parameters_fail_subparameter_not_declared() {
             fetch: None,
             group: None,
             arguments: vec!["-e".to_string(), "param|x".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let parameters = BTreeMap::new();
-    assert!(expand_parameters(inputs, &parameters).is_err());
+    assert!(expand_parameters(inputs, &parameters, &[], &[]).is_err());
 }
 
 #[test]
@@ -487,6 +633,11 @@ fn test_expand_parameters_fail_subparameter_size_not_match() {
                 "-x".to_string(),
                 "param|x_2".to_string(),
             ],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     );
     let mut sub_parameters = BTreeMap::new();
@@ -508,7 +659,556 @@ fn test_expand_parameters_fail_subparameter_size_not_match() {
         Parameter {
             sub: Some(sub_parameters),
             values: None,
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
         },
     );
-    assert!(expand_parameters(inputs, &parameters).is_err());
+    assert!(expand_parameters(inputs, &parameters, &[], &[]).is_err());
+}
+
+/// Generate the values of a parameter that only has the given generator.
+fn generated(parameter: Parameter) -> Result<Option<Vec<String>>> {
+    let mut parameters = BTreeMap::new();
+    parameters.insert("x".to_string(), parameter);
+
+    validate_parameters(&parameters)?;
+
+    Ok(generate_parameter_values(&parameters)?["x"].values.clone())
+}
+
+#[test]
+fn test_generate_parameter_values_range() {
+    let range = |start, stop, step| Parameter {
+        sub: None,
+        values: None,
+        range: Some(RangeSpec { start, stop, step }),
+        linspace: None,
+        logspace: None,
+        uniform: None,
+        loguniform: None,
+    };
+
+    assert_eq!(
+        generated(range(1.0, 9.0, 2.0)).unwrap().unwrap(),
+        vec!["1", "3", "5", "7"]
+    );
+    assert_eq!(
+        generated(range(0.0, 0.5, 0.1)).unwrap().unwrap(),
+        vec!["0", "0.1", "0.2", "0.3", "0.4"]
+    );
+    assert_eq!(
+        generated(range(3.0, 0.0, -1.0)).unwrap().unwrap(),
+        vec!["3", "2", "1"]
+    );
+
+    assert!(generated(range(0.0, 1.0, 0.0)).is_err());
+    assert!(generated(range(0.0, 1.0, -1.0)).is_err());
+    assert!(generated(range(0.0, f64::INFINITY, 1.0)).is_err());
+}
+
+#[test]
+fn test_generate_parameter_values_linspace() {
+    let linspace = |start, stop, count| Parameter {
+        sub: None,
+        values: None,
+        range: None,
+        linspace: Some(SpacingSpec { start, stop, count }),
+        logspace: None,
+        uniform: None,
+        loguniform: None,
+    };
+
+    assert_eq!(
+        generated(linspace(0.0, 1.0, 5)).unwrap().unwrap(),
+        vec!["0", "0.25", "0.5", "0.75", "1"]
+    );
+    assert_eq!(
+        generated(linspace(2.0, 0.0, 3)).unwrap().unwrap(),
+        vec!["2", "1", "0"]
+    );
+    assert_eq!(
+        generated(linspace(4.0, 8.0, 1)).unwrap().unwrap(),
+        vec!["4"]
+    );
+
+    assert!(generated(linspace(0.0, 1.0, 0)).is_err());
+    assert!(generated(linspace(f64::NAN, 1.0, 2)).is_err());
+}
+
+#[test]
+fn test_generate_parameter_values_logspace() {
+    let logspace = |start, stop, count| Parameter {
+        sub: None,
+        values: None,
+        range: None,
+        linspace: None,
+        logspace: Some(SpacingSpec { start, stop, count }),
+        uniform: None,
+        loguniform: None,
+    };
+
+    assert_eq!(
+        generated(logspace(0.0001, 1.0, 5)).unwrap().unwrap(),
+        vec!["0.0001", "0.001", "0.01", "0.1", "1"]
+    );
+    assert_eq!(
+        generated(logspace(1.0, 64.0, 4)).unwrap().unwrap(),
+        vec!["1", "4", "16", "64"]
+    );
+
+    assert!(generated(logspace(0.0, 1.0, 3)).is_err());
+    assert!(generated(logspace(-1.0, 1.0, 3)).is_err());
+    assert!(generated(logspace(1.0, 10.0, 0)).is_err());
+}
+
+#[test]
+fn test_validate_parameters_multiple_sources() {
+    let parameter = Parameter {
+        sub: None,
+        values: Some(vec!["1".to_string()]),
+        range: Some(RangeSpec {
+            start: 0.0,
+            stop: 1.0,
+            step: 1.0,
+        }),
+        linspace: None,
+        logspace: None,
+        uniform: None,
+        loguniform: None,
+    };
+
+    assert!(generated(parameter).is_err());
+}
+
+#[test]
+fn test_expand_parameters_ok_range() {
+    let mut inputs = BTreeMap::new();
+    inputs.insert(
+        "first".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["-t".to_string(), "param|threads".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    );
+    let mut parameters = BTreeMap::new();
+    parameters.insert(
+        "threads".to_string(),
+        Parameter {
+            sub: None,
+            values: None,
+            range: Some(RangeSpec {
+                start: 1.0,
+                stop: 3.0,
+                step: 1.0,
+            }),
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
+        },
+    );
+
+    let expanded = expand_parameters(inputs, &parameters, &[], &[]).unwrap();
+
+    assert_eq!(
+        expanded[&format!("first_threads_1{INTERNAL_PREFIX}{INTERNAL_PARAMETER}")].arguments,
+        vec!["-t", "2"]
+    );
+    assert_eq!(2, expanded.len());
+}
+
+/// An input that uses the parameters `x` and `y`.
+fn sampled_input() -> BTreeMap<String, UserInput> {
+    let mut inputs = BTreeMap::new();
+    inputs.insert(
+        "first".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["param|x".to_string(), "param|y".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    );
+
+    inputs
+}
+
+/// A parameter that is sampled from the `uniform` distribution.
+fn uniform(low: f64, high: f64) -> Parameter {
+    Parameter {
+        sub: None,
+        values: None,
+        range: None,
+        linspace: None,
+        logspace: None,
+        uniform: Some(DistributionSpec { low, high }),
+        loguniform: None,
+    }
+}
+
+#[test]
+fn test_sample_parameters_ok() {
+    let mut parameters = BTreeMap::new();
+    parameters.insert(
+        "x".to_string(),
+        Parameter {
+            sub: None,
+            values: Some(vec!["a".to_string(), "b".to_string()]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
+        },
+    );
+    parameters.insert("y".to_string(), uniform(1.0, 2.0));
+
+    let sampled = sample_parameters(
+        sampled_input(),
+        (&parameters, &[]),
+        &[],
+        20,
+        &mut SearchRng::new(42),
+    )
+    .unwrap();
+
+    assert_eq!(20, sampled.len());
+    assert!(sampled.contains_key(&format!(
+        "first_sample_19{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"
+    )));
+
+    for input in sampled.values() {
+        assert!(["a", "b"].contains(&input.arguments[0].as_str()));
+
+        let y: f64 = input.arguments[1].parse().unwrap();
+        assert!((1.0..=2.0).contains(&y));
+    }
+
+    // The same seed samples the same values.
+    assert_eq!(
+        sampled,
+        sample_parameters(
+            sampled_input(),
+            (&parameters, &[]),
+            &[],
+            20,
+            &mut SearchRng::new(42)
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_sample_parameters_small_space() {
+    let mut parameters = BTreeMap::new();
+    for name in ["x", "y"] {
+        parameters.insert(
+            name.to_string(),
+            Parameter {
+                sub: None,
+                values: None,
+                range: Some(RangeSpec {
+                    start: 0.0,
+                    stop: 2.0,
+                    step: 1.0,
+                }),
+                linspace: None,
+                logspace: None,
+                uniform: None,
+                loguniform: None,
+            },
+        );
+    }
+
+    let sampled = sample_parameters(
+        sampled_input(),
+        (&parameters, &[]),
+        &[],
+        10,
+        &mut SearchRng::new(0),
+    )
+    .unwrap();
+
+    // There are only four different combinations.
+    let mut combinations: Vec<Vec<String>> =
+        sampled.into_values().map(|input| input.arguments).collect();
+    combinations.sort();
+
+    assert_eq!(
+        combinations,
+        vec![
+            vec!["0", "0"],
+            vec!["0", "1"],
+            vec!["1", "0"],
+            vec!["1", "1"]
+        ]
+    );
+}
+
+#[test]
+fn test_distributions_invalid() {
+    let mut parameters = BTreeMap::new();
+    parameters.insert("x".to_string(), uniform(2.0, 1.0));
+    assert!(validate_parameters(&parameters).is_err());
+
+    parameters.insert(
+        "x".to_string(),
+        Parameter {
+            loguniform: Some(DistributionSpec {
+                low: 0.0,
+                high: 1.0,
+            }),
+            uniform: None,
+            ..uniform(0.0, 1.0)
+        },
+    );
+    assert!(validate_parameters(&parameters).is_err());
+
+    // A grid cannot be made of a distribution.
+    parameters.insert("x".to_string(), uniform(0.0, 1.0));
+    parameters.insert("y".to_string(), uniform(0.0, 1.0));
+    assert!(validate_parameters(&parameters).is_ok());
+    assert!(expand_parameters(sampled_input(), &parameters, &[], &[]).is_err());
+}
+
+#[test]
+fn test_expand_parameters_constraints() {
+    let mut parameters = BTreeMap::new();
+    for name in ["x", "y"] {
+        parameters.insert(
+            name.to_string(),
+            Parameter {
+                sub: None,
+                values: None,
+                range: Some(RangeSpec {
+                    start: 0.0,
+                    stop: 3.0,
+                    step: 1.0,
+                }),
+                linspace: None,
+                logspace: None,
+                uniform: None,
+                loguniform: None,
+            },
+        );
+    }
+
+    let constraints = [Constraint::parse("x < y").unwrap()];
+    validate_constraints(&constraints, &parameters).unwrap();
+
+    let mut combinations: Vec<Vec<String>> =
+        expand_parameters(sampled_input(), &parameters, &[], &constraints)
+            .unwrap()
+            .into_values()
+            .map(|input| input.arguments)
+            .collect();
+    combinations.sort();
+
+    assert_eq!(
+        combinations,
+        vec![vec!["0", "1"], vec!["0", "2"], vec!["1", "2"]]
+    );
+
+    let sampled = sample_parameters(
+        sampled_input(),
+        (&parameters, &[]),
+        &constraints,
+        10,
+        &mut SearchRng::new(1),
+    )
+    .unwrap();
+    assert_eq!(3, sampled.len());
+
+    assert!(validate_constraints(&[Constraint::parse("z < 1").unwrap()], &parameters).is_err());
+    assert!(validate_constraints(&[Constraint::parse("x.a < 1").unwrap()], &parameters).is_err());
+}
+
+#[test]
+fn test_expand_parameters_paths() {
+    let mut inputs = BTreeMap::new();
+    inputs.insert(
+        "first".to_string(),
+        UserInput {
+            file: Some(PathBuf::from("instances/n{size}_{x.a}.txt")),
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["{unknown}".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    );
+
+    let mut parameters = BTreeMap::new();
+    parameters.insert(
+        "size".to_string(),
+        Parameter {
+            sub: None,
+            values: Some(vec!["10".to_string(), "20".to_string()]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
+        },
+    );
+    parameters.insert(
+        "x".to_string(),
+        Parameter {
+            sub: Some(BTreeMap::from([(
+                "a".to_string(),
+                SubParameter {
+                    values: vec!["u".to_string()],
+                },
+            )])),
+            values: None,
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
+        },
+    );
+
+    let expanded = expand_parameters(inputs, &parameters, &[], &[]).unwrap();
+
+    let files: Vec<(String, PathBuf, Vec<String>)> = expanded
+        .into_iter()
+        .map(|(name, input)| (name, input.file.unwrap(), input.arguments))
+        .collect();
+
+    assert_eq!(
+        files,
+        vec![
+            (
+                format!("first_size_0_x_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                PathBuf::from("instances/n10_u.txt"),
+                vec!["{unknown}".to_string()]
+            ),
+            (
+                format!("first_size_1_x_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                PathBuf::from("instances/n20_u.txt"),
+                vec!["{unknown}".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_expand_parameters_zipped() {
+    let mut parameters = BTreeMap::new();
+    for (name, values) in [("x", ["1", "2"]), ("y", ["a", "b"])] {
+        parameters.insert(
+            name.to_string(),
+            Parameter {
+                sub: None,
+                values: Some(values.map(String::from).to_vec()),
+                range: None,
+                linspace: None,
+                logspace: None,
+                uniform: None,
+                loguniform: None,
+            },
+        );
+    }
+
+    let zipped = [vec!["x".to_string(), "y".to_string()]];
+
+    let expanded = expand_parameters(sampled_input(), &parameters, &zipped, &[]).unwrap();
+    let combinations: Vec<(String, Vec<String>)> = expanded
+        .into_iter()
+        .map(|(name, input)| (name, input.arguments))
+        .collect();
+
+    assert_eq!(
+        combinations,
+        vec![
+            (
+                format!("first_x_0_y_0{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                vec!["1".to_string(), "a".to_string()]
+            ),
+            (
+                format!("first_x_1_y_1{INTERNAL_PREFIX}{INTERNAL_PARAMETER}"),
+                vec!["2".to_string(), "b".to_string()]
+            ),
+        ]
+    );
+
+    let sampled = sample_parameters(
+        sampled_input(),
+        (&parameters, &zipped),
+        &[],
+        10,
+        &mut SearchRng::new(3),
+    )
+    .unwrap();
+    for input in sampled.values() {
+        assert!(input.arguments == ["1", "a"] || input.arguments == ["2", "b"]);
+    }
+
+    parameters.get_mut("y").unwrap().values = Some(vec!["a".to_string()]);
+    assert!(expand_parameters(sampled_input(), &parameters, &zipped, &[]).is_err());
+
+    let twice = [zipped[0].clone(), vec!["x".to_string()]];
+    assert!(validate_zipped(&twice, &parameters).is_err());
+    assert!(validate_zipped(&[vec!["z".to_string()]], &parameters).is_err());
+}
+
+#[test]
+fn test_expand_parameters_env() {
+    let inputs = BTreeMap::from([(
+        "first".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::from([("THREADS".to_string(), "{x}".to_string())]),
+        },
+    )]);
+    let parameters = BTreeMap::from([(
+        "x".to_string(),
+        Parameter {
+            sub: None,
+            values: Some(vec!["1".to_string(), "4".to_string()]),
+            range: None,
+            linspace: None,
+            logspace: None,
+            uniform: None,
+            loguniform: None,
+        },
+    )]);
+
+    let expanded = expand_parameters(inputs, &parameters, &[], &[]).unwrap();
+
+    let threads: Vec<&str> = expanded
+        .values()
+        .map(|input| input.env["THREADS"].as_str())
+        .collect();
+    assert_eq!(threads, vec!["1", "4"]);
 }
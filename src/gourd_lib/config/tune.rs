@@ -0,0 +1,78 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::constants::TUNE_BATCH_DEFAULT;
+use crate::constants::TUNE_METRIC_DEFAULT;
+use crate::constants::TUNE_REDUCTION_DEFAULT;
+
+/// How `gourd tune` searches for the best values of the parameters.
+///
+/// # Examples
+///
+/// ```toml
+/// [tune]
+/// strategy = "halving"
+/// budget = 300
+/// initial = 27
+/// metric = "cpu_time"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TuneConfig {
+    /// How to choose the next configurations of parameter values.
+    pub strategy: TuneStrategy,
+
+    /// The most runs that tuning creates, including the first batch.
+    pub budget: usize,
+
+    /// How many configurations of parameter values to run first.
+    pub initial: usize,
+
+    /// What to optimize: `wall_time`, `cpu_time` or a custom metric reported
+    /// by the programs.
+    #[serde(default = "TUNE_METRIC_DEFAULT")]
+    pub metric: String,
+
+    /// Whether the metric should be as low or as high as possible.
+    #[serde(default)]
+    pub goal: TuneGoal,
+
+    /// Successive halving keeps the best `1 / reduction` of the
+    /// configurations every round, and runs them `reduction` times as often.
+    #[serde(default = "TUNE_REDUCTION_DEFAULT")]
+    pub reduction: usize,
+
+    /// How many configurations the Parzen estimator proposes at every step.
+    #[serde(default = "TUNE_BATCH_DEFAULT")]
+    pub batch: usize,
+
+    /// The seed of the random choices, the same seed proposes the same
+    /// configurations for the same results.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// A way to choose the next configurations of parameter values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TuneStrategy {
+    /// Successive halving: run many configurations a few times, and the best
+    /// ones more and more often.
+    Halving,
+
+    /// A tree-structured Parzen estimator: propose configurations that look
+    /// like the best ones so far, and unlike the worst ones.
+    Tpe,
+}
+
+/// Whether the tuned metric is minimized or maximized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TuneGoal {
+    /// Lower is better.
+    #[default]
+    Minimize,
+
+    /// Higher is better.
+    Maximize,
+}
@@ -39,6 +39,68 @@ pub const PARAMETER_ESCAPE: &str = "param|";
 /// The prefix which will cause an argument to be interpreted as a subparameter.
 pub const SUB_PARAMETER_ESCAPE: &str = "subparam|";
 
+/// The prefix which will cause an input selector to select a group of inputs.
+pub const GROUP_ESCAPE: &str = "group|";
+
+/// The placeholder in arguments that is replaced by the seed of a run.
+pub const SEED_PLACEHOLDER: &str = "{seed}";
+
+/// The placeholder in arguments that is replaced by the path of the input file
+/// of a run.
+pub const INPUT_PLACEHOLDER: &str = "{input}";
+
+/// The placeholder in arguments that is replaced by the name of the input file
+/// of a run, without its extension.
+pub const INPUT_STEM_PLACEHOLDER: &str = "{input_stem}";
+
+/// The placeholder in arguments that is replaced by the working directory of a
+/// run.
+pub const WORK_DIR_PLACEHOLDER: &str = "{work_dir}";
+
+/// The placeholder in arguments that is replaced by the id of a run.
+pub const RUN_ID_PLACEHOLDER: &str = "{run_id}";
+
+/// The start of the placeholder in arguments that is replaced by a named file
+/// of the input of a run, as in `{file:graph}`.
+pub const FILE_PLACEHOLDER_PREFIX: &str = "{file:";
+
+/// The file in the working directory of a run that a generated input is
+/// written to.
+pub const GENERATED_INPUT_FILE: &str = "generated_input";
+
+/// The file in the working directory of a run that its verdict is cached in.
+pub const VERDICT_FILE: &str = "verdict";
+
+/// Whether the input file is passed to a program on its standard input by
+/// default.
+pub const STDIN_DEFAULT: fn() -> bool = || true;
+
+/// How many times a random search tries to sample a combination of parameter
+/// values per requested sample, before it concludes that there are no more
+/// combinations that it did not sample yet.
+pub const SEARCH_SAMPLE_ATTEMPTS: usize = 10;
+
+/// The metric that `gourd tune` optimizes by default.
+pub const TUNE_METRIC_DEFAULT: fn() -> String = || "wall_time".to_string();
+
+/// How much successive halving reduces the configurations every round by
+/// default.
+pub const TUNE_REDUCTION_DEFAULT: fn() -> usize = || 3;
+
+/// How many configurations the Parzen estimator proposes at once by default.
+pub const TUNE_BATCH_DEFAULT: fn() -> usize = || 4;
+
+/// How many candidate configurations the Parzen estimator compares for every
+/// configuration that it proposes.
+pub const TUNE_CANDIDATES: usize = 24;
+
+/// The fraction of the configurations that the Parzen estimator considers to
+/// be good.
+pub const TUNE_GOOD_FRACTION: f64 = 0.25;
+
+/// The internal representation of configurations created by `gourd tune`.
+pub const INTERNAL_TUNE: &str = "tune";
+
 /// The internal representation of inputs generated from a schema
 pub const INTERNAL_SCHEMA_INPUTS: &str = "schema";
 
@@ -54,6 +116,21 @@ pub const INTERNAL_PREFIX: &str = "_i_";
 /// The amount between refreshes of the status screen, in ms.
 pub const STATUS_REFRESH_PERIOD: Duration = Duration::from_millis(500);
 
+/// How long a program that exceeded its time limit has to exit after receiving
+/// `SIGTERM`, before the wrapper kills it.
+pub const LIMIT_KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// The interval at which the wrapper checks a program against its limits.
+pub const LIMIT_POLL_PERIOD: Duration = Duration::from_millis(100);
+
+/// How long a cancelled local run has to exit after receiving `SIGTERM`,
+/// before it is killed.
+pub const CANCEL_KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// The environment variable that tells a program where it can report custom
+/// metrics.
+pub const CUSTOM_METRICS_ENV: &str = "GOURD_METRICS_FILE";
+
 /// Create a style with a defined foreground color.
 pub const fn style_from_fg(color: AnsiColor) -> Style {
     Style::new().fg_color(Some(Color::Ansi(color)))
@@ -130,9 +207,6 @@ pub const RERUN_LIST_PROMPT_CUTOFF: usize = 15;
 /// Do we assume by default that runs with custom labels are failed runs?
 pub const RERUN_LABEL_BY_DEFAULT: fn() -> bool = || true;
 
-/// The maximal amount of tasks that gourd will schedule.
-pub const TASK_LIMIT: usize = 200;
-
 /// The logo of the application.
 pub const LOGO: &str = include_str!("../resources/logo.ascii");
 
@@ -1,123 +1,471 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
 
 use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
+use log::warn;
 
 use crate::bailc;
+use crate::config::constraints::Constraint;
+use crate::config::maps::canon_command;
 use crate::config::maps::canon_path;
 use crate::config::maps::expand_argument_globs;
 use crate::config::parameters::expand_parameters;
+use crate::config::parameters::sample_parameters;
 use crate::config::parameters::validate_parameters;
+use crate::config::search::search_samples;
+use crate::config::search::SearchConfig;
+use crate::config::search::SearchRng;
+use crate::config::Generator;
 use crate::config::Parameter;
 use crate::config::UserInput;
+use crate::constants::FILE_PLACEHOLDER_PREFIX;
+use crate::constants::SEED_PLACEHOLDER;
+use crate::ctx;
+use crate::experiment::programs::check_repetitions;
 use crate::experiment::FieldRef;
+use crate::experiment::GeneratedInput;
 use crate::experiment::InternalInput;
 use crate::experiment::Metadata;
 use crate::file_system::FileOperations;
 
 /// Convert a [`UserInput`] to a list of [`InternalInput`]s, expanding globs and
 /// fetching remote resources.
+///
+/// Parameters are expanded into the full grid, unless `search` asks for a
+/// random search, leaving out the combinations that violate the `constraints`.
+/// The parameters in a `zipped` group take their values together.
 pub fn expand_inputs(
     inp: &BTreeMap<String, UserInput>,
     parameters: &Option<BTreeMap<String, Parameter>>,
+    zipped: &[Vec<String>],
+    search: &Option<SearchConfig>,
+    constraints: &[String],
     fs: &impl FileOperations,
 ) -> Result<BTreeMap<FieldRef, InternalInput>> {
-    let mut initial = inp.clone();
     let mut out = BTreeMap::new();
 
-    // Expand globs in arguments.
-    initial = expand_argument_globs(&initial, fs)?;
+    // The constraints can also refer to the parameters of programs, they are
+    // checked together with those.
+    let constraints = constraints
+        .iter()
+        .map(|constraint| Constraint::parse(constraint))
+        .collect::<Result<Vec<_>>>()?;
 
-    // Expand parameters.
     if let Some(params) = parameters {
         validate_parameters(params)?;
-        initial = expand_parameters(initial, params)?;
     }
 
+    let mut rng = SearchRng::new(search.map_or(0, |search| search.seed));
+
+    // Every input is expanded on its own, to remember which input of the
+    // configuration the expanded inputs come from.
+    for (origin, user_input) in inp {
+        let initial = expand_input_parameters(
+            expand_argument_globs(&BTreeMap::from([(origin.clone(), user_input.clone())]), fs)?,
+            (parameters, zipped),
+            search,
+            &constraints,
+            &mut rng,
+        )?;
+
+        out.extend(expand_input_files(origin, initial, fs)?);
+    }
+
+    Ok(out)
+}
+
+/// Expands the parameters of inputs into the full grid, or samples them for a
+/// random search (Helper function).
+fn expand_input_parameters(
+    initial: BTreeMap<String, UserInput>,
+    (parameters, zipped): (&Option<BTreeMap<String, Parameter>>, &[Vec<String>]),
+    search: &Option<SearchConfig>,
+    constraints: &[Constraint],
+    rng: &mut SearchRng,
+) -> Result<BTreeMap<String, UserInput>> {
+    let Some(params) = parameters else {
+        return Ok(initial);
+    };
+
+    match search_samples(search)? {
+        Some(samples) => sample_parameters(initial, (params, zipped), constraints, samples, rng),
+        None => expand_parameters(initial, params, zipped, constraints),
+    }
+}
+
+/// Turns the inputs expanded from the input `origin` into [`InternalInput`]s,
+/// expanding globs, named files and fetching remote resources (Helper
+/// function).
+fn expand_input_files(
+    origin: &str,
+    initial: BTreeMap<String, UserInput>,
+    fs: &impl FileOperations,
+) -> Result<BTreeMap<FieldRef, InternalInput>> {
+    let mut out = BTreeMap::new();
+
     // Expand file input
     for (name, user) in initial {
-        match (user.file, user.glob, user.fetch) {
-            (Some(f), None, None) => {
-                out.insert(
+        check_repetitions(user.repetitions, &name)?;
+
+        if user.glob.is_some() && !user.files.is_empty() {
+            bailc!(
+                "Input {name:?} has both a glob and named files",;
+                "The files that the glob matches cannot be paired with the named files",;
+                "Replace the glob by a named file, named files are paired by their stem",
+            );
+        }
+
+        let named = expand_named_files(&user.files, fs)?;
+        let expected = expand_expected(&user.expected, fs)?;
+        let mut expanded = BTreeMap::new();
+
+        match (user.file, user.glob, user.fetch, user.generator) {
+            (Some(f), None, None, None) => {
+                expanded.insert(
                     name.clone(),
                     InternalInput {
                         input: Some(canon_path(&f, fs)?),
                         arguments: user.arguments.clone(),
+                        repetitions: user.repetitions,
+                        files: BTreeMap::new(),
+                        expected: None,
+                        generator: None,
                         metadata: Metadata {
                             glob_from: None,
                             is_fetched: false,
                             group: user.group,
+                            origin: Some(origin.to_string()),
                         },
+                        env: user.env.clone(),
                     },
                 );
             }
 
-            (None, Some(glob), None) => {
+            (None, Some(glob), None, None) => {
                 for glob in glob::glob(&glob)? {
                     let path = glob?;
 
                     if let Some(f) = path.file_stem() {
-                        out.insert(
+                        expanded.insert(
                             format!("{}_i_{}", &name, f.to_str().unwrap_or("")),
                             InternalInput {
                                 input: Some(canon_path(&path, fs)?),
                                 arguments: user.arguments.clone(),
+                                repetitions: user.repetitions,
+                                files: BTreeMap::new(),
+                                expected: None,
+                                generator: None,
                                 metadata: Metadata {
                                     glob_from: Some(name.clone()),
                                     is_fetched: false,
                                     group: user.group.clone(),
+                                    origin: Some(origin.to_string()),
                                 },
+                                env: user.env.clone(),
                             },
                         );
                     }
                 }
             }
 
-            (None, None, Some(fetched)) => {
+            (None, None, Some(fetched), None) => {
                 let name = format!("{name}_fetched");
 
-                out.insert(
+                expanded.insert(
                     name,
                     InternalInput {
                         input: Some(canon_path(&fetched.fetch(fs)?, fs)?),
                         arguments: user.arguments.clone(),
+                        repetitions: user.repetitions,
+                        files: BTreeMap::new(),
+                        expected: None,
+                        generator: None,
                         metadata: Metadata {
                             glob_from: None,
                             is_fetched: true,
                             group: user.group,
+                            origin: Some(origin.to_string()),
                         },
+                        env: user.env.clone(),
                     },
                 );
             }
-            (None, None, None) => {
+            (None, None, None, Some(generator)) => {
+                let (command, seeds) = check_generator(&name, &generator, fs)?;
+
+                for seed in seeds {
+                    expanded.insert(
+                        format!("{name}_seed_{seed}"),
+                        InternalInput {
+                            input: None,
+                            arguments: user.arguments.clone(),
+                            repetitions: user.repetitions,
+                            files: BTreeMap::new(),
+                            expected: None,
+                            generator: Some(GeneratedInput {
+                                command: command
+                                    .iter()
+                                    .map(|arg| arg.replace(SEED_PLACEHOLDER, &seed.to_string()))
+                                    .collect(),
+                                seed,
+                            }),
+                            metadata: Metadata {
+                                glob_from: None,
+                                is_fetched: false,
+                                group: user.group.clone(),
+                                origin: Some(origin.to_string()),
+                            },
+                            env: user.env.clone(),
+                        },
+                    );
+                }
+            }
+            (None, None, None, None) => {
                 ensure!(
-                    !user.arguments.is_empty(),
+                    !user.arguments.is_empty() || !user.files.is_empty(),
                     "Empty inputs are not allowed! \
-                ({name:?} has no file, glob, fetch or arguments specified)"
+                ({name:?} has no file, glob, fetch, files or arguments specified)"
                 );
-                out.insert(
+                expanded.insert(
                     name.clone(),
                     InternalInput {
                         input: None,
                         arguments: user.arguments.clone(),
+                        repetitions: user.repetitions,
+                        files: BTreeMap::new(),
+                        expected: None,
+                        generator: None,
                         metadata: Metadata {
                             glob_from: None,
                             is_fetched: false,
                             group: user.group,
+                            origin: Some(origin.to_string()),
                         },
+                        env: user.env.clone(),
                     },
                 );
             }
             _ => {
                 bailc!(
                     "Wrong number of file sources specified.",;
-                    "Input {name:?} has more than one file/glob/fetch specified",;
-                    "Split this input into one for each file/glob/fetch",
+                    "Input {name:?} has more than one file/glob/fetch/generator specified",;
+                    "Split this input into one for each file/glob/fetch/generator",
                 );
             }
         }
+
+        for (expanded_name, input) in expanded {
+            for (stem, files) in &named {
+                let mut input = input.clone();
+                input.files.clone_from(files);
+
+                let expanded_name = match stem {
+                    Some(stem) => {
+                        input.metadata.glob_from.get_or_insert(name.clone());
+                        format!("{expanded_name}_i_{stem}")
+                    }
+                    None => expanded_name.clone(),
+                };
+
+                input.expected = match &expected {
+                    Expected::File(file) => Some(file.clone()),
+                    Expected::ByStem(files) => {
+                        let stem = stem.clone().or_else(|| {
+                            let file = input.input.as_ref()?;
+                            Some(file.file_stem()?.to_string_lossy().to_string())
+                        });
+
+                        let file = stem.and_then(|stem| files.get(&stem)).cloned();
+
+                        if file.is_none() {
+                            warn!("There is no expected output for input {expanded_name:?}");
+                        }
+
+                        file
+                    }
+                    Expected::None => None,
+                };
+
+                out.insert(expanded_name, input);
+            }
+        }
     }
 
     Ok(out)
 }
+
+/// The expected output of the inputs expanded from one input.
+#[derive(Debug)]
+enum Expected {
+    /// There is no expected output.
+    None,
+
+    /// All inputs have the same expected output.
+    File(PathBuf),
+
+    /// The expected output of an input is the file with the same stem as its
+    /// input file.
+    ByStem(BTreeMap<String, PathBuf>),
+}
+
+/// Finds the files of the `expected` output of an input (Helper function).
+fn expand_expected(expected: &Option<String>, fs: &impl FileOperations) -> Result<Expected> {
+    let Some(expected) = expected else {
+        return Ok(Expected::None);
+    };
+
+    if !expected.contains(['*', '?', '[']) {
+        return Ok(Expected::File(canon_path(Path::new(expected), fs)?));
+    }
+
+    let mut files = BTreeMap::new();
+
+    for path in glob::glob(expected).with_context(ctx!(
+        "The expected output is not a valid glob: {expected}", ;
+        "",
+    ))? {
+        let path = path?;
+
+        if let Some(stem) = path.file_stem() {
+            files.insert(stem.to_string_lossy().to_string(), canon_path(&path, fs)?);
+        }
+    }
+
+    Ok(Expected::ByStem(files))
+}
+
+/// Checks the generator of the input called `name`, and returns its command
+/// with the path of the generator made absolute, together with the seeds it
+/// generates inputs for (Helper function).
+///
+/// The command is run in the working directory of the run, so a generator that
+/// is a file is found by its absolute path. Other commands are looked up in the
+/// `PATH`.
+fn check_generator(
+    name: &str,
+    generator: &Generator,
+    fs: &impl FileOperations,
+) -> Result<(Vec<String>, RangeInclusive<u64>)> {
+    if generator.command.is_empty() {
+        bailc!(
+            "The generator of input {name:?} has no command",;
+            "The command is the generator followed by its arguments",;
+            "Set `command`, for example to [\"./gen\", \"--seed\", \"{{seed}}\"]",
+        );
+    }
+
+    if generator.count == 0 {
+        bailc!(
+            "The generator of input {name:?} generates no inputs",;
+            "`count` is the amount of inputs to generate",;
+            "Set `count` to at least 1",
+        );
+    }
+
+    let Some(last) = u64::try_from(generator.count - 1)
+        .ok()
+        .and_then(|count| generator.seed_start.checked_add(count))
+    else {
+        bailc!(
+            "The generator of input {name:?} has seeds beyond {}", u64::MAX;
+            "The seeds count up from `seed_start`, once for every input",;
+            "Lower `seed_start` or `count`",
+        );
+    };
+
+    Ok((
+        canon_command(&generator.command, fs)?,
+        generator.seed_start..=last,
+    ))
+}
+
+/// Expands the named `files` of an input into the sets of files that the input
+/// is instantiated with (Helper function).
+///
+/// Files that are globs are paired by their stem: there is a set for every
+/// stem that all globs match, together with the files that are not globs.
+#[allow(clippy::type_complexity)]
+fn expand_named_files(
+    files: &BTreeMap<String, String>,
+    fs: &impl FileOperations,
+) -> Result<Vec<(Option<String>, BTreeMap<String, PathBuf>)>> {
+    let mut fixed = BTreeMap::new();
+    let mut globbed: BTreeMap<&String, BTreeMap<String, PathBuf>> = BTreeMap::new();
+
+    for (name, file) in files {
+        if !file.contains(['*', '?', '[']) {
+            fixed.insert(name.clone(), canon_path(Path::new(file), fs)?);
+            continue;
+        }
+
+        let matches = globbed.entry(name).or_default();
+
+        for path in glob::glob(file).with_context(ctx!(
+            "The file {name:?} is not a valid glob: {file}", ;
+            "",
+        ))? {
+            let path = path?;
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            if let Some(other) = matches.insert(stem.clone(), canon_path(&path, fs)?) {
+                bailc!(
+                    "The glob of file {name:?} matches more than one file with the stem {stem:?}",;
+                    "Both {other:?} and {path:?} match, so they cannot be paired",;
+                    "Make the glob {file:?} more specific",
+                );
+            }
+        }
+    }
+
+    let Some(stems) = globbed
+        .values()
+        .map(|matches| matches.keys().cloned().collect::<BTreeSet<_>>())
+        .reduce(|all, stems| &all & &stems)
+    else {
+        return Ok(vec![(None, fixed)]);
+    };
+
+    if stems.is_empty() {
+        bailc!(
+            "The globs of the named files have no stem in common",;
+            "Files are paired by their name without extension",;
+            "Check that the globs match files with the same stems",
+        );
+    }
+
+    for (name, matches) in &globbed {
+        for stem in matches.keys().filter(|stem| !stems.contains(*stem)) {
+            warn!("The {name:?} file with stem {stem:?} has no files to pair with, it is not used");
+        }
+    }
+
+    Ok(stems
+        .into_iter()
+        .map(|stem| {
+            let mut set = fixed.clone();
+
+            for (name, matches) in &globbed {
+                set.insert((*name).clone(), matches[&stem].clone());
+            }
+
+            (Some(stem), set)
+        })
+        .collect())
+}
+
+/// The names of the files that an argument refers to with `{file:<name>}`.
+pub fn referenced_files(arg: &str) -> Vec<&str> {
+    arg.split(FILE_PLACEHOLDER_PREFIX)
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name)
+        .collect()
+}
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use anyhow::Context;
@@ -10,11 +11,15 @@ use chrono::Local;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::config::compare::Agreement;
+use crate::config::compare::Comparison;
 use crate::config::slurm::ResourceLimits;
 use crate::config::slurm::SlurmConfig;
 use crate::config::Label;
+use crate::constants::STDIN_DEFAULT;
 use crate::ctx;
 use crate::experiment::labels::Labels;
+use crate::experiment::tuning::Tuning;
 use crate::file_system::FileOperations;
 
 /// Dealing with [`UserInput`]s and [`InternalInput`]s
@@ -26,6 +31,9 @@ pub mod labels;
 /// Dealing with [`UserProgram`]s and [`InternalProgram`]s
 pub mod programs;
 
+/// The progress of tuning the parameters of an experiment.
+pub mod tuning;
+
 /// A string referencing a [`UserProgram`], [`InternalProgram`], [`UserInput`]
 /// or [`InternalInput`].
 pub type FieldRef = String;
@@ -39,6 +47,26 @@ pub struct InternalInput {
     /// Command line arguments to be passed to the executable
     pub arguments: Vec<String>,
 
+    /// How many times every program runs on this input, if overridden.
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
+    /// The named files of this input.
+    #[serde(default)]
+    pub files: BTreeMap<String, PathBuf>,
+
+    /// The output that the programs should give on this input.
+    #[serde(default)]
+    pub expected: Option<PathBuf>,
+
+    /// The command that generates the input file, if it is generated.
+    #[serde(default)]
+    pub generator: Option<GeneratedInput>,
+
+    /// The environment variables of this input.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
     /// Additional data for this input
     pub metadata: Metadata,
 }
@@ -55,6 +83,10 @@ pub struct Metadata {
 
     /// The group this item belongs to.
     pub group: Option<String>,
+
+    /// The input in the configuration that this was created from.
+    #[serde(default)]
+    pub origin: Option<String>,
 }
 
 /// The internal representation of a [`UserProgram`]
@@ -63,22 +95,58 @@ pub struct InternalProgram {
     /// The name given to this program by the user.
     pub name: String,
 
+    /// The program in the configuration that this was created from, which
+    /// differs from the name if the program uses parameters.
+    #[serde(default)]
+    pub origin: Option<String>,
+
     /// The [`Executable`] of this program (absolute path to it)
     pub binary: PathBuf,
 
     /// An executable afterscript to run on the output of this program
     pub afterscript: Option<PathBuf>,
 
-    /// The limits to be applied on executions of this program
-    pub limits: ResourceLimits,
+    /// The limits to be applied on executions of this program, if they were
+    /// configured.
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
 
     /// The command line arguments to be passed to all executions of this
     /// program
     pub arguments: Vec<String>,
 
+    /// How many times this program runs on every input, if not overridden by
+    /// the input.
+    #[serde(default)]
+    pub repetitions: Option<usize>,
+
     /// This program runs on the output of our program,
     /// a reference to the other program's name.
     pub next: Vec<usize>,
+
+    /// If not empty, the selectors of the inputs that this program runs on.
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// The selectors of the inputs that this program does not run on.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Whether the input file is passed on the standard input of this program.
+    #[serde(default = "STDIN_DEFAULT")]
+    pub stdin: bool,
+
+    /// The command that prepares the working directory of a run, if any.
+    #[serde(default)]
+    pub setup: Vec<String>,
+
+    /// The command that cleans up the working directory of a run, if any.
+    #[serde(default)]
+    pub teardown: Vec<String>,
+
+    /// The environment variables of this program, including the global ones.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 /// The input for a [`Run`], exactly as will be passed to the wrapper for
@@ -93,6 +161,30 @@ pub struct RunInput {
     /// Holds the concatenation of [`UserProgram`] specified arguments and
     /// [`UserInput`] arguments.
     pub arguments: Vec<String>,
+
+    /// The named files of the input, that replace the `{file:<name>}`
+    /// placeholders in the arguments.
+    #[serde(default)]
+    pub files: BTreeMap<String, PathBuf>,
+
+    /// The command that generates the `file` right before the run starts.
+    #[serde(default)]
+    pub generator: Option<GeneratedInput>,
+
+    /// The environment variables of the input, that replace those of the
+    /// program.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// An input file that is generated by a command with a seed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneratedInput {
+    /// The command and its arguments, with the seed filled in.
+    pub command: Vec<String>,
+
+    /// The seed that the input is generated with.
+    pub seed: u64,
 }
 
 /// Describes a matching between an algorithm and an input.
@@ -114,6 +206,13 @@ pub struct Run {
     /// The path to the metrics file.
     pub metrics_path: PathBuf,
 
+    /// The path to the file where the program can report custom metrics, if
+    /// the run was created after custom metrics were supported.
+    pub custom_metrics_path: Option<PathBuf>,
+
+    /// The path to the resource usage samples, if sampling is enabled.
+    pub samples_path: Option<PathBuf>,
+
     /// The path to afterscript output, if there is an afterscript.
     pub afterscript_output_path: Option<PathBuf>,
 
@@ -123,8 +222,12 @@ pub struct Run {
     /// Slurm job id, if ran on slurm
     pub slurm_id: Option<String>,
 
-    /// Resource limits applied to this run
-    pub limits: ResourceLimits,
+    /// Resource limits applied to this run, if they were configured.
+    ///
+    /// Locally only configured limits are enforced, while Slurm falls back to
+    /// the default limits.
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
 
     /// If this job has been rerun, a reference to the new one.
     pub rerun: Option<usize>,
@@ -137,6 +240,15 @@ pub struct Run {
 
     /// The group this run belongs to.
     pub group: Option<String>,
+
+    /// Which repetition of its program and input this run is, if they are
+    /// repeated.
+    #[serde(default)]
+    pub repetition: Option<usize>,
+
+    /// The seed that replaces `{seed}` in the arguments of this run.
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 /// An enum to distinguish the run context.
@@ -199,6 +311,35 @@ pub struct Experiment {
     /// The input groups present in this experiment.
     pub groups: Vec<String>,
 
+    /// How often the wrapper samples the resource usage of a run, if at all.
+    #[serde(default)]
+    pub sampling_interval: Option<Duration>,
+
+    /// Whether the wrapper collects performance counters of the runs.
+    #[serde(default)]
+    pub performance_counters: bool,
+
+    /// The seed of the first repetition of every run, if seeds are used.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// How the output of runs is compared with the expected output.
+    #[serde(default)]
+    pub compare: Comparison,
+
+    /// How the programs are checked to agree on the output of every input, if
+    /// they are.
+    #[serde(default)]
+    pub agreement: Option<Agreement>,
+
+    /// Whether runs start from an empty environment.
+    #[serde(default)]
+    pub clear_env: bool,
+
+    /// The progress of `gourd tune`, if this experiment is tuned.
+    #[serde(default)]
+    pub tuning: Option<Tuning>,
+
     // last in the struct so that the lockfile has these at the bottom
     /// The pairings of program-input for this experiment.
     pub runs: Vec<Run>,
@@ -210,6 +351,25 @@ impl Experiment {
         self.home.join(format!("{}.lock", self.seq))
     }
 
+    /// The file in which the supervisor of a detached local experiment records
+    /// its pid.
+    pub fn supervisor_pid_file(&self) -> PathBuf {
+        self.home.join(format!("{}.supervisor.pid", self.seq))
+    }
+
+    /// The file in which the local runner records the pid of the wrapper of a
+    /// run while it runs.
+    pub fn wrapper_pid_file(&self, run_id: usize) -> PathBuf {
+        self.home.join(format!("{}_pids/{}.pid", self.seq, run_id))
+    }
+
+    /// The file in which the wrapper of a local run records the pid of the
+    /// program while it runs.
+    pub fn program_pid_file(&self, run_id: usize) -> PathBuf {
+        self.home
+            .join(format!("{}_pids/{}.program.pid", self.seq, run_id))
+    }
+
     /// Save the experiment to a file with its timestamp.
     pub fn save_to(&self, folder: &Path, fs: &impl FileOperations) -> Result<PathBuf> {
         let saving_path = folder.join(format!("{}.lock", self.seq));
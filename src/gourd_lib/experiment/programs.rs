@@ -1,27 +1,95 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 use anyhow::Context;
 use anyhow::Result;
+use glob::Pattern;
+use log::warn;
 
 use crate::bailc;
 use crate::config::fetching::fetch_git;
+use crate::config::maps::canon_command;
 use crate::config::maps::canon_path;
+use crate::config::parameters::expand_program_parameters;
+use crate::config::parameters::input_parameters;
+use crate::config::parameters::parse_constraints;
+use crate::config::parameters::program_parameters;
+use crate::config::parameters::validate_parameters;
+use crate::config::search::search_samples;
+use crate::config::search::SearchRng;
+use crate::config::slurm::ResourceLimits;
 use crate::config::Config;
 use crate::config::UserProgram;
+use crate::constants::GROUP_ESCAPE;
+use crate::ctx;
+use crate::experiment::InternalInput;
 use crate::experiment::InternalProgram;
 use crate::file_system::FileOperations;
 
 /// Convert a [`UserProgram`] to a list of [`InternalProgram`]s,
 /// expanding globs and fetching remote resources.
+///
+/// A program that uses parameters is expanded into one program for every
+/// combination of their values.
 pub fn expand_programs(
     prog: &BTreeMap<String, UserProgram>,
     conf: &Config,
     fs: &impl FileOperations,
 ) -> Result<Vec<InternalProgram>> {
     let mut out = Vec::new();
-    let mut mapper = BTreeMap::new();
+    let mut mapper: BTreeMap<&String, Vec<usize>> = BTreeMap::new();
+
+    check_repetitions(conf.repetitions, "the configuration")?;
+
+    let global = conf.parameters.clone().unwrap_or_default();
+    validate_parameters(&global)?;
+
+    // Constraints can refer to the parameters of any program.
+    let mut known = global.clone();
+    for user in prog.values() {
+        let own = user.parameters.clone().unwrap_or_default();
+        validate_parameters(&own)?;
+        known.extend(own);
+    }
+
+    let constraints = parse_constraints(&conf.constraints, &known)?;
+
+    // The programs sample from a generator of their own, so that their values
+    // do not repeat those sampled for the inputs.
+    let samples = search_samples(&conf.search)?;
+    let mut rng = SearchRng::new(conf.search.map_or(0, |search| search.seed)).split();
+
+    // Every program runs on every input, so a global parameter that both use
+    // would pair every value on one side with every value on the other.
+    let mut used_by_inputs = BTreeSet::new();
+    for input in conf.inputs.values() {
+        used_by_inputs.extend(input_parameters(input, &global)?);
+    }
+
+    for (original, user) in prog {
+        for child in &user.next {
+            if !prog.contains_key(child) {
+                bailc!(
+                    "Incorrect program dependency: {}", child;
+                    "Program {child} runs on {original}, but there's no program called {child}!",;
+                    "Please make sure all programs exist and spelling is correct",
+                );
+            }
+        }
+
+        check_repetitions(user.repetitions, original)?;
+        check_selectors(&user.include, original)?;
+        check_selectors(&user.exclude, original)?;
+
+        let postprocessing = prog.values().any(|other| other.next.contains(original));
+
+        if postprocessing && !(user.include.is_empty() && user.exclude.is_empty()) {
+            warn!(
+                "Program {original} postprocesses the output of other programs, \
+                so it ignores its include and exclude selectors"
+            );
+        }
 
-    for (name, user) in prog {
         let file = canon_path(
             &match (&user.binary, &user.fetch, &user.git) {
                 (Some(f), None, None) => f.clone(),
@@ -31,47 +99,166 @@ pub fn expand_programs(
                 _ => {
                     bailc!(
                         "Wrong number of file sources specified.",;
-                        "Program {name} does not have 1 binary/fetch specified",;
+                        "Program {original} does not have 1 binary/fetch specified",;
                         "Specify exactly one binary source per program.",
                     );
                 }
             },
             fs,
         )?;
-        let limits = user
-            .resource_limits
-            .unwrap_or(conf.resource_limits.unwrap_or_default());
 
-        for child in &user.next {
-            if !prog.contains_key(child) {
-                bailc!(
-                    "Incorrect program dependency: {}", child;
-                    "Program {child} runs on {name}, but there's no program called {child}!",;
-                    "Please make sure all programs exist and spelling is correct",
-                );
-            }
+        let afterscript = user
+            .afterscript
+            .clone()
+            .map(|p| canon_path(&p, fs))
+            .transpose()?;
+
+        let setup = canon_command(&user.setup, fs)?;
+        let teardown = canon_command(&user.teardown, fs)?;
+
+        // The variables of the program replace the global ones, before the
+        // parameters in either are expanded.
+        let mut user = user.clone();
+        user.env = conf.env.clone().into_iter().chain(user.env).collect();
+
+        let own = user.parameters.clone().unwrap_or_default();
+        let mut visible = global.clone();
+        visible.extend(own.clone());
+
+        if let Some(shared) = program_parameters(&user, &visible)?
+            .into_iter()
+            .find(|name| used_by_inputs.contains(name) && !own.contains_key(name))
+        {
+            bailc!(
+                "Parameter {shared} is used by program {original} and by an input",;
+                "The programs run on every input, so every value in the program \
+                would be paired with every value in the input",;
+                "Use a separate parameter for either, or give program {original} \
+                its own parameter {shared}",
+            );
         }
 
-        mapper.insert(name, out.len());
-        out.push(InternalProgram {
-            name: name.to_string(),
-            binary: file,
-            afterscript: user
-                .afterscript
-                .clone()
-                .map(|p| canon_path(&p, fs))
-                .transpose()?,
-            limits,
-            arguments: user.arguments.clone(),
-            next: Vec::new(),
-        });
+        let expanded = if conf.parameters.is_none() && user.parameters.is_none() {
+            vec![(original.clone(), user.clone())]
+        } else {
+            expand_program_parameters(
+                original,
+                &user,
+                (&global, &conf.zip),
+                &constraints,
+                samples.map(|samples| (samples, &mut rng)),
+            )?
+        };
+
+        for (name, expanded_user) in &expanded {
+            let limits = match &expanded_user.resource_limits {
+                Some(limits) => Some(limits.resolve().with_context(ctx!(
+                    "In the resource limits of program {original}", ;
+                    "",
+                ))?),
+                None => conf
+                    .resource_limits
+                    .map(ResourceLimits::checked)
+                    .transpose()?,
+            };
+
+            mapper.entry(original).or_default().push(out.len());
+            out.push(InternalProgram {
+                name: name.to_string(),
+                origin: Some(original.clone()),
+                binary: file.clone(),
+                afterscript: afterscript.clone(),
+                limits,
+                arguments: expanded_user.arguments.clone(),
+                repetitions: user.repetitions.or(conf.repetitions),
+                next: Vec::new(),
+                include: user.include.clone(),
+                exclude: user.exclude.clone(),
+                stdin: user.stdin,
+                setup: setup.clone(),
+                teardown: teardown.clone(),
+                env: expanded_user.env.clone(),
+            });
+        }
     }
 
-    for out_prog in out.iter_mut() {
-        for next_norm in &prog[&out_prog.name].next {
-            out_prog.next.push(mapper[next_norm]);
+    for (original, user) in prog {
+        let next: Vec<usize> = user
+            .next
+            .iter()
+            .flat_map(|next_norm| mapper.get(next_norm).into_iter().flatten().copied())
+            .collect();
+
+        // The constraints might have excluded every version of a program.
+        for program in mapper.get(original).into_iter().flatten() {
+            out[*program].next.clone_from(&next);
         }
     }
 
     Ok(out)
 }
+
+impl InternalProgram {
+    /// The name of the program in the configuration that this was created
+    /// from.
+    pub fn original_name(&self) -> &str {
+        self.origin.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether this program runs on the input called `name`, according to its
+    /// include and exclude selectors.
+    pub fn runs_on(&self, name: &str, input: &InternalInput) -> bool {
+        let selected = |selector: &String| selects(selector, name, input);
+
+        (self.include.is_empty() || self.include.iter().any(selected))
+            && !self.exclude.iter().any(selected)
+    }
+}
+
+/// Whether an input selector selects the input called `name`.
+///
+/// A selector is either `group|<group name>`, or a glob pattern matched
+/// against the name of the input in the configuration and its expanded name.
+fn selects(selector: &str, name: &str, input: &InternalInput) -> bool {
+    if let Some(group) = selector.strip_prefix(GROUP_ESCAPE) {
+        return input.metadata.group.as_deref() == Some(group);
+    }
+
+    Pattern::new(selector).is_ok_and(|pattern| {
+        pattern.matches(name)
+            || input
+                .metadata
+                .origin
+                .as_ref()
+                .is_some_and(|origin| pattern.matches(origin))
+    })
+}
+
+/// Check that the input selectors of a program are valid.
+fn check_selectors(selectors: &[String], program: &str) -> Result<()> {
+    for selector in selectors {
+        if selector.starts_with(GROUP_ESCAPE) {
+            continue;
+        }
+
+        Pattern::new(selector).with_context(ctx!(
+            "Invalid input selector {selector:?} of program {program}", ;
+            "Select inputs by name, by a glob pattern of names, or as {GROUP_ESCAPE}<group name>",
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Check that something that is repeated runs at least once.
+pub(crate) fn check_repetitions(repetitions: Option<usize>, name: &str) -> Result<()> {
+    if repetitions == Some(0) {
+        bailc!(
+            "Invalid amount of repetitions for {name}",;
+            "Every program has to run at least once on every input",;
+            "Set `repetitions` to at least 1, or remove it to run once",
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config::parameters::assign_parameters;
+use crate::config::tune::TuneConfig;
+use crate::config::Parameter;
+use crate::config::UserInput;
+use crate::constants::INTERNAL_PREFIX;
+use crate::constants::INTERNAL_TUNE;
+use crate::experiment::inputs::expand_inputs;
+use crate::experiment::FieldRef;
+use crate::experiment::InternalInput;
+use crate::file_system::FileOperations;
+
+/// The progress of `gourd tune` on an experiment.
+///
+/// Tuning adds the runs of every configuration of parameter values that it
+/// tries to the experiment, this keeps track of which runs belong to which
+/// configuration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Tuning {
+    /// How the parameters are tuned.
+    pub config: TuneConfig,
+
+    /// The parameters, with the values of `range`, `linspace` and `logspace`
+    /// generated.
+    pub parameters: BTreeMap<String, Parameter>,
+
+    /// The groups of parameters that take their values together.
+    pub zip: Vec<Vec<String>>,
+
+    /// The constraints that every configuration satisfies.
+    pub constraints: Vec<String>,
+
+    /// The inputs that use the parameters, every configuration runs its own
+    /// copy of them.
+    pub templates: BTreeMap<String, UserInput>,
+
+    /// The configurations of parameter values tried so far.
+    pub configurations: Vec<Configuration>,
+
+    /// How many runs tuning has created so far.
+    pub spent: usize,
+
+    /// Whether tuning has finished.
+    pub finished: bool,
+}
+
+/// One configuration of parameter values that is tried by `gourd tune`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Configuration {
+    /// The value of every parameter, and of every subparameter as `x.a`.
+    pub values: BTreeMap<String, String>,
+
+    /// The inputs created for this configuration.
+    pub inputs: Vec<FieldRef>,
+
+    /// The runs of the programs on these inputs, without the runs that
+    /// postprocess their output.
+    pub runs: Vec<usize>,
+
+    /// How many times every program runs on every input, as a multiple of
+    /// their repetitions.
+    pub repetitions: usize,
+
+    /// The round of successive halving that this configuration reached.
+    pub round: usize,
+}
+
+impl Tuning {
+    /// The inputs of a configuration: a copy of every template, named after
+    /// the template and the `index` of the configuration, with the parameters
+    /// replaced by their `values`.
+    pub fn configuration_inputs(
+        &self,
+        index: usize,
+        values: &BTreeMap<String, String>,
+        fs: &impl FileOperations,
+    ) -> Result<BTreeMap<FieldRef, InternalInput>> {
+        let mut inputs = BTreeMap::new();
+
+        for (name, template) in &self.templates {
+            let copy = BTreeMap::from([(
+                format!("{name}_tune_{index}{INTERNAL_PREFIX}{INTERNAL_TUNE}"),
+                assign_parameters(template, values)?,
+            )]);
+
+            for (expanded, mut input) in expand_inputs(&copy, &None, &[], &None, &[], fs)? {
+                // Selectors refer to the template by its own name.
+                input.metadata.origin = Some(name.clone());
+                inputs.insert(expanded, input);
+            }
+        }
+
+        Ok(inputs)
+    }
+}
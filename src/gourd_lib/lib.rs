@@ -19,6 +19,8 @@ pub mod error;
 /// Constant values.
 pub mod constants;
 
+/// Telling apart the processes that local experiments record.
+pub mod process;
 /// Resource fetching helpers.
 pub mod resources;
 
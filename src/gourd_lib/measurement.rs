@@ -2,16 +2,24 @@
 // Licensed under MIT.
 // It exists because we have to modify the behaviour of it.
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::path::Path;
 use std::time::Duration;
 
+use anyhow::Context;
+use anyhow::Result;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::bailc;
 use crate::constants::NAME_STYLE;
+use crate::ctx;
 
 /// The metrics of running a program.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+// Only ever (de)serialized one at a time, so the size does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type")]
 pub enum Metrics {
     /// The metrics have not been calculated yet.
@@ -19,17 +27,298 @@ pub enum Metrics {
 
     /// The measurement has been finished.
     Done(Measurement),
+
+    /// The wrapper could not run the program.
+    WrapperFailed {
+        /// Why the wrapper failed.
+        reason: String,
+    },
+
+    /// The run was cancelled before it finished.
+    Cancelled,
 }
 
 /// This structure contains the measurements for one run of the binary.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Measurement {
     /// Interval of wall time.
     pub wall_micros: Duration,
-    /// The exit code of the invoked program.
-    pub exit_code: i32,
+    /// How the invoked program terminated.
+    ///
+    /// Metrics from before signals were recorded only have an `exit_code`.
+    #[serde(alias = "exit_code", deserialize_with = "deserialize_termination")]
+    pub termination: Termination,
     /// The rusage of the invoked program.
     pub rusage: Option<RUsage>,
+    /// The resource limit that the program was stopped for exceeding, if any.
+    #[serde(default)]
+    pub limit_exceeded: Option<LimitExceeded>,
+    /// The performance counters of the program, if they were collected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters: Option<Counters>,
+    /// The metrics that the program reported itself.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_metrics: BTreeMap<String, f64>,
+    /// How the setup command terminated, if the program has one.
+    ///
+    /// When the setup failed the program did not run, and `termination` is
+    /// that of the setup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<Termination>,
+    /// How the teardown command terminated, if the program has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub teardown: Option<Termination>,
+}
+
+impl Measurement {
+    /// Check if the setup command failed, so that the program did not run.
+    pub fn setup_failed(&self) -> bool {
+        self.setup.is_some_and(|setup| !setup.is_success())
+    }
+}
+
+/// Parse the custom metrics that a program reported.
+///
+/// Every line of the report holds one metric as `name = value`, where the
+/// value is a number. Empty lines and lines starting with `#` are ignored.
+/// When a metric is reported more than once, the last value is kept.
+pub fn parse_custom_metrics(report: &str) -> Result<BTreeMap<String, f64>> {
+    let mut metrics = BTreeMap::new();
+
+    for (number, line) in (1..).zip(report.lines()) {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            bailc!(
+                "Line {number} of the custom metrics is not a metric: {line:?}", ;
+                "", ;
+                "Metrics have to be reported as name = value",
+            );
+        };
+
+        let name = name.trim();
+
+        if name.is_empty() {
+            bailc!(
+                "Line {number} of the custom metrics has no name: {line:?}", ;
+                "", ;
+                "Metrics have to be reported as name = value",
+            );
+        }
+
+        let Ok(value) = value.trim().parse::<f64>() else {
+            bailc!(
+                "The value of the custom metric {name:?} is not a number: {:?}", value.trim();
+                "", ;
+                "Only numeric metrics are supported",
+            );
+        };
+
+        metrics.insert(name.to_string(), value);
+    }
+
+    Ok(metrics)
+}
+
+/// The way in which a program terminated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "type")]
+pub enum Termination {
+    /// The program exited by itself.
+    Exited {
+        /// The exit code of the program.
+        code: i32,
+    },
+
+    /// The program was killed by a signal.
+    Signalled {
+        /// The number of the signal.
+        signal: i32,
+
+        /// Whether the program dumped its core.
+        core_dumped: bool,
+    },
+}
+
+/// A recorded [Termination], or the bare exit code that was recorded before
+/// signals were.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecordedTermination {
+    /// The way in which the program terminated.
+    Termination(Termination),
+
+    /// The exit code of the program.
+    ExitCode(i32),
+}
+
+/// Deserialize a [Termination], also from a bare exit code.
+fn deserialize_termination<'de, D>(deserializer: D) -> Result<Termination, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match RecordedTermination::deserialize(deserializer)? {
+        RecordedTermination::Termination(termination) => termination,
+        RecordedTermination::ExitCode(code) => Termination::Exited { code },
+    })
+}
+
+impl Termination {
+    /// The exit code of the program, if it exited by itself.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Termination::Exited { code } => Some(*code),
+            Termination::Signalled { .. } => None,
+        }
+    }
+
+    /// The signal that killed the program, if any.
+    pub fn signal(&self) -> Option<i32> {
+        match self {
+            Termination::Exited { .. } => None,
+            Termination::Signalled { signal, .. } => Some(*signal),
+        }
+    }
+
+    /// Check if the program exited with exit code 0.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Termination::Exited { code: 0 })
+    }
+}
+
+/// The name of a signal, for example `SIGSEGV`.
+///
+/// Signals that are not known are shown by their number.
+pub fn signal_name(signal: i32) -> String {
+    #[cfg(unix)]
+    let name = match signal {
+        libc::SIGHUP => Some("SIGHUP"),
+        libc::SIGINT => Some("SIGINT"),
+        libc::SIGQUIT => Some("SIGQUIT"),
+        libc::SIGILL => Some("SIGILL"),
+        libc::SIGTRAP => Some("SIGTRAP"),
+        libc::SIGABRT => Some("SIGABRT"),
+        libc::SIGBUS => Some("SIGBUS"),
+        libc::SIGFPE => Some("SIGFPE"),
+        libc::SIGKILL => Some("SIGKILL"),
+        libc::SIGUSR1 => Some("SIGUSR1"),
+        libc::SIGSEGV => Some("SIGSEGV"),
+        libc::SIGUSR2 => Some("SIGUSR2"),
+        libc::SIGPIPE => Some("SIGPIPE"),
+        libc::SIGALRM => Some("SIGALRM"),
+        libc::SIGTERM => Some("SIGTERM"),
+        libc::SIGXCPU => Some("SIGXCPU"),
+        libc::SIGXFSZ => Some("SIGXFSZ"),
+        libc::SIGSYS => Some("SIGSYS"),
+        _ => None,
+    };
+    #[cfg(not(unix))]
+    let name: Option<&str> = None;
+
+    name.map(String::from)
+        .unwrap_or_else(|| format!("signal {signal}"))
+}
+
+/// A resource limit that caused the wrapper to stop a program.
+///
+/// These mirror the Slurm failure states `TIMEOUT` and `OUT_OF_MEMORY`, so
+/// that local runs report the same failure classes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LimitExceeded {
+    /// The program ran for longer than its time limit.
+    Time,
+
+    /// The program used more memory than it was allowed to.
+    Memory,
+}
+
+/// Hardware and software performance counters of a program.
+///
+/// A counter is `None` when the system did not allow it to be measured.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counters {
+    /// Instructions retired.
+    pub instructions: Option<u64>,
+    /// CPU cycles.
+    pub cycles: Option<u64>,
+    /// Cache misses, usually of the last level cache.
+    pub cache_misses: Option<u64>,
+    /// Mispredicted branches.
+    pub branch_misses: Option<u64>,
+    /// Page faults.
+    pub page_faults: Option<u64>,
+}
+
+impl Counters {
+    /// The names and values of all counters, in a fixed order.
+    pub fn named(&self) -> [(&'static str, Option<u64>); 5] {
+        [
+            ("instructions", self.instructions),
+            ("cycles", self.cycles),
+            ("cache misses", self.cache_misses),
+            ("branch misses", self.branch_misses),
+            ("page faults", self.page_faults),
+        ]
+    }
+}
+
+/// One sample of the resource usage of a running program.
+///
+/// Every sample covers the program and all of its child processes that were
+/// alive at that moment.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sample {
+    /// Milliseconds since the program was started.
+    pub millis: u64,
+    /// The amount of processes.
+    pub processes: u64,
+    /// The amount of threads.
+    pub threads: u64,
+    /// The resident set size, in kilobytes.
+    pub rss_kb: u64,
+    /// The cpu time used so far, in milliseconds.
+    pub cpu_millis: u64,
+    /// The bytes read from storage so far.
+    pub read_bytes: u64,
+    /// The bytes written to storage so far.
+    pub write_bytes: u64,
+}
+
+impl Sample {
+    /// The highest value of every field over a series of samples.
+    pub fn peaks(samples: &[Sample]) -> Sample {
+        samples
+            .iter()
+            .fold(Sample::default(), |peak, sample| Sample {
+                millis: peak.millis.max(sample.millis),
+                processes: peak.processes.max(sample.processes),
+                threads: peak.threads.max(sample.threads),
+                rss_kb: peak.rss_kb.max(sample.rss_kb),
+                cpu_millis: peak.cpu_millis.max(sample.cpu_millis),
+                read_bytes: peak.read_bytes.max(sample.read_bytes),
+                write_bytes: peak.write_bytes.max(sample.write_bytes),
+            })
+    }
+}
+
+/// Read the series of samples that the wrapper wrote for a run.
+pub fn read_samples(path: &Path) -> Result<Vec<Sample>> {
+    let mut reader = csv::Reader::from_path(path).with_context(ctx!(
+        "Could not open the samples at {path:?}", ;
+        "",
+    ))?;
+
+    reader
+        .deserialize()
+        .collect::<Result<Vec<Sample>, csv::Error>>()
+        .with_context(ctx!(
+            "Could not read the samples at {path:?}", ;
+            "",
+        ))
 }
 
 /// Resource usage statistics for a process.
@@ -69,6 +358,35 @@ pub struct RUsage {
     pub nivcsw: usize,
 }
 
+impl Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Termination::Exited { code } => write!(f, "exit code {code}"),
+            Termination::Signalled {
+                signal,
+                core_dumped,
+            } => {
+                write!(f, "killed by {}", signal_name(*signal))?;
+
+                if *core_dumped {
+                    write!(f, " (core dumped)")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitExceeded::Time => write!(f, "timed out"),
+            LimitExceeded::Memory => write!(f, "out of memory"),
+        }
+    }
+}
+
 impl Display for RUsage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(
@@ -100,3 +418,7 @@ impl Display for RUsage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+#[path = "tests/measurement.rs"]
+mod tests;
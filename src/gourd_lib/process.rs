@@ -0,0 +1,89 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A process recorded by a local experiment, told apart from a later process
+/// that reuses its pid by the time at which it started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedProcess {
+    /// The pid of the process.
+    pub pid: u32,
+
+    /// When the process started, in clock ticks since boot, if the system
+    /// tells.
+    pub start: Option<u64>,
+}
+
+impl RecordedProcess {
+    /// The process with this pid, as it is running now.
+    pub fn of(pid: u32) -> Self {
+        Self {
+            pid,
+            start: start_time(pid),
+        }
+    }
+
+    /// Record the process in a file.
+    pub fn record(&self, path: &Path) -> io::Result<()> {
+        match self.start {
+            Some(start) => fs::write(path, format!("{} {start}", self.pid)),
+            None => fs::write(path, self.pid.to_string()),
+        }
+    }
+
+    /// Read a process recorded in a file, if there is one.
+    pub fn read(path: &Path) -> Option<Self> {
+        let recorded = fs::read_to_string(path).ok()?;
+        let mut fields = recorded.split_whitespace();
+
+        let pid = fields.next()?.parse().ok().filter(|pid| *pid > 0)?;
+        let start = match fields.next() {
+            Some(start) => Some(start.parse().ok()?),
+            None => None,
+        };
+
+        Some(Self { pid, start })
+    }
+
+    /// Whether the recorded process is still running.
+    ///
+    /// Where the system tells when processes started, a process that only has
+    /// the same pid is not the recorded one. Elsewhere, any process with the
+    /// pid is.
+    pub fn is_running(&self) -> bool {
+        match (self.start, start_time(self.pid)) {
+            (Some(recorded), Some(current)) => recorded == current,
+            (None, None) => exists(self.pid),
+            _ => false,
+        }
+    }
+}
+
+/// When the process with this pid started, in clock ticks since boot, if it
+/// exists and the system tells.
+fn start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+    // The name of the program can contain spaces and parentheses, the start
+    // time is the 22nd field and the 20th after the name.
+    let (_, fields) = stat.rsplit_once(')')?;
+
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Whether a process with this pid exists.
+#[cfg(unix)]
+fn exists(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists.
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+/// Whether a process with this pid exists.
+#[cfg(not(unix))]
+fn exists(_: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+#[path = "tests/process.rs"]
+mod tests;
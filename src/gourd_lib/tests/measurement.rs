@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::*;
+
+#[test]
+fn parse_custom_metrics_test() {
+    let report = "# written by the solver\n\
+        nodes = 1200\n\
+        \n\
+        objective=-3.5\n\
+        nodes = 1337\n";
+
+    let mut expected = BTreeMap::new();
+    expected.insert("nodes".to_string(), 1337.0);
+    expected.insert("objective".to_string(), -3.5);
+
+    assert_eq!(expected, parse_custom_metrics(report).unwrap());
+}
+
+#[test]
+fn parse_custom_metrics_invalid_test() {
+    assert!(parse_custom_metrics("nodes 1200").is_err());
+    assert!(parse_custom_metrics(" = 5").is_err());
+    assert!(parse_custom_metrics("solver = simplex").is_err());
+}
+
+#[test]
+fn custom_metrics_roundtrip_test() {
+    let mut custom_metrics = BTreeMap::new();
+    custom_metrics.insert("iterations".to_string(), 42.0);
+
+    let metrics = Metrics::Done(Measurement {
+        wall_micros: Duration::from_millis(20),
+        termination: Termination::Exited { code: 0 },
+        rusage: None,
+        limit_exceeded: None,
+        counters: None,
+        custom_metrics,
+        setup: None,
+        teardown: None,
+    });
+
+    let serialized = toml::to_string(&metrics).unwrap();
+
+    assert_eq!(metrics, toml::from_str(&serialized).unwrap());
+}
+
+#[test]
+fn termination_from_exit_code_test() {
+    // The metrics of a run, as recorded before signals were.
+    let recorded = r#"
+        type = "Done"
+        exit_code = 3
+
+        [wall_micros]
+        secs = 0
+        nanos = 20000000
+    "#;
+
+    let Metrics::Done(measurement) = toml::from_str(recorded).unwrap() else {
+        panic!("The metrics are not done");
+    };
+
+    assert_eq!(measurement.termination, Termination::Exited { code: 3 });
+    assert_eq!(measurement.wall_micros, Duration::from_millis(20));
+}
+
+#[test]
+fn sample_peaks_test() {
+    let samples = [
+        Sample {
+            millis: 0,
+            processes: 1,
+            threads: 1,
+            rss_kb: 300,
+            ..Default::default()
+        },
+        Sample {
+            millis: 100,
+            processes: 3,
+            threads: 2,
+            rss_kb: 200,
+            cpu_millis: 80,
+            read_bytes: 4096,
+            write_bytes: 0,
+        },
+    ];
+
+    assert_eq!(
+        Sample {
+            millis: 100,
+            processes: 3,
+            threads: 2,
+            rss_kb: 300,
+            cpu_millis: 80,
+            read_bytes: 4096,
+            write_bytes: 0,
+        },
+        Sample::peaks(&samples)
+    );
+    assert_eq!(Sample::default(), Sample::peaks(&[]));
+}
+
+#[test]
+fn read_samples_test() {
+    let tempdir = tempdir::TempDir::new("samples_test").unwrap();
+    let path = tempdir.path().join("samples.csv");
+
+    std::fs::write(
+        &path,
+        "millis,processes,threads,rss_kb,cpu_millis,read_bytes,write_bytes\n\
+        0,1,1,1024,0,0,0\n\
+        100,2,3,2048,90,512,64\n",
+    )
+    .unwrap();
+
+    let samples = read_samples(&path).unwrap();
+
+    assert_eq!(2, samples.len());
+    assert_eq!(2048, samples[1].rss_kb);
+    assert!(read_samples(&tempdir.path().join("missing.csv")).is_err());
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::process;
+
+use tempdir::TempDir;
+
+use super::*;
+
+#[test]
+fn record_and_read_test() {
+    let tmp = TempDir::new("process").unwrap();
+    let path = tmp.path().join("recorded.pid");
+
+    let current = RecordedProcess::of(process::id());
+    current.record(&path).unwrap();
+
+    assert_eq!(Some(current), RecordedProcess::read(&path));
+    assert!(current.is_running());
+}
+
+#[test]
+fn read_without_start_test() {
+    let tmp = TempDir::new("process").unwrap();
+    let path = tmp.path().join("recorded.pid");
+
+    fs::write(&path, "1234\n").unwrap();
+    assert_eq!(
+        Some(RecordedProcess {
+            pid: 1234,
+            start: None
+        }),
+        RecordedProcess::read(&path)
+    );
+
+    fs::write(&path, "0").unwrap();
+    assert_eq!(None, RecordedProcess::read(&path));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reused_pid_test() {
+    // A process that started at another time is a later process with the
+    // same pid.
+    let reused = RecordedProcess {
+        start: RecordedProcess::of(process::id())
+            .start
+            .map(|start| start + 1),
+        ..RecordedProcess::of(process::id())
+    };
+
+    assert!(!reused.is_running());
+}
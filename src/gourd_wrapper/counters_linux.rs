@@ -0,0 +1,168 @@
+#![cfg(target_os = "linux")]
+
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::FromRawFd;
+use std::ptr::addr_of;
+
+use gourd_lib::measurement::Counters;
+
+/// `PERF_TYPE_HARDWARE` from `linux/perf_event.h`.
+const PERF_TYPE_HARDWARE: u32 = 0;
+
+/// `PERF_TYPE_SOFTWARE` from `linux/perf_event.h`.
+const PERF_TYPE_SOFTWARE: u32 = 1;
+
+/// `PERF_COUNT_HW_CPU_CYCLES` from `linux/perf_event.h`.
+const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+
+/// `PERF_COUNT_HW_INSTRUCTIONS` from `linux/perf_event.h`.
+const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+
+/// `PERF_COUNT_HW_CACHE_MISSES` from `linux/perf_event.h`.
+const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+
+/// `PERF_COUNT_HW_BRANCH_MISSES` from `linux/perf_event.h`.
+const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+
+/// `PERF_COUNT_SW_PAGE_FAULTS` from `linux/perf_event.h`.
+const PERF_COUNT_SW_PAGE_FAULTS: u64 = 2;
+
+/// `PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING`.
+const READ_FORMAT: u64 = 0b11;
+
+/// The `disabled`, `inherit`, `exclude_kernel`, `exclude_hv` and
+/// `enable_on_exec` bits of the attribute flags.
+///
+/// Excluding the kernel allows unprivileged users to count their own
+/// programs with the default `perf_event_paranoid` setting.
+const FLAGS: u64 = 1 | 1 << 1 | 1 << 5 | 1 << 6 | 1 << 12;
+
+/// `PERF_FLAG_FD_CLOEXEC`, so that the program does not inherit the counters.
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+/// The first version of `struct perf_event_attr` from `linux/perf_event.h`,
+/// which every kernel that has `perf_event_open` accepts.
+#[repr(C)]
+#[derive(Debug, Default)]
+struct PerfEventAttr {
+    /// The type of the event.
+    kind: u32,
+    /// The size of this structure.
+    size: u32,
+    /// Which event of the type to count.
+    config: u64,
+    /// Unused, for sampling.
+    sample_period: u64,
+    /// Unused, for sampling.
+    sample_type: u64,
+    /// What a read of the counter returns.
+    read_format: u64,
+    /// The bitfield of boolean attributes.
+    flags: u64,
+    /// Unused, for sampling.
+    wakeup_events: u32,
+    /// Unused, for breakpoints.
+    bp_type: u32,
+    /// Unused, for extended events.
+    config1: u64,
+}
+
+/// The performance counters of the programs started by the wrapper.
+#[derive(Debug)]
+pub struct CounterSet {
+    /// The counters in the order of [Counters::named], `None` if the system
+    /// does not allow one to be opened.
+    counters: [Option<File>; 5],
+}
+
+impl CounterSet {
+    /// Open the counters for the wrapper and every process it starts from
+    /// now on.
+    ///
+    /// A counter is only enabled once a process executes a program, so the
+    /// wrapper itself is never counted. The counts of the program and its
+    /// children are added to the counter of the wrapper when they exit.
+    pub fn open() -> Self {
+        Self {
+            counters: [
+                (PERF_TYPE_HARDWARE, PERF_COUNT_HW_INSTRUCTIONS),
+                (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CPU_CYCLES),
+                (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CACHE_MISSES),
+                (PERF_TYPE_HARDWARE, PERF_COUNT_HW_BRANCH_MISSES),
+                (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_PAGE_FAULTS),
+            ]
+            .map(|(kind, config)| open_counter(kind, config)),
+        }
+    }
+
+    /// Read the counters, after the program has exited and was reaped.
+    pub fn read(self) -> Counters {
+        let [instructions, cycles, cache_misses, branch_misses, page_faults] =
+            self.counters.map(|counter| counter.and_then(read_counter));
+
+        Counters {
+            instructions,
+            cycles,
+            cache_misses,
+            branch_misses,
+            page_faults,
+        }
+    }
+}
+
+/// Open a single counter, returns `None` if the system does not allow it.
+fn open_counter(kind: u32, config: u64) -> Option<File> {
+    let attr = PerfEventAttr {
+        kind,
+        size: size_of::<PerfEventAttr>() as u32,
+        config,
+        read_format: READ_FORMAT,
+        flags: FLAGS,
+        ..Default::default()
+    };
+
+    // SAFETY: `attr` is a valid `perf_event_attr` of the size that it
+    // reports, and the kernel does not keep the pointer after the call.
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_perf_event_open,
+            addr_of!(attr),
+            0 as libc::pid_t,
+            -1 as libc::c_int,
+            -1 as libc::c_int,
+            PERF_FLAG_FD_CLOEXEC,
+        )
+    };
+
+    if fd < 0 {
+        return None;
+    }
+
+    // SAFETY: The kernel returned a new file descriptor that nothing else owns.
+    Some(unsafe { File::from_raw_fd(fd as libc::c_int) })
+}
+
+/// Read a counter, scaled up if the kernel had to share the hardware between
+/// more counters than it has.
+fn read_counter(mut counter: File) -> Option<u64> {
+    let mut buffer = [0u8; 24];
+    counter.read_exact(&mut buffer).ok()?;
+
+    let [value, enabled, running] = [0, 8, 16].map(|offset| {
+        u64::from_ne_bytes(
+            buffer[offset..offset + 8]
+                .try_into()
+                .expect("slices of eight bytes fit in a u64"),
+        )
+    });
+
+    if running == 0 {
+        // The counter never got the hardware to count the program.
+        None
+    } else if running < enabled {
+        Some((value as u128 * enabled as u128 / running as u128) as u64)
+    } else {
+        Some(value)
+    }
+}
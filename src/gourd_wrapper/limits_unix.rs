@@ -0,0 +1,230 @@
+#![cfg(unix)]
+
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::Child;
+use std::process::Command;
+use std::ptr::addr_of_mut;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use gourd_lib::config::slurm::ResourceLimits;
+use gourd_lib::constants::LIMIT_KILL_GRACE;
+use gourd_lib::constants::LIMIT_POLL_PERIOD;
+use gourd_lib::measurement::LimitExceeded;
+
+/// The amount of memory in bytes that a program with these limits may use.
+///
+/// Like in Slurm, this is the memory per cpu times the amount of cpus.
+fn memory_limit(limits: &ResourceLimits) -> u64 {
+    (limits.cpus * limits.mem_per_cpu) as u64 * 1024 * 1024
+}
+
+/// Put the spawned program in its own process group, so that it can be
+/// stopped and signalled together with anything it has spawned.
+///
+/// Both the [Watchdog] and cancelling a local run rely on this.
+pub fn own_group(cmd: &mut Command) {
+    cmd.process_group(0);
+}
+
+/// Make the kernel enforce the resource limits on the spawned program, which
+/// has to be in its own process group (see [own_group]).
+///
+/// Its CPU time is limited to the time limit times the amount of cpus. On
+/// Linux the memory is enforced by the [Watchdog], elsewhere the address space
+/// of the program is limited instead.
+pub fn apply_rlimits(cmd: &mut Command, limits: &ResourceLimits) {
+    let cpu_seconds = limits.time_limit.as_millis().div_ceil(1000) as u64 * limits.cpus as u64;
+
+    #[cfg(not(target_os = "linux"))]
+    let address_space = memory_limit(limits);
+
+    // SAFETY: The closure runs in the forked child right before `exec`,
+    // where only async-signal-safe functions may be called.
+    // `setrlimit` is async-signal-safe, and the closure does not allocate.
+    unsafe {
+        cmd.pre_exec(move || {
+            let set_limit = |resource, soft: u64, hard: u64| {
+                let limit = libc::rlimit {
+                    rlim_cur: soft as libc::rlim_t,
+                    rlim_max: hard as libc::rlim_t,
+                };
+
+                if libc::setrlimit(resource, &limit) != 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                }
+            };
+
+            set_limit(
+                libc::RLIMIT_CPU,
+                cpu_seconds,
+                cpu_seconds + LIMIT_KILL_GRACE.as_secs(),
+            )?;
+
+            // Not all systems enforce (or accept) this limit, so it is best effort.
+            #[cfg(not(target_os = "linux"))]
+            let _ = set_limit(libc::RLIMIT_AS, address_space, address_space);
+
+            Ok(())
+        });
+    }
+}
+
+/// Block until the program exits, without reaping it.
+///
+/// As long as the program is not reaped its process id cannot be reused,
+/// so the [Watchdog] can never signal an unrelated process.
+pub fn wait_for_exit(child: &Child) -> Result<()> {
+    loop {
+        // SAFETY: An all-zero `siginfo_t` is a valid value,
+        // `waitid` only writes to it.
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+
+        // SAFETY: `info` is a valid pointer for the duration of the call.
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                child.id() as libc::id_t,
+                addr_of_mut!(info),
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+
+        if res == 0 {
+            return Ok(());
+        }
+
+        let err = io::Error::last_os_error();
+
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err).context("Could not wait for the program to exit");
+        }
+    }
+}
+
+/// Watches a running program and stops it once it exceeds its limits.
+#[derive(Debug)]
+pub struct Watchdog {
+    /// Used to tell the watchdog that the program has exited.
+    exited: Sender<()>,
+
+    /// The watchdog thread, returns the limit that was exceeded.
+    thread: JoinHandle<Option<LimitExceeded>>,
+}
+
+impl Watchdog {
+    /// Start watching the program, which has to be spawned with
+    /// [own_group] and [apply_rlimits].
+    pub fn start(child: &Child, limits: ResourceLimits) -> Self {
+        let (exited, receiver) = mpsc::channel();
+        let pid = child.id() as libc::pid_t;
+
+        Self {
+            exited,
+            thread: thread::spawn(move || watch(pid, limits, receiver)),
+        }
+    }
+
+    /// Stop watching the program and return the limit it exceeded, if any.
+    ///
+    /// This has to be called after [wait_for_exit] and before the program is
+    /// reaped.
+    pub fn stop(self) -> Result<Option<LimitExceeded>> {
+        // If the watchdog already stopped the program nobody is listening.
+        let _ = self.exited.send(());
+
+        self.thread
+            .join()
+            .map_err(|_| anyhow!("The resource limit watchdog has crashed"))
+    }
+}
+
+/// The body of the [Watchdog] thread.
+fn watch(pid: libc::pid_t, limits: ResourceLimits, exited: Receiver<()>) -> Option<LimitExceeded> {
+    let deadline = Instant::now() + limits.time_limit;
+
+    loop {
+        if exited.recv_timeout(LIMIT_POLL_PERIOD) != Err(RecvTimeoutError::Timeout) {
+            return None;
+        }
+
+        #[cfg(target_os = "linux")]
+        if group_resident_memory(pid).is_some_and(|rss| rss > memory_limit(&limits)) {
+            signal_group(pid, libc::SIGKILL);
+
+            return Some(LimitExceeded::Memory);
+        }
+
+        if Instant::now() >= deadline {
+            signal_group(pid, libc::SIGTERM);
+
+            if exited.recv_timeout(LIMIT_KILL_GRACE) == Err(RecvTimeoutError::Timeout) {
+                signal_group(pid, libc::SIGKILL);
+            }
+
+            return Some(LimitExceeded::Time);
+        }
+    }
+}
+
+/// Send a signal to the process group of the program.
+fn signal_group(pid: libc::pid_t, signal: libc::c_int) {
+    // SAFETY: `kill` does not touch our memory. The program is not reaped
+    // while the watchdog runs, so the group still belongs to it.
+    unsafe {
+        libc::kill(-pid, signal);
+    }
+}
+
+/// The resident set size of all processes in the process group `group` in
+/// bytes, read from `/proc`.
+#[cfg(target_os = "linux")]
+fn group_resident_memory(group: libc::pid_t) -> Option<u64> {
+    let processes = std::fs::read_dir("/proc").ok()?;
+
+    Some(
+        processes
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .filter(|pid| process_group(*pid) == Some(group))
+            .filter_map(resident_memory)
+            .sum(),
+    )
+}
+
+/// The process group of a process, read from `/proc`.
+#[cfg(target_os = "linux")]
+fn process_group(pid: libc::pid_t) -> Option<libc::pid_t> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+    // The name of the process is in parentheses and can contain anything,
+    // the state, parent and group follow it.
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(2)?
+        .parse()
+        .ok()
+}
+
+/// The resident set size of a process in bytes, read from `/proc`.
+#[cfg(target_os = "linux")]
+fn resident_memory(pid: libc::pid_t) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|kilobytes| kilobytes.trim().trim_end_matches("kB").trim().parse().ok())
+        .map(|kilobytes: u64| kilobytes * 1024)
+}
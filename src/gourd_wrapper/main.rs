@@ -11,13 +11,30 @@
 /// Measurements for unix-like systems.
 mod measurement_unix;
 
+/// Resource limit enforcement for unix-like systems.
+mod limits_unix;
+
+/// Forwarding of signals to the program for unix-like systems.
+mod signals_unix;
+
+/// Resource usage sampling for Linux.
+mod sampling_linux;
+
+/// Performance counters for Linux.
+mod counters_linux;
+
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::exit;
 use std::process::Command;
 use std::process::Stdio;
+use std::time::Duration;
 use std::time::Instant;
 
 use anstyle::Color;
@@ -25,13 +42,29 @@ use anstyle::Style;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
+use gourd_lib::config::slurm::ResourceLimits;
+use gourd_lib::constants::CUSTOM_METRICS_ENV;
+use gourd_lib::constants::FILE_PLACEHOLDER_PREFIX;
+use gourd_lib::constants::GENERATED_INPUT_FILE;
+use gourd_lib::constants::INPUT_PLACEHOLDER;
+use gourd_lib::constants::INPUT_STEM_PLACEHOLDER;
+use gourd_lib::constants::RUN_ID_PLACEHOLDER;
+use gourd_lib::constants::SEED_PLACEHOLDER;
+use gourd_lib::constants::WORK_DIR_PLACEHOLDER;
 use gourd_lib::ctx;
+use gourd_lib::experiment::inputs::referenced_files;
+use gourd_lib::experiment::Environment;
 use gourd_lib::experiment::Experiment;
+use gourd_lib::experiment::Run;
 use gourd_lib::file_system::FileOperations;
 use gourd_lib::file_system::FileSystemInteractor;
+use gourd_lib::measurement::parse_custom_metrics;
+use gourd_lib::measurement::LimitExceeded;
 use gourd_lib::measurement::Measurement;
 use gourd_lib::measurement::Metrics;
 use gourd_lib::measurement::RUsage;
+use gourd_lib::measurement::Termination;
+use gourd_lib::process::RecordedProcess;
 
 /// How to style the errors.
 const ERROR_STYLE: Style = anstyle::Style::new()
@@ -58,8 +91,36 @@ struct RunConf {
     result_path: PathBuf,
     /// The path to the stderr file.
     err_path: PathBuf,
+    /// The path where the program can report custom metrics, if it can.
+    custom_metrics_path: Option<PathBuf>,
     /// Additional arguments.
     additional_args: Vec<String>,
+    /// The resource limits to enforce, when not running on Slurm and they
+    /// were configured.
+    limits: Option<ResourceLimits>,
+    /// Whether the program runs in its own process group, which receives the
+    /// signals that stop the wrapper, when not running on Slurm.
+    ///
+    /// Cancelling a local run signals this group, so every local run has it,
+    /// whether or not limits are enforced.
+    own_group: bool,
+    /// How often to sample the resource usage, and where to write the samples.
+    sampling: Option<(Duration, PathBuf)>,
+    /// Whether to collect performance counters.
+    counters: bool,
+    /// The command that generates the input, and the file to generate it in.
+    generator: Option<(Vec<String>, PathBuf)>,
+    /// The command to run before the program, if any.
+    setup: Vec<String>,
+    /// The command to run after the program, if any.
+    teardown: Vec<String>,
+    /// The environment variables to set for the program and its hooks.
+    env: BTreeMap<String, String>,
+    /// Whether the program and its hooks start from an empty environment.
+    clear_env: bool,
+    /// Where to record the pid of the program while it runs, when running
+    /// locally.
+    program_pid_path: Option<PathBuf>,
 }
 
 fn main() {
@@ -82,27 +143,71 @@ fn process() -> Result<()> {
 
     let fs = FileSystemInteractor { dry_run: false };
 
-    let rc = match args.len() {
-        4 => process_args(&args, &fs)?,
+    let (exp, run_id) = match args.len() {
+        4 => find_run(&args, &fs)?,
         _ => bail!("gourd_wrapper needs an experiment file path, a chunk index and a task index"),
     };
 
-    fs::write(
-        &rc.result_path,
-        toml::to_string(&Metrics::NotCompleted)
-            .context("Could not serialize the Not Completed metrics state")?,
-    )
-    .context(format!(
-        "Could not write to the result file {:?}",
-        rc.result_path
-    ))?;
+    let result_path = &exp.runs[run_id].metrics_path;
+
+    process_args(&exp, run_id).and_then(run).inspect_err(|err| {
+        // Recording the failure is best effort, the error is reported either way.
+        let _ = write_metrics(
+            result_path,
+            &Metrics::WrapperFailed {
+                reason: format!("{err:#}"),
+            },
+        );
+    })
+}
+
+/// Run the program of a run configuration and measure it.
+fn run(rc: RunConf) -> Result<()> {
+    write_metrics(&rc.result_path, &Metrics::NotCompleted)?;
+
+    if let Some(path) = &rc.custom_metrics_path {
+        File::create(path).context(format!(
+            "Could not truncate the custom metrics file {:?}",
+            path
+        ))?;
+    }
+
+    // Generating the input is not part of the measurement, and neither are
+    // the setup and teardown.
+    if let Some((command, path)) = &rc.generator {
+        generate_input(command, path, &rc.work_dir)?;
+    }
+
+    let setup = run_hook("SETUP", &rc.setup, &rc)?;
+
+    if let Some(termination) = setup.filter(|setup| !setup.is_success()) {
+        let meas = Measurement {
+            wall_micros: Duration::ZERO,
+            termination,
+            rusage: None,
+            limit_exceeded: None,
+            counters: None,
+            custom_metrics: BTreeMap::new(),
+            setup,
+            teardown: run_hook("TEARDOWN", &rc.teardown, &rc)?,
+        };
+
+        return write_metrics(&rc.result_path, &Metrics::Done(meas));
+    }
 
     let clock = start_measuring();
 
     eprintln!("RUNNING {:?}", &rc.binary_path);
     eprintln!("ARGS {:?}", &rc.additional_args);
-    #[allow(unused_mut)]
-    let mut child = Command::new(&rc.binary_path)
+    let mut command = Command::new(&rc.binary_path);
+
+    apply_env(&mut command, &rc);
+
+    if let Some(path) = &rc.custom_metrics_path {
+        command.env(CUSTOM_METRICS_ENV, path);
+    }
+
+    command
         .current_dir(&rc.work_dir)
         .args(&rc.additional_args)
         .stdin(if let Some(actual_input) = rc.input_path.clone() {
@@ -118,42 +223,151 @@ fn process() -> Result<()> {
         )?))
         .stderr(Stdio::from(File::create(rc.err_path.clone()).context(
             format!("Could not truncate the error {:?}", rc.err_path),
-        )?))
+        )?));
+
+    #[cfg(unix)]
+    if rc.own_group {
+        limits_unix::own_group(&mut command);
+        signals_unix::install();
+    }
+
+    #[cfg(unix)]
+    if let Some(limits) = &rc.limits {
+        limits_unix::apply_rlimits(&mut command, limits);
+    }
+
+    // The counters have to exist before the program is started to follow it.
+    #[cfg(target_os = "linux")]
+    let counter_set = rc.counters.then(counters_linux::CounterSet::open);
+    #[cfg(not(target_os = "linux"))]
+    if rc.counters {
+        eprintln!(
+            "{}warning:{:#} Performance counters are only supported on Linux",
+            ERROR_STYLE, ERROR_STYLE
+        );
+    }
+
+    #[allow(unused_mut)]
+    let mut child = command
         .spawn()
         .context(format!("Could not start the binary {:?}", &rc.binary_path))?;
 
+    // The pid only serves to cancel the run, so the run goes on even if it
+    // cannot be recorded.
+    if let Some(path) = &rc.program_pid_path {
+        if let Err(err) = RecordedProcess::of(child.id()).record(path) {
+            eprintln!(
+                "{}warning:{:#} Could not record the pid of the program: {}",
+                ERROR_STYLE, ERROR_STYLE, err
+            );
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    let sampler = match &rc.sampling {
+        Some((interval, path)) => Some(sampling_linux::Sampler::start(&child, *interval, path)?),
+        None => None,
+    };
+    #[cfg(not(target_os = "linux"))]
+    if rc.sampling.is_some() {
+        eprintln!(
+            "{}warning:{:#} Sampling the resource usage is only supported on Linux",
+            ERROR_STYLE, ERROR_STYLE
+        );
+    }
+
     #[cfg(not(unix))]
-    let (rusage_output, exit_code) = (
+    let limit_exceeded = None;
+    #[cfg(unix)]
+    let limit_exceeded = if rc.own_group {
+        let forwarding = signals_unix::Forwarding::start(&child);
+        let watchdog = rc
+            .limits
+            .map(|limits| limits_unix::Watchdog::start(&child, limits));
+
+        limits_unix::wait_for_exit(&child)?;
+        drop(forwarding);
+
+        watchdog
+            .map(limits_unix::Watchdog::stop)
+            .transpose()?
+            .flatten()
+    } else {
+        None
+    };
+
+    #[cfg(not(unix))]
+    let (rusage_output, termination) = (
         None,
-        child
-            .wait()?
-            .code()
-            .context("Failed to retrieve the exit code")?,
+        Termination::Exited {
+            code: child
+                .wait()?
+                .code()
+                .context("Failed to retrieve the exit code")?,
+        },
     );
     #[cfg(unix)]
-    let (rusage_output, exit_code) = {
+    let (rusage_output, termination) = {
         use crate::measurement_unix::GetRUsage;
         child
             .wait_for_rusage()
             .context("Could not rusage the child")?
     };
 
-    let meas = stop_measuring(clock, exit_code, rusage_output);
+    if let Some(path) = &rc.program_pid_path {
+        let _ = fs::remove_file(path);
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(sampler) = sampler {
+        sampler.stop()?;
+    }
+
+    #[cfg(target_os = "linux")]
+    let counters = counter_set.map(counters_linux::CounterSet::read);
+    #[cfg(not(target_os = "linux"))]
+    let counters = None;
+
+    // The kernel sends SIGXCPU once the cpu time limit set by the wrapper is
+    // reached, before the watchdog notices that the time is up.
+    #[cfg(unix)]
+    let limit_exceeded = match (limit_exceeded, termination.signal()) {
+        (None, Some(libc::SIGXCPU)) if rc.limits.is_some() => Some(LimitExceeded::Time),
+        (limit, _) => limit,
+    };
+
+    let mut meas = stop_measuring(clock, termination, rusage_output, limit_exceeded);
+    meas.counters = counters;
 
+    // A malformed report should not lose the rest of the measurement.
+    if let Some(path) = &rc.custom_metrics_path {
+        match read_custom_metrics(path) {
+            Ok(custom_metrics) => meas.custom_metrics = custom_metrics,
+            Err(err) => eprintln!(
+                "{}warning:{:#} Could not read the custom metrics: {:#}",
+                ERROR_STYLE, ERROR_STYLE, err
+            ),
+        }
+    }
+
+    meas.setup = setup;
+    meas.teardown = run_hook("TEARDOWN", &rc.teardown, &rc)?;
+
+    write_metrics(&rc.result_path, &Metrics::Done(meas))
+}
+
+/// Write the metrics of the run to the result file.
+fn write_metrics(path: &Path, metrics: &Metrics) -> Result<()> {
     fs::write(
-        &rc.result_path,
-        toml::to_string(&Metrics::Done(meas)).context("Could not serialize the measurement")?,
+        path,
+        toml::to_string(metrics).context("Could not serialize the measurement")?,
     )
-    .context(format!(
-        "Could not write to the result file {:?}",
-        rc.result_path
-    ))?;
-
-    Ok(())
+    .context(format!("Could not write to the result file {:?}", path))
 }
 
-/// Process the command line arguments passed to the wrapper.
-fn process_args(args: &[String], fs: &impl FileOperations) -> Result<RunConf> {
+/// Find the experiment and the id of the run from the command line arguments
+/// passed to the wrapper.
+fn find_run(args: &[String], fs: &impl FileOperations) -> Result<(Experiment, usize)> {
     let exp_path: PathBuf = args[1]
         .parse()
         .context(format!("The experiment file path is invalid: {}", args[1]))?;
@@ -174,24 +388,185 @@ fn process_args(args: &[String], fs: &impl FileOperations) -> Result<RunConf> {
         "Ensure that Slurm is configured correctly",
     ))?;
 
-    let run = exp.runs[exp.chunks[chunk_id][task_id]].clone();
+    let run_id = *exp
+        .chunks
+        .get(chunk_id)
+        .and_then(|chunk| chunk.get(task_id))
+        .with_context(ctx!(
+            "There is no task {task_id} in chunk {chunk_id} of the experiment", ;
+            "Ensure that Slurm is configured correctly",
+        ))?;
+
+    Ok((exp, run_id))
+}
+
+/// Make the configuration of run `run_id` of the experiment.
+fn process_args(exp: &Experiment, run_id: usize) -> Result<RunConf> {
+    let mut run = exp.runs[run_id].clone();
+
+    let generator = run.input.generator.as_ref().map(|generator| {
+        (
+            generator.command.clone(),
+            run.work_dir.join(GENERATED_INPUT_FILE),
+        )
+    });
+
+    if let Some((_, path)) = &generator {
+        run.input.file = Some(path.clone());
+    }
 
     let program = &exp.get_program(&run)?;
 
     let mut additional_args = program.arguments.clone();
     additional_args.append(&mut run.input.arguments.clone());
 
+    for arg in &mut additional_args {
+        *arg = substitute_placeholders(arg, run_id, &run)?;
+    }
+
+    let setup = program
+        .setup
+        .iter()
+        .map(|arg| substitute_placeholders(arg, run_id, &run))
+        .collect::<Result<_>>()?;
+
+    let teardown = program
+        .teardown
+        .iter()
+        .map(|arg| substitute_placeholders(arg, run_id, &run))
+        .collect::<Result<_>>()?;
+
+    // The variables of the input replace those of the program.
+    let env = program
+        .env
+        .iter()
+        .chain(&run.input.env)
+        .map(|(name, value)| Ok((name.clone(), substitute_placeholders(value, run_id, &run)?)))
+        .collect::<Result<_>>()?;
+
     Ok(RunConf {
         binary_path: program.binary.clone().to_path_buf(),
-        input_path: run.input.file,
+        input_path: run.input.file.filter(|_| program.stdin),
         output_path: run.output_path.clone(),
         result_path: run.metrics_path.clone(),
         work_dir: run.work_dir.clone(),
         err_path: run.err_path.clone(),
+        custom_metrics_path: run.custom_metrics_path.clone(),
         additional_args,
+        limits: run.limits.filter(|_| exp.env == Environment::Local),
+        own_group: exp.env == Environment::Local,
+        sampling: exp.sampling_interval.zip(run.samples_path),
+        counters: exp.performance_counters,
+        generator,
+        setup,
+        teardown,
+        env,
+        clear_env: exp.clear_env,
+        program_pid_path: (exp.env == Environment::Local).then(|| exp.program_pid_file(run_id)),
     })
 }
 
+/// Run the generator `command` in `work_dir`, writing the input that it
+/// generates to `path`.
+fn generate_input(command: &[String], path: &Path, work_dir: &Path) -> Result<()> {
+    eprintln!("GENERATING {:?}", command);
+
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .current_dir(work_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(File::create(path).context(format!(
+            "Could not create the generated input {:?}",
+            path
+        ))?))
+        .status()
+        .context(format!("Could not start the generator {:?}", &command[0]))?;
+
+    if !status.success() {
+        bail!("The generator {:?} failed: {}", command, status);
+    }
+
+    Ok(())
+}
+
+/// Set the environment variables of the run on a `command`.
+fn apply_env(command: &mut Command, rc: &RunConf) {
+    if rc.clear_env {
+        command.env_clear();
+    }
+
+    command.envs(&rc.env);
+}
+
+/// Run the setup or teardown `command` in the working directory of the run,
+/// returning how it terminated, or `None` if there is no such command.
+fn run_hook(name: &str, command: &[String], rc: &RunConf) -> Result<Option<Termination>> {
+    let Some((program, arguments)) = command.split_first() else {
+        return Ok(None);
+    };
+
+    eprintln!("{} {:?}", name, command);
+
+    let mut hook = Command::new(program);
+
+    apply_env(&mut hook, rc);
+
+    let status = hook
+        .args(arguments)
+        .current_dir(&rc.work_dir)
+        .stdin(Stdio::null())
+        .status()
+        .context(format!("Could not start the command {:?}", program))?;
+
+    #[cfg(unix)]
+    if let Some(signal) = status.signal() {
+        return Ok(Some(Termination::Signalled {
+            signal,
+            core_dumped: status.core_dumped(),
+        }));
+    }
+
+    Ok(Some(Termination::Exited {
+        code: status.code().context("Failed to retrieve the exit code")?,
+    }))
+}
+
+/// Replace the placeholders in an argument by their values for this run.
+fn substitute_placeholders(arg: &str, run_id: usize, run: &Run) -> Result<String> {
+    let mut arg = arg
+        .replace(RUN_ID_PLACEHOLDER, &run_id.to_string())
+        .replace(WORK_DIR_PLACEHOLDER, &run.work_dir.to_string_lossy());
+
+    if let Some(seed) = run.seed {
+        arg = arg.replace(SEED_PLACEHOLDER, &seed.to_string());
+    }
+
+    if arg.contains(INPUT_PLACEHOLDER) || arg.contains(INPUT_STEM_PLACEHOLDER) {
+        let Some(input) = &run.input.file else {
+            bail!("The argument {arg:?} refers to the input file, but the run has none");
+        };
+
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+
+        arg = arg
+            .replace(INPUT_PLACEHOLDER, &input.to_string_lossy())
+            .replace(INPUT_STEM_PLACEHOLDER, &stem);
+    }
+
+    for (name, file) in &run.input.files {
+        arg = arg.replace(
+            &format!("{FILE_PLACEHOLDER_PREFIX}{name}}}"),
+            &file.to_string_lossy(),
+        );
+    }
+
+    if let Some(name) = referenced_files(&arg).first() {
+        bail!("The argument {arg:?} refers to the file {name:?}, but the input has no such file");
+    }
+
+    Ok(arg)
+}
+
 /// This is an extensible structure for measuring monotonic metrics.
 struct Clock {
     /// The real-world time this program took to execute.
@@ -206,10 +581,28 @@ fn start_measuring() -> Clock {
 }
 
 /// Stop a measurement, returns a new instance of a [Measurement]
-fn stop_measuring(clk: Clock, exit_code: i32, rusage: Option<RUsage>) -> Measurement {
+fn stop_measuring(
+    clk: Clock,
+    termination: Termination,
+    rusage: Option<RUsage>,
+    limit_exceeded: Option<LimitExceeded>,
+) -> Measurement {
     Measurement {
         wall_micros: clk.wall_time.elapsed(),
-        exit_code,
+        termination,
         rusage,
+        limit_exceeded,
+        counters: None,
+        custom_metrics: BTreeMap::new(),
+        setup: None,
+        teardown: None,
     }
 }
+
+/// Read the custom metrics that the program reported.
+fn read_custom_metrics(path: &Path) -> Result<BTreeMap<String, f64>> {
+    let report = fs::read_to_string(path)
+        .context(format!("Could not read the custom metrics file {:?}", path))?;
+
+    parse_custom_metrics(&report)
+}
@@ -7,9 +7,15 @@ use std::process::Child;
 use std::ptr::addr_of_mut;
 use std::time::Duration;
 
+use anyhow::anyhow;
 use anyhow::Error;
 use gourd_lib::measurement::RUsage;
+use gourd_lib::measurement::Termination;
+use libc::WCOREDUMP;
+use libc::WEXITSTATUS;
 use libc::WIFEXITED;
+use libc::WIFSIGNALED;
+use libc::WTERMSIG;
 
 /// Returns an empty `libc::rusage` struct.
 unsafe fn empty_raw_rusage() -> libc::rusage {
@@ -17,11 +23,12 @@ unsafe fn empty_raw_rusage() -> libc::rusage {
 }
 
 impl GetRUsage for Child {
-    fn wait_for_rusage(&self) -> Result<(Option<RUsage>, i32), Error> {
+    fn wait_for_rusage(&self) -> Result<(Option<RUsage>, Termination), Error> {
         let pid = self.id() as i32;
         let mut status: i32 = 0;
 
         let mut rusage;
+        let waited;
 
         // SAFETY: Calling libc is always unsafe, we also have to
         // pass in the arguments as pointers for libc to modify them.
@@ -29,34 +36,52 @@ impl GetRUsage for Child {
         // This should be safe as long as libc is correctly loaded.
         unsafe {
             rusage = empty_raw_rusage();
-            libc::wait4(pid, addr_of_mut!(status), 0i32, addr_of_mut!(rusage));
+            waited = libc::wait4(pid, addr_of_mut!(status), 0i32, addr_of_mut!(rusage));
         }
 
-        if WIFEXITED(status) {
-            Ok((
-                Some(RUsage {
-                    utime: duration_from_timeval(rusage.ru_utime),
-                    stime: duration_from_timeval(rusage.ru_stime),
-                    maxrss: rusage.ru_maxrss as usize,
-                    ixrss: rusage.ru_ixrss as usize,
-                    idrss: rusage.ru_idrss as usize,
-                    isrss: rusage.ru_isrss as usize,
-                    minflt: rusage.ru_minflt as usize,
-                    majflt: rusage.ru_majflt as usize,
-                    nswap: rusage.ru_nswap as usize,
-                    inblock: rusage.ru_inblock as usize,
-                    oublock: rusage.ru_oublock as usize,
-                    msgsnd: rusage.ru_msgsnd as usize,
-                    msgrcv: rusage.ru_msgrcv as usize,
-                    nsignals: rusage.ru_nsignals as usize,
-                    nvcsw: rusage.ru_nvcsw as usize,
-                    nivcsw: rusage.ru_nivcsw as usize,
-                }),
-                status,
-            ))
-        } else {
-            Ok((None, -33))
+        if waited != pid {
+            return Err(anyhow!(
+                "Could not wait for the program: {}",
+                std::io::Error::last_os_error()
+            ));
         }
+
+        let termination = if WIFEXITED(status) {
+            Termination::Exited {
+                code: WEXITSTATUS(status),
+            }
+        } else if WIFSIGNALED(status) {
+            Termination::Signalled {
+                signal: WTERMSIG(status),
+                core_dumped: WCOREDUMP(status),
+            }
+        } else {
+            return Err(anyhow!(
+                "The program stopped without terminating, wait status {status}"
+            ));
+        };
+
+        Ok((
+            Some(RUsage {
+                utime: duration_from_timeval(rusage.ru_utime),
+                stime: duration_from_timeval(rusage.ru_stime),
+                maxrss: rusage.ru_maxrss as usize,
+                ixrss: rusage.ru_ixrss as usize,
+                idrss: rusage.ru_idrss as usize,
+                isrss: rusage.ru_isrss as usize,
+                minflt: rusage.ru_minflt as usize,
+                majflt: rusage.ru_majflt as usize,
+                nswap: rusage.ru_nswap as usize,
+                inblock: rusage.ru_inblock as usize,
+                oublock: rusage.ru_oublock as usize,
+                msgsnd: rusage.ru_msgsnd as usize,
+                msgrcv: rusage.ru_msgrcv as usize,
+                nsignals: rusage.ru_nsignals as usize,
+                nvcsw: rusage.ru_nvcsw as usize,
+                nivcsw: rusage.ru_nivcsw as usize,
+            }),
+            termination,
+        ))
     }
 }
 
@@ -64,7 +89,7 @@ impl GetRUsage for Child {
 pub trait GetRUsage {
     /// Waits for the process to exit and returns its resource usage statistics.
     /// Works only on linux with wait4 syscall available.
-    fn wait_for_rusage(&self) -> Result<(Option<RUsage>, i32), Error>;
+    fn wait_for_rusage(&self) -> Result<(Option<RUsage>, Termination), Error>;
 }
 
 /// Converts a `libc::timeval` to a `std::time::Duration`.
@@ -0,0 +1,205 @@
+#![cfg(target_os = "linux")]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::process::Child;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use csv::Writer;
+use gourd_lib::measurement::Sample;
+
+/// Periodically samples the resource usage of a running program.
+#[derive(Debug)]
+pub struct Sampler {
+    /// Used to tell the sampler that the program has exited.
+    exited: Sender<()>,
+
+    /// The sampler thread.
+    thread: JoinHandle<Result<()>>,
+}
+
+impl Sampler {
+    /// Start sampling the program every `interval`, writing the samples to
+    /// `path`.
+    pub fn start(child: &Child, interval: Duration, path: &Path) -> Result<Self> {
+        let mut writer = Writer::from_writer(
+            File::create(path)
+                .context(format!("Could not truncate the samples file {:?}", path))?,
+        );
+
+        let (exited, receiver) = mpsc::channel();
+        let pid = child.id() as libc::pid_t;
+        let units = Units::get();
+        let start = Instant::now();
+
+        let thread = thread::spawn(move || loop {
+            let sample = take_sample(pid, start.elapsed(), &units);
+
+            // Once the program is gone there is nothing left to sample.
+            if sample.processes > 0 {
+                writer.serialize(sample)?;
+                writer.flush()?;
+            }
+
+            if receiver.recv_timeout(interval) != Err(RecvTimeoutError::Timeout) {
+                return Ok(());
+            }
+        });
+
+        Ok(Self { exited, thread })
+    }
+
+    /// Stop sampling the program.
+    pub fn stop(self) -> Result<()> {
+        let _ = self.exited.send(());
+
+        self.thread
+            .join()
+            .map_err(|_| anyhow!("The resource sampler has crashed"))?
+            .context("Could not write the resource usage samples")
+    }
+}
+
+/// The units in which `/proc/<pid>/stat` reports its values.
+#[derive(Debug, Clone, Copy)]
+struct Units {
+    /// The amount of clock ticks per second.
+    ticks_per_second: u64,
+
+    /// The size of a memory page in kilobytes.
+    page_kb: u64,
+}
+
+impl Units {
+    /// Get the units of this system.
+    fn get() -> Self {
+        // SAFETY: `sysconf` only reads system configuration.
+        let (ticks, page_size) = unsafe {
+            (
+                libc::sysconf(libc::_SC_CLK_TCK),
+                libc::sysconf(libc::_SC_PAGESIZE),
+            )
+        };
+
+        Self {
+            ticks_per_second: u64::try_from(ticks).unwrap_or(100).max(1),
+            page_kb: u64::try_from(page_size).unwrap_or(4096) / 1024,
+        }
+    }
+}
+
+/// What `/proc/<pid>/stat` tells about a single process.
+#[derive(Debug, Clone, Copy)]
+struct ProcessStat {
+    /// The parent of the process.
+    ppid: libc::pid_t,
+
+    /// The user and system cpu time, in clock ticks.
+    cpu_ticks: u64,
+
+    /// The amount of threads.
+    threads: u64,
+
+    /// The resident set size, in pages.
+    rss_pages: u64,
+}
+
+/// Sample the program and all of its descendants.
+fn take_sample(root: libc::pid_t, elapsed: Duration, units: &Units) -> Sample {
+    let mut sample = Sample {
+        millis: elapsed.as_millis() as u64,
+        ..Default::default()
+    };
+
+    for (pid, stat) in process_tree(root) {
+        sample.processes += 1;
+        sample.threads += stat.threads;
+        sample.rss_kb += stat.rss_pages * units.page_kb;
+        sample.cpu_millis += stat.cpu_ticks * 1000 / units.ticks_per_second;
+
+        if let Some((read, written)) = read_io(pid) {
+            sample.read_bytes += read;
+            sample.write_bytes += written;
+        }
+    }
+
+    sample
+}
+
+/// Find the process and all of its descendants.
+fn process_tree(root: libc::pid_t) -> Vec<(libc::pid_t, ProcessStat)> {
+    let mut processes = BTreeMap::new();
+
+    if let Ok(entries) = fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            if let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+                if let Some(stat) = read_stat(pid) {
+                    processes.insert(pid, stat);
+                }
+            }
+        }
+    }
+
+    let mut tree = Vec::new();
+    let mut frontier = vec![root];
+
+    while let Some(parent) = frontier.pop() {
+        if let Some(stat) = processes.get(&parent) {
+            tree.push((parent, *stat));
+        }
+
+        frontier.extend(
+            processes
+                .iter()
+                .filter(|(_, stat)| stat.ppid == parent)
+                .map(|(pid, _)| *pid),
+        );
+    }
+
+    tree
+}
+
+/// Read `/proc/<pid>/stat`.
+fn read_stat(pid: libc::pid_t) -> Option<ProcessStat> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+    // The name of the program may contain spaces and parentheses,
+    // so the fields are counted from its closing parenthesis.
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+
+    let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+
+    Some(ProcessStat {
+        ppid: fields.get(1)?.parse().ok()?,
+        cpu_ticks: field(14)? + field(15)?,
+        threads: field(20)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// Read the bytes read from and written to storage from `/proc/<pid>/io`.
+fn read_io(pid: libc::pid_t) -> Option<(u64, u64)> {
+    let io = fs::read_to_string(format!("/proc/{pid}/io")).ok()?;
+
+    let value = |name: &str| {
+        io.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().parse().ok())
+    };
+
+    Some((value("read_bytes:")?, value("write_bytes:")?))
+}
@@ -0,0 +1,67 @@
+#![cfg(unix)]
+
+use std::process::Child;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::Ordering;
+
+/// The process group of the running program, or 0 while there is none.
+static PROGRAM_GROUP: AtomicI32 = AtomicI32::new(0);
+
+/// The signals that are passed on to the program.
+const FORWARDED: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+/// Pass the interrupt and terminate signals that the wrapper receives on to
+/// the program, for as long as a [Forwarding] exists.
+///
+/// Otherwise these signals stop the wrapper as usual.
+pub fn install() {
+    for signal in FORWARDED {
+        // SAFETY: The handler only calls async-signal-safe functions.
+        unsafe {
+            libc::signal(
+                signal,
+                forward as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+}
+
+/// Forwards signals to the process group of a running program.
+///
+/// The wrapper then keeps running, so it measures how the program ended.
+#[derive(Debug)]
+pub struct Forwarding;
+
+impl Forwarding {
+    /// Start forwarding signals to the program, which has to be spawned in its
+    /// own process group.
+    ///
+    /// Has to be dropped before the program is reaped, so that no signal can
+    /// reach an unrelated process group.
+    pub fn start(child: &Child) -> Self {
+        PROGRAM_GROUP.store(child.id() as i32, Ordering::SeqCst);
+
+        Forwarding
+    }
+}
+
+impl Drop for Forwarding {
+    fn drop(&mut self) {
+        PROGRAM_GROUP.store(0, Ordering::SeqCst);
+    }
+}
+
+/// The signal handler installed by [install].
+extern "C" fn forward(signal: libc::c_int) {
+    let group = PROGRAM_GROUP.load(Ordering::SeqCst);
+
+    // SAFETY: `kill`, `signal` and `raise` are async-signal-safe.
+    unsafe {
+        if group > 0 {
+            libc::kill(-group, signal);
+        } else {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+}
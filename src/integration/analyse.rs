@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use gourd_lib::config::UserInput;
 
 use crate::config;
@@ -17,6 +19,11 @@ fn test_analyse_csv() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     ));
 
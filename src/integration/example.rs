@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use gourd_lib::config::UserInput;
 
 use crate::config;
@@ -18,6 +20,11 @@ fn test_one_run() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     ));
 
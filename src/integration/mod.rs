@@ -149,6 +149,15 @@ fn new_program(
             afterscript: None,
             next: post.map(|p| vec![p.to_string()]).unwrap_or_default(),
             resource_limits: None,
+            repetitions: None,
+            parameters: None,
+            zip: vec![],
+            include: vec![],
+            exclude: vec![],
+            stdin: true,
+            setup: vec![],
+            teardown: vec![],
+            env: BTreeMap::new(),
         },
     );
 }
@@ -274,6 +283,18 @@ macro_rules! config {
                 resource_limits: None,
                 labels: None,
                 warn_on_label_overlap: false,
+                sampling_interval: None,
+                performance_counters: false,
+                repetitions: None,
+                seed: None,
+                search: None,
+                constraints: vec![],
+                zip: vec![],
+                tune: None,
+                compare: gourd_lib::config::compare::Comparison::default(),
+                agreement: None,
+                env: std::collections::BTreeMap::new(),
+                clear_env: false,
             }
         }
     };
@@ -293,6 +314,18 @@ macro_rules! config {
                 resource_limits: None,
                 labels: $label,
                 warn_on_label_overlap: false,
+                sampling_interval: None,
+                performance_counters: false,
+                repetitions: None,
+                seed: None,
+                search: None,
+                constraints: vec![],
+                zip: vec![],
+                tune: None,
+                compare: gourd_lib::config::compare::Comparison::default(),
+                agreement: None,
+                env: std::collections::BTreeMap::new(),
+                clear_env: false,
             }
         }
     };
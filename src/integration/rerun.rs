@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::io::Read;
 use std::io::Write;
 use std::process::Stdio;
@@ -21,6 +22,11 @@ fn test_dry_one_run() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     ));
     let conf_path = save_gourd_toml(&conf, &env.temp_dir);
@@ -47,6 +53,11 @@ fn test_two_one_run() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     ));
     let conf_path = save_gourd_toml(&conf, &env.temp_dir);
@@ -73,12 +84,22 @@ fn test_setting_resource_limits() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["1".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         }),
         ("input_two".to_string(),
         UserInput {
             file: None,
             glob: None,
             fetch: None,group: None,arguments: vec!["2".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         }),
         ("input_five".to_string(),
         UserInput {
@@ -86,6 +107,11 @@ fn test_setting_resource_limits() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["5".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         })
     );
 
@@ -1,4 +1,15 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use gourd_lib::config::slurm::ResourceLimits;
+use gourd_lib::config::Label;
+use gourd_lib::config::Regex;
 use gourd_lib::config::UserInput;
+use gourd_lib::experiment::Run;
+use gourd_lib::measurement::LimitExceeded;
+use gourd_lib::measurement::Measurement;
+use gourd_lib::measurement::Metrics;
+use gourd_lib::measurement::Termination;
 
 use crate::config;
 use crate::gourd;
@@ -28,6 +39,11 @@ fn test_dry_one_run() {
             glob: None,
             fetch: None,
             group: None,arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         },
     ));
 
@@ -39,3 +55,517 @@ fn test_dry_one_run() {
     // check that the output file does not exist
     assert!(read_experiment_from_stdout(&output).is_err());
 }
+
+#[test]
+fn test_setup_and_teardown() {
+    let mut env = init();
+
+    // The teardown only succeeds if the setup staged the fixture in the same
+    // working directory.
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.setup = vec!["touch".to_string(), "fixture".to_string()];
+    fibonacci.teardown = vec!["rm".to_string(), "fixture".to_string()];
+
+    env.programs.get_mut("hello").unwrap().setup = vec!["false".to_string()];
+
+    let conf = config!(&env; "fibonacci", "hello"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s"; "run local");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    let measurement =
+        |run: &Run| match toml::from_str(&std::fs::read_to_string(&run.metrics_path).unwrap())
+            .unwrap()
+        {
+            Metrics::Done(measurement) => measurement,
+            Metrics::NotCompleted => panic!("The run did not complete"),
+            Metrics::WrapperFailed { reason } => panic!("The wrapper failed: {reason}"),
+            Metrics::Cancelled => panic!("The run was cancelled"),
+        };
+
+    for run in &exp.runs {
+        let measurement = measurement(run);
+
+        if exp.get_program(run).unwrap().name == "fibonacci" {
+            assert_eq!(measurement.setup, Some(Termination::Exited { code: 0 }));
+            assert_eq!(measurement.teardown, Some(Termination::Exited { code: 0 }));
+            assert!(measurement.termination.is_success());
+            assert!(!run.work_dir.join("fixture").exists());
+        } else {
+            // The program does not run when its setup fails.
+            assert!(measurement.setup_failed());
+            assert_eq!(measurement.termination, Termination::Exited { code: 1 });
+            assert_eq!(measurement.teardown, None);
+            assert_eq!(measurement.rusage, None);
+        }
+    }
+}
+
+#[test]
+fn test_environment_variables() {
+    let mut env = init();
+
+    // The setup names a file after the variables it sees.
+    let stage = |variables: &str| {
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("touch \"{variables}\""),
+        ]
+    };
+
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.setup = stage("$SCOPE-$GREETING-$HOME");
+    fibonacci.env = BTreeMap::from([("SCOPE".to_string(), "program".to_string())]);
+
+    let mut conf = config!(&env; "fibonacci"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::from([("SCOPE".to_string(), "input".to_string())]),
+        },
+    ), (
+        "input_five".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["5".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    conf.env = BTreeMap::from([("GREETING".to_string(), "hello".to_string())]);
+    conf.clear_env = true;
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s"; "run local");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    for run in &exp.runs {
+        // The input overrides the program, and nothing else is inherited.
+        let staged = match run.generated_from_input.as_ref().unwrap().as_str() {
+            "input_ten" => "input-hello-",
+            _ => "program-hello-",
+        };
+
+        assert!(run.work_dir.join(staged).exists());
+    }
+}
+
+#[test]
+fn test_detached() {
+    let env = init();
+
+    let conf = config!(&env; "fibonacci"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s", "--detach"; "run local detached");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    // The command returns right away, the supervisor finishes the runs later.
+    let done = || {
+        exp.runs.iter().all(|run| {
+            matches!(
+                std::fs::read_to_string(&run.metrics_path)
+                    .ok()
+                    .and_then(|metrics| toml::from_str(&metrics).ok()),
+                Some(Metrics::Done(_))
+            )
+        })
+    };
+
+    for _ in 0..300 {
+        if done() && !exp.supervisor_pid_file().exists() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    assert!(done());
+    assert!(!exp.supervisor_pid_file().exists());
+
+    let log =
+        std::fs::read_to_string(exp.home.join(format!("{}.supervisor.log", exp.seq))).unwrap();
+    assert!(log.contains("finished"));
+}
+
+#[test]
+fn test_cancel_local() {
+    let mut env = init();
+
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.binary = Some("/bin/sh".into());
+    fibonacci.arguments = vec!["-c".to_string(), "sleep 60".to_string()];
+
+    // The argument of the input is the name of the shell.
+    let input = |name: &str| UserInput {
+        file: None,
+        glob: None,
+        fetch: None,
+        group: None,
+        arguments: vec![name.to_string()],
+        repetitions: None,
+        files: BTreeMap::new(),
+        generator: None,
+        expected: None,
+        env: BTreeMap::new(),
+    };
+
+    let conf = config!(&env; "fibonacci"; ("first".to_string(), input("first")), ("second".to_string(), input("second")));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+    let conf_path = conf_path.to_str().unwrap();
+
+    let output =
+        gourd!(env; "-c", conf_path, "run", "local", "-s", "--detach"; "run local detached");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    let metrics = |run: usize| {
+        std::fs::read_to_string(&exp.runs[run].metrics_path)
+            .ok()
+            .and_then(|metrics| toml::from_str::<Metrics>(&metrics).ok())
+    };
+
+    // The runs are sequential, so one of them runs while the other waits.
+    let mut running = None;
+
+    for _ in 0..300 {
+        running = (0..2).find(|run| exp.program_pid_file(*run).exists());
+
+        if running.is_some() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    let running = running.expect("The program never started");
+    let waiting = 1 - running;
+
+    let waiting_id = waiting.to_string();
+    let running_id = running.to_string();
+
+    gourd!(env; "-c", conf_path, "cancel", "-i", &waiting_id; "cancel the waiting run");
+    gourd!(env; "-c", conf_path, "cancel", "-i", &running_id; "cancel the running run");
+
+    assert_eq!(metrics(running), Some(Metrics::Cancelled));
+    assert!(!exp.wrapper_pid_file(running).exists());
+    assert!(!exp.program_pid_file(running).exists());
+
+    // The supervisor passes over the cancelled run and finishes.
+    for _ in 0..300 {
+        if !exp.supervisor_pid_file().exists() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    assert!(!exp.supervisor_pid_file().exists());
+    assert_eq!(metrics(waiting), Some(Metrics::Cancelled));
+
+    let status = gourd!(env; "-c", conf_path, "status"; "status");
+    assert!(String::from_utf8(status.stdout)
+        .unwrap()
+        .contains("cancelled"));
+}
+
+#[test]
+fn test_terminate_supervisor() {
+    let mut env = init();
+
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.binary = Some("/bin/sh".into());
+    fibonacci.arguments = vec!["-c".to_string(), "sleep 60".to_string()];
+
+    let conf = config!(&env; "fibonacci"; (
+        "input".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["input".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s", "--detach"; "run local detached");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    for _ in 0..300 {
+        if exp.program_pid_file(0).exists() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    assert!(
+        exp.program_pid_file(0).exists(),
+        "The program never started"
+    );
+
+    let supervisor = std::fs::read_to_string(exp.supervisor_pid_file()).unwrap();
+    let supervisor = supervisor.split_whitespace().next().unwrap().to_string();
+
+    let terminate = || {
+        assert!(std::process::Command::new("kill")
+            .args(["-TERM", &supervisor])
+            .status()
+            .unwrap()
+            .success());
+    };
+
+    // The first request waits for the running program, the second cancels it.
+    terminate();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    assert!(exp.supervisor_pid_file().exists());
+    terminate();
+
+    for _ in 0..300 {
+        if !exp.supervisor_pid_file().exists() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    assert!(!exp.supervisor_pid_file().exists());
+    assert!(!exp.program_pid_file(0).exists());
+    assert_eq!(
+        std::fs::read_to_string(&exp.runs[0].metrics_path)
+            .ok()
+            .and_then(|metrics| toml::from_str::<Metrics>(&metrics).ok()),
+        Some(Metrics::Cancelled)
+    );
+
+    let log =
+        std::fs::read_to_string(exp.home.join(format!("{}.supervisor.log", exp.seq))).unwrap();
+    assert!(log.contains("stopped"));
+}
+
+#[test]
+fn test_unlimited_local_run() {
+    let mut env = init();
+
+    // More memory than the default limits give a program.
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.binary = Some("/bin/sh".into());
+    fibonacci.arguments = vec![
+        "-c".to_string(),
+        "x=$(head -c 60000000 /dev/zero | tr '\\0' a)".to_string(),
+    ];
+
+    let conf = config!(&env; "fibonacci"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s"; "run local");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    let metrics: Metrics =
+        toml::from_str(&std::fs::read_to_string(&exp.runs[0].metrics_path).unwrap()).unwrap();
+
+    let Metrics::Done(measurement) = metrics else {
+        panic!("The run did not complete: {metrics:?}");
+    };
+
+    assert_eq!(measurement.termination, Termination::Exited { code: 0 });
+    assert_eq!(measurement.limit_exceeded, None);
+}
+
+/// Run a shell command as a local run with these limits, and measure it.
+fn limited_run(command: &str, limits: ResourceLimits) -> Measurement {
+    let mut env = init();
+
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.binary = Some("/bin/sh".into());
+    fibonacci.arguments = vec!["-c".to_string(), command.to_string()];
+    fibonacci.resource_limits = Some(limits.into());
+
+    let conf = config!(&env; "fibonacci"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s"; "run local");
+
+    let exp = read_experiment_from_stdout(&output).unwrap();
+
+    let metrics: Metrics =
+        toml::from_str(&std::fs::read_to_string(&exp.runs[0].metrics_path).unwrap()).unwrap();
+
+    let Metrics::Done(measurement) = metrics else {
+        panic!("The run did not complete: {metrics:?}");
+    };
+
+    measurement
+}
+
+#[test]
+fn test_time_limit_exceeded() {
+    let measurement = limited_run(
+        "sleep 30",
+        ResourceLimits {
+            time_limit: Duration::from_secs(1),
+            cpus: 1,
+            mem_per_cpu: 512,
+        },
+    );
+
+    assert_eq!(measurement.limit_exceeded, Some(LimitExceeded::Time));
+    assert!(!measurement.termination.is_success());
+    assert!(measurement.wall_micros < Duration::from_secs(20));
+}
+
+#[test]
+fn test_memory_limit_exceeded() {
+    // Holds about 200MB, well past the 32MB limit.
+    let measurement = limited_run(
+        "x=$(head -c 200000000 /dev/zero | tr '\\0' a); sleep 5",
+        ResourceLimits {
+            time_limit: Duration::from_secs(60),
+            cpus: 1,
+            mem_per_cpu: 32,
+        },
+    );
+
+    assert_eq!(measurement.limit_exceeded, Some(LimitExceeded::Memory));
+    assert!(!measurement.termination.is_success());
+}
+
+#[test]
+fn test_label_by_termination() {
+    let mut env = init();
+
+    let fibonacci = env.programs.get_mut("fibonacci").unwrap();
+    fibonacci.binary = Some("/bin/sh".into());
+    fibonacci.arguments = vec!["-c".to_string(), "kill -TERM $$".to_string()];
+
+    let conf = config!(&env; "fibonacci"; (
+        "input_ten".to_string(),
+        UserInput {
+            file: None,
+            glob: None,
+            fetch: None,
+            group: None,
+            arguments: vec!["10".to_string()],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
+        },
+    ); Some(BTreeMap::from([(
+        "terminated".to_string(),
+        Label {
+            regex: Regex::from(regex_lite::Regex::new("killed by SIGTERM").unwrap()),
+            priority: 0,
+            rerun_by_default: false,
+        },
+    )])));
+
+    let conf_path = save_gourd_toml(&conf, &env.temp_dir);
+
+    let _ = gourd!(env; "-c", conf_path.to_str().unwrap(), "run", "local", "-s"; "run local");
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "status", "-s"; "status");
+
+    let text_out = std::str::from_utf8(output.stdout.as_slice()).unwrap();
+    assert!(text_out.contains("terminated"), "{text_out}");
+
+    let output = gourd!(env; "-c", conf_path.to_str().unwrap(), "status", "-s", "-i", "0"; "status of the run");
+
+    let text_out = std::str::from_utf8(output.stdout.as_slice()).unwrap();
+    assert!(
+        text_out.contains("termination assigned label"),
+        "{text_out}"
+    );
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use flate2::bufread::GzDecoder;
@@ -34,6 +35,15 @@ fn test_repo_commit() {
             afterscript: None,
             resource_limits: None,
             next: vec![],
+            repetitions: None,
+            parameters: None,
+            zip: vec![],
+            include: vec![],
+            exclude: vec![],
+            stdin: true,
+            setup: vec![],
+            teardown: vec![],
+            env: BTreeMap::new(),
         },
     );
 
@@ -21,11 +21,21 @@ fn gourd_run_test() {
         UserInput {
             file: Some(env.temp_dir.path().join("input_ten")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         }),
         ("input_hello".to_string(),
         UserInput {
             file: Some(env.temp_dir.path().join("input_hello")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         });
         Some(BTreeMap::from([(
             "correct".to_string(),
@@ -67,11 +77,21 @@ fn gourd_status_test() {
         UserInput {
             file: Some(env.temp_dir.path().join("input_ten")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         }),
         ("input_hello".to_string(),
         UserInput {
             file: Some(env.temp_dir.path().join("input_hello")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         });
         Some(BTreeMap::from([(
             "correct".to_string(),
@@ -88,6 +108,11 @@ fn gourd_status_test() {
         UserInput {
             file: Some(env.temp_dir.path().join("input_ten")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         })
     );
 
@@ -153,11 +178,21 @@ fn gourd_rerun_test() {
         UserInput {
             file: Some(env.temp_dir.path().join("input_ten")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         }),
         ("input_hello".to_string(),
         UserInput {
             file: Some(env.temp_dir.path().join("input_hello")),
             glob: None,fetch: None,group: None,arguments: vec![],
+            repetitions: None,
+            files: BTreeMap::new(),
+            generator: None,
+            expected: None,
+            env: BTreeMap::new(),
         });
         Some(BTreeMap::from([(
             "correct".to_string(),